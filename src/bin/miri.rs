@@ -477,10 +477,16 @@ fn main() {
             miri_config.collect_leak_backtraces = false;
         } else if arg == "-Zmiri-panic-on-unsupported" {
             miri_config.panic_on_unsupported = true;
+        } else if arg == "-Zmiri-report-unsupported-intrinsics" {
+            miri_config.report_unsupported_intrinsics = true;
         } else if arg == "-Zmiri-strict-provenance" {
             miri_config.provenance_mode = ProvenanceMode::Strict;
         } else if arg == "-Zmiri-permissive-provenance" {
             miri_config.provenance_mode = ProvenanceMode::Permissive;
+        } else if arg == "-Zmiri-deterministic-float-approximations" {
+            miri_config.deterministic_float_approximations = true;
+        } else if arg == "-Zmiri-per-thread-float-rng" {
+            miri_config.per_thread_float_rng = true;
         } else if arg == "-Zmiri-mute-stdout-stderr" {
             miri_config.mute_stdout_stderr = true;
         } else if arg == "-Zmiri-retag-fields" {
@@ -29,6 +29,8 @@ enum SchedulingAction {
     ExecuteStep,
     /// Execute a timeout callback.
     ExecuteTimeoutCallback,
+    /// Execute the readiness callbacks of all threads that have one.
+    ExecuteReadinessCallbacks,
     /// Wait for a bit, until there is a timeout to be called.
     Sleep(Duration),
 }
@@ -49,6 +51,8 @@ pub trait MachineCallback<'mir, 'tcx>: VisitProvenance {
 
 type TimeoutCallback<'mir, 'tcx> = Box<dyn MachineCallback<'mir, 'tcx> + 'tcx>;
 
+type ReadinessCallback<'mir, 'tcx> = Box<dyn MachineCallback<'mir, 'tcx> + 'tcx>;
+
 /// A thread identifier.
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct ThreadId(u32);
@@ -108,6 +112,8 @@ pub enum BlockReason {
     Futex { addr: u64 },
     /// Blocked on an InitOnce.
     InitOnce(InitOnceId),
+    /// Waiting for file descriptors to become ready.
+    Readiness,
 }
 
 /// The state of a thread.
@@ -371,6 +377,12 @@ pub struct ThreadManager<'mir, 'tcx> {
     yield_active_thread: bool,
     /// Callbacks that are called once the specified time passes.
     timeout_callbacks: FxHashMap<ThreadId, TimeoutCallbackInfo<'mir, 'tcx>>,
+    /// Callbacks that check whether a blocked thread can continue because of something another
+    /// thread did, like making a file descriptor ready. They are called whenever the scheduler
+    /// has to pick a new thread.
+    readiness_callbacks: FxHashMap<ThreadId, ReadinessCallback<'mir, 'tcx>>,
+    /// Whether the readiness callbacks were already called since the last step was executed.
+    readiness_callbacks_called: bool,
 }
 
 impl VisitProvenance for ThreadManager<'_, '_> {
@@ -379,8 +391,10 @@ impl VisitProvenance for ThreadManager<'_, '_> {
             threads,
             thread_local_alloc_ids,
             timeout_callbacks,
+            readiness_callbacks,
             active_thread: _,
             yield_active_thread: _,
+            readiness_callbacks_called: _,
             sync,
         } = self;
 
@@ -393,6 +407,9 @@ impl VisitProvenance for ThreadManager<'_, '_> {
         for callback in timeout_callbacks.values() {
             callback.callback.visit_provenance(visit);
         }
+        for callback in readiness_callbacks.values() {
+            callback.visit_provenance(visit);
+        }
         sync.visit_provenance(visit);
     }
 }
@@ -409,6 +426,8 @@ impl<'mir, 'tcx> Default for ThreadManager<'mir, 'tcx> {
             thread_local_alloc_ids: Default::default(),
             yield_active_thread: false,
             timeout_callbacks: FxHashMap::default(),
+            readiness_callbacks: FxHashMap::default(),
+            readiness_callbacks_called: false,
         }
     }
 }
@@ -662,6 +681,24 @@ impl<'mir, 'tcx: 'mir> ThreadManager<'mir, 'tcx> {
         self.timeout_callbacks.remove(&thread);
     }
 
+    /// Register the given `callback` to be called whenever the scheduler has to pick a new thread,
+    /// until it is unregistered.
+    ///
+    /// The callback will be called with `thread` being the active thread, and
+    /// the callback may not change the active thread.
+    fn register_readiness_callback(
+        &mut self,
+        thread: ThreadId,
+        callback: ReadinessCallback<'mir, 'tcx>,
+    ) {
+        self.readiness_callbacks.try_insert(thread, callback).unwrap();
+    }
+
+    /// Unregister the readiness callback for the `thread`.
+    fn unregister_readiness_callback_if_exists(&mut self, thread: ThreadId) {
+        self.readiness_callbacks.remove(&thread);
+    }
+
     /// Get a callback that is ready to be called.
     fn get_ready_callback(
         &mut self,
@@ -737,8 +774,15 @@ impl<'mir, 'tcx: 'mir> ThreadManager<'mir, 'tcx> {
             && !self.yield_active_thread
         {
             // The currently active thread is still enabled, just continue with it.
+            self.readiness_callbacks_called = false;
             return Ok(SchedulingAction::ExecuteStep);
         }
+        // The steps executed since the last time we got here might have unblocked some threads
+        // that are waiting on a readiness callback, so give them a chance to check.
+        if !self.readiness_callbacks.is_empty() && !self.readiness_callbacks_called {
+            self.readiness_callbacks_called = true;
+            return Ok(SchedulingAction::ExecuteReadinessCallbacks);
+        }
         // The active thread yielded or got terminated. Let's see if there are any timeouts to take
         // care of. We do this *before* running any other thread, to ensure that timeouts "in the
         // past" fire before any other thread can take an action. This ensures that for
@@ -777,6 +821,7 @@ impl<'mir, 'tcx: 'mir> ThreadManager<'mir, 'tcx> {
         }
         self.yield_active_thread = false;
         if self.threads[self.active_thread].state == ThreadState::Enabled {
+            self.readiness_callbacks_called = false;
             return Ok(SchedulingAction::ExecuteStep);
         }
         // We have not found a thread to execute.
@@ -824,6 +869,31 @@ trait EvalContextPrivExt<'mir, 'tcx: 'mir>: MiriInterpCxExt<'mir, 'tcx> {
         Ok(())
     }
 
+    /// Execute the readiness callbacks, each on its own thread. Callbacks that are registered
+    /// while doing so are only called the next time.
+    fn run_readiness_callbacks(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        // We iterate over all threads in the order of their indices because
+        // this allows us to have a deterministic scheduler.
+        let threads: Vec<ThreadId> = this
+            .machine
+            .threads
+            .threads
+            .indices()
+            .filter(|thread| this.machine.threads.readiness_callbacks.contains_key(thread))
+            .collect();
+        for thread in threads {
+            // An earlier callback may have unregistered this one.
+            let Some(callback) = this.machine.threads.readiness_callbacks.remove(&thread) else {
+                continue;
+            };
+            let old_thread = this.set_active_thread(thread);
+            callback.call(this)?;
+            this.set_active_thread(old_thread);
+        }
+        Ok(())
+    }
+
     #[inline]
     fn run_on_stack_empty(&mut self) -> InterpResult<'tcx, Poll<()>> {
         let this = self.eval_context_mut();
@@ -1070,6 +1140,22 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         this.machine.threads.unregister_timeout_callback_if_exists(thread);
     }
 
+    #[inline]
+    fn register_readiness_callback(
+        &mut self,
+        thread: ThreadId,
+        callback: ReadinessCallback<'mir, 'tcx>,
+    ) {
+        let this = self.eval_context_mut();
+        this.machine.threads.register_readiness_callback(thread, callback);
+    }
+
+    #[inline]
+    fn unregister_readiness_callback_if_exists(&mut self, thread: ThreadId) {
+        let this = self.eval_context_mut();
+        this.machine.threads.unregister_readiness_callback_if_exists(thread);
+    }
+
     /// Run the core interpreter loop. Returns only when an interrupt occurs (an error or program
     /// termination).
     fn run_threads(&mut self) -> InterpResult<'tcx, !> {
@@ -1093,6 +1179,9 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 SchedulingAction::ExecuteTimeoutCallback => {
                     this.run_timeout_callback()?;
                 }
+                SchedulingAction::ExecuteReadinessCallbacks => {
+                    this.run_readiness_callbacks()?;
+                }
                 SchedulingAction::Sleep(duration) => {
                     this.machine.clock.sleep(duration);
                 }
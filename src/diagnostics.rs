@@ -481,6 +481,22 @@ pub fn report_leaks<'mir, 'tcx>(
     }
 }
 
+/// Lists the unsupported x86 intrinsics the program called, if
+/// `-Zmiri-report-unsupported-intrinsics` is set and there were any.
+pub fn report_unsupported_intrinsics<'mir, 'tcx>(
+    ecx: &InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
+) {
+    let Some(unsupported_intrinsics) = &ecx.machine.unsupported_intrinsics else {
+        return;
+    };
+    if unsupported_intrinsics.is_empty() {
+        return;
+    }
+    let names: Vec<_> = unsupported_intrinsics.iter().map(|name| format!("`{name}`")).collect();
+    let names = names.join(", ");
+    ecx.tcx.dcx().note(format!("the program called these unsupported x86 intrinsics: {names}"));
+}
+
 /// Report an error or note (depending on the `error` argument) with the given stacktrace.
 /// Also emits a full stacktrace of the interpreter stack.
 /// We want to present a multi-line span message for some errors. Diagnostics do not support this
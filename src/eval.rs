@@ -8,7 +8,7 @@ use std::task::Poll;
 use std::thread;
 
 use crate::concurrency::thread::TlsAllocAction;
-use crate::diagnostics::report_leaks;
+use crate::diagnostics::{report_leaks, report_unsupported_intrinsics};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::def::Namespace;
 use rustc_hir::def_id::DefId;
@@ -128,6 +128,8 @@ pub struct MiriConfig {
     pub measureme_out: Option<String>,
     /// Panic when unsupported functionality is encountered.
     pub panic_on_unsupported: bool,
+    /// List the distinct unsupported x86 intrinsics the program called when execution ends.
+    pub report_unsupported_intrinsics: bool,
     /// Which style to use for printing backtraces.
     pub backtrace_style: BacktraceStyle,
     /// Which provenance to use for int2ptr casts
@@ -152,10 +154,21 @@ pub struct MiriConfig {
     pub page_size: Option<u64>,
     /// Whether to collect a backtrace when each allocation is created, just in case it leaks.
     pub collect_leak_backtraces: bool,
+    /// Whether intrinsics that approximate their result (like `_mm_rcp_ps`) return the exact
+    /// result instead of one with a small random error.
+    pub deterministic_float_approximations: bool,
+    /// Whether each thread uses its own RNG, seeded from the thread id, for the random error of
+    /// intrinsics that approximate their result. This makes those results independent of how
+    /// threads are interleaved.
+    pub per_thread_float_rng: bool,
     /// Probability for address reuse.
     pub address_reuse_rate: f64,
     /// Probability for address reuse across threads.
     pub address_reuse_cross_thread_rate: f64,
+    /// File descriptors to open before the program starts, in addition to the standard ones.
+    /// Each one is created by calling the given function. This lets embedders provide their own
+    /// `FileDescriptor` implementations.
+    pub preloaded_fds: Vec<(i32, FileDescriptorFactory)>,
 }
 
 impl Default for MiriConfig {
@@ -182,6 +195,7 @@ impl Default for MiriConfig {
             cmpxchg_weak_failure_rate: 0.8, // 80%
             measureme_out: None,
             panic_on_unsupported: false,
+            report_unsupported_intrinsics: false,
             backtrace_style: BacktraceStyle::Short,
             provenance_mode: ProvenanceMode::Default,
             mute_stdout_stderr: false,
@@ -193,8 +207,11 @@ impl Default for MiriConfig {
             num_cpus: 1,
             page_size: None,
             collect_leak_backtraces: true,
+            deterministic_float_approximations: false,
+            per_thread_float_rng: false,
             address_reuse_rate: 0.5,
             address_reuse_cross_thread_rate: 0.1,
+            preloaded_fds: vec![],
         }
     }
 }
@@ -269,6 +286,10 @@ pub fn create_ecx<'mir, 'tcx: 'mir>(
     entry_type: EntryFnType,
     config: &MiriConfig,
 ) -> InterpResult<'tcx, InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>> {
+    if let Some((fd, _)) = config.preloaded_fds.iter().find(|(fd, _)| *fd < 0) {
+        tcx.dcx().fatal(format!("cannot preload file descriptor {fd}: it is negative"));
+    }
+
     let param_env = ty::ParamEnv::reveal_all();
     let layout_cx = LayoutCx { tcx, param_env };
     let mut ecx =
@@ -463,7 +484,10 @@ pub fn eval_entry<'tcx>(
     }
 
     // Process the result.
-    let (return_code, leak_check) = report_error(&ecx, res)?;
+    let res = report_error(&ecx, res);
+    // List the unsupported intrinsics even if one of them stopped execution.
+    report_unsupported_intrinsics(&ecx);
+    let (return_code, leak_check) = res?;
     if leak_check && !ignore_leaks {
         // Check for thread leaks.
         if !ecx.have_all_terminated() {
@@ -4,9 +4,11 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
+use std::collections::BTreeSet;
 use std::fmt;
 use std::path::Path;
 use std::process;
+use std::rc::Rc;
 
 use rand::rngs::StdRng;
 use rand::Rng;
@@ -342,11 +344,24 @@ pub struct AllocExtra<'tcx> {
     /// if this allocation is leakable. The backtrace is not
     /// pruned yet; that should be done before printing it.
     pub backtrace: Option<Vec<FrameInfo<'tcx>>>,
+    /// The access permissions of memory mapped with `mmap`, if they have been restricted by
+    /// `mmap` or `mprotect`. `None` means the whole allocation is readable and writable.
+    pub mmap_protection: Option<RangeMap<unix::MmapProtection>>,
+    /// Whether this allocation was created by `mmap` of a file (as opposed to an anonymous
+    /// mapping or any other allocation).
+    pub mmap_file_backed: bool,
 }
 
 impl VisitProvenance for AllocExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
-        let AllocExtra { borrow_tracker, data_race, weak_memory, backtrace: _ } = self;
+        let AllocExtra {
+            borrow_tracker,
+            data_race,
+            weak_memory,
+            backtrace: _,
+            mmap_protection: _,
+            mmap_file_backed: _,
+        } = self;
 
         borrow_tracker.visit_provenance(visit);
         data_race.visit_provenance(visit);
@@ -468,8 +483,9 @@ pub struct MiriMachine<'mir, 'tcx> {
     /// The table of directory descriptors.
     pub(crate) dirs: unix::DirTable,
 
-    /// This machine's monotone clock.
-    pub(crate) clock: Clock,
+    /// This machine's monotone clock. It is shared with the file descriptors that need to know
+    /// the time, like `timerfd`s.
+    pub(crate) clock: Rc<Clock>,
 
     /// The set of threads.
     pub(crate) threads: ThreadManager<'mir, 'tcx>,
@@ -496,6 +512,10 @@ pub struct MiriMachine<'mir, 'tcx> {
     /// instead (default behavior)
     pub(crate) panic_on_unsupported: bool,
 
+    /// The distinct unsupported x86 intrinsics the program called, listed when execution ends.
+    /// `None` unless `-Zmiri-report-unsupported-intrinsics` is set.
+    pub(crate) unsupported_intrinsics: Option<BTreeSet<String>>,
+
     /// Equivalent setting as RUST_BACKTRACE on encountering an error.
     pub(crate) backtrace_style: BacktraceStyle,
 
@@ -521,6 +541,17 @@ pub struct MiriMachine<'mir, 'tcx> {
     /// Failure rate of compare_exchange_weak, between 0.0 and 1.0
     pub(crate) cmpxchg_weak_failure_rate: f64,
 
+    /// Corresponds to -Zmiri-deterministic-float-approximations and makes approximating
+    /// intrinsics return the exact result.
+    pub(crate) deterministic_float_approximations: bool,
+
+    /// Corresponds to -Zmiri-per-thread-float-rng. If set, contains the RNG of each thread that
+    /// approximating intrinsics use for their random error. They are created lazily.
+    pub(crate) thread_float_rngs: Option<FxHashMap<ThreadId, StdRng>>,
+
+    /// The seed the global RNG was created with.
+    pub(crate) seed: u64,
+
     /// Corresponds to -Zmiri-mute-stdout-stderr and doesn't write the output but acts as if it succeeded.
     pub(crate) mute_stdout_stderr: bool,
 
@@ -598,7 +629,8 @@ impl<'mir, 'tcx> MiriMachine<'mir, 'tcx> {
             let path = Path::new(out).join(filename);
             measureme::Profiler::new(path).expect("Couldn't create `measureme` profiler")
         });
-        let rng = StdRng::seed_from_u64(config.seed.unwrap_or(0));
+        let seed = config.seed.unwrap_or(0);
+        let rng = StdRng::seed_from_u64(seed);
         let borrow_tracker = config.borrow_tracker.map(|bt| bt.instantiate_global_state(config));
         let data_race = config.data_race_detector.then(|| data_race::GlobalState::new(config));
         // Determine page size, stack address, and stack size.
@@ -641,7 +673,7 @@ impl<'mir, 'tcx> MiriMachine<'mir, 'tcx> {
             tls: TlsData::default(),
             isolated_op: config.isolated_op,
             validate: config.validate,
-            fds: unix::FdTable::new(config.mute_stdout_stderr),
+            fds: unix::FdTable::new(config),
             dirs: Default::default(),
             layouts,
             threads: ThreadManager::default(),
@@ -650,6 +682,7 @@ impl<'mir, 'tcx> MiriMachine<'mir, 'tcx> {
             string_cache: Default::default(),
             exported_symbols_cache: FxHashMap::default(),
             panic_on_unsupported: config.panic_on_unsupported,
+            unsupported_intrinsics: config.report_unsupported_intrinsics.then(BTreeSet::new),
             backtrace_style: config.backtrace_style,
             local_crates,
             extern_statics: FxHashMap::default(),
@@ -658,12 +691,15 @@ impl<'mir, 'tcx> MiriMachine<'mir, 'tcx> {
             track_alloc_accesses: config.track_alloc_accesses,
             check_alignment: config.check_alignment,
             cmpxchg_weak_failure_rate: config.cmpxchg_weak_failure_rate,
+            deterministic_float_approximations: config.deterministic_float_approximations,
+            thread_float_rngs: config.per_thread_float_rng.then(FxHashMap::default),
+            seed,
             mute_stdout_stderr: config.mute_stdout_stderr,
             weak_memory: config.weak_memory_emulation,
             preemption_rate: config.preemption_rate,
             report_progress: config.report_progress,
             basic_block_count: 0,
-            clock: Clock::new(config.isolated_op == IsolatedOp::Allow),
+            clock: Rc::new(Clock::new(config.isolated_op == IsolatedOp::Allow)),
             #[cfg(target_os = "linux")]
             external_so_lib: config.external_so_file.as_ref().map(|lib_file_path| {
                 let target_triple = layout_cx.tcx.sess.opts.target_triple.triple();
@@ -790,6 +826,7 @@ impl VisitProvenance for MiriMachine<'_, '_> {
             string_cache: _,
             exported_symbols_cache: _,
             panic_on_unsupported: _,
+            unsupported_intrinsics: _,
             backtrace_style: _,
             local_crates: _,
             rng: _,
@@ -797,6 +834,9 @@ impl VisitProvenance for MiriMachine<'_, '_> {
             track_alloc_accesses: _,
             check_alignment: _,
             cmpxchg_weak_failure_rate: _,
+            deterministic_float_approximations: _,
+            thread_float_rngs: _,
+            seed: _,
             mute_stdout_stderr: _,
             weak_memory: _,
             preemption_rate: _,
@@ -1133,6 +1173,8 @@ impl<'mir, 'tcx> Machine<'mir, 'tcx> for MiriMachine<'mir, 'tcx> {
                 data_race: race_alloc,
                 weak_memory: buffer_alloc,
                 backtrace,
+                mmap_protection: None,
+                mmap_file_backed: false,
             },
             |ptr| ecx.global_root_pointer(ptr),
         )?;
@@ -1237,6 +1279,9 @@ impl<'mir, 'tcx> Machine<'mir, 'tcx> for MiriMachine<'mir, 'tcx> {
             machine
                 .emit_diagnostic(NonHaltingDiagnostic::AccessedAlloc(alloc_id, AccessKind::Read));
         }
+        if let Some(mmap_protection) = &alloc_extra.mmap_protection {
+            unix::MmapProtection::check_access(mmap_protection, alloc_id, range, AccessKind::Read)?;
+        }
         if let Some(data_race) = &alloc_extra.data_race {
             data_race.read(alloc_id, range, NaReadType::Read, None, machine)?;
         }
@@ -1261,6 +1306,14 @@ impl<'mir, 'tcx> Machine<'mir, 'tcx> for MiriMachine<'mir, 'tcx> {
             machine
                 .emit_diagnostic(NonHaltingDiagnostic::AccessedAlloc(alloc_id, AccessKind::Write));
         }
+        if let Some(mmap_protection) = &alloc_extra.mmap_protection {
+            unix::MmapProtection::check_access(
+                mmap_protection,
+                alloc_id,
+                range,
+                AccessKind::Write,
+            )?;
+        }
         if let Some(data_race) = &mut alloc_extra.data_race {
             data_race.write(alloc_id, range, NaWriteType::Write, None, machine)?;
         }
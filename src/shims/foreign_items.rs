@@ -140,6 +140,11 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                     return Ok(Some(body));
                 }
 
+                if link_name.as_str().starts_with("llvm.x86.")
+                    && let Some(unsupported_intrinsics) = &mut this.machine.unsupported_intrinsics
+                {
+                    unsupported_intrinsics.insert(link_name.to_string());
+                }
                 this.handle_unsupported(format!(
                     "can't call foreign function `{link_name}` on OS `{os}`",
                     os = this.tcx.sess.target.os,
@@ -335,6 +335,67 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         Ok(0)
     }
 
+    /// Like `nanosleep`, but measured against the given clock. With `TIMER_ABSTIME`, `req` is an
+    /// absolute time on that clock rather than a duration. Unlike most other functions, errors
+    /// are returned directly instead of via `errno`.
+    fn clock_nanosleep(
+        &mut self,
+        clock_id_op: &OpTy<'tcx, Provenance>,
+        flags_op: &OpTy<'tcx, Provenance>,
+        req_op: &OpTy<'tcx, Provenance>,
+        _rem: &OpTy<'tcx, Provenance>, // Signal handlers are not supported, so rem will never be written to.
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        this.assert_target_os_is_unix("clock_nanosleep");
+
+        let clock_id = this.read_scalar(clock_id_op)?.to_i32()?;
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+        let req = this.deref_pointer_as(req_op, this.libc_ty_layout("timespec"))?;
+
+        let timer_abstime = this.eval_libc_i32("TIMER_ABSTIME");
+        let realtime = clock_id == this.eval_libc_i32("CLOCK_REALTIME");
+        if (!realtime && clock_id != this.eval_libc_i32("CLOCK_MONOTONIC"))
+            || flags & !timer_abstime != 0
+        {
+            return Ok(this.eval_libc("EINVAL"));
+        }
+        let abstime = flags & timer_abstime == timer_abstime;
+
+        let Some(req) = this.read_timespec(&req)? else {
+            return Ok(this.eval_libc("EINVAL"));
+        };
+
+        let now = this.machine.clock.now();
+        let timeout_time = if !abstime {
+            now.checked_add(req)
+        } else if realtime {
+            // We cannot sleep on the real-time clock, so we sleep on the monotonic clock for the
+            // time remaining until the deadline. Waking up early is acceptable if the real-time
+            // clock jumps.
+            this.check_no_isolation("`clock_nanosleep` with `REALTIME` clocks")?;
+            let until = req.saturating_sub(system_time_to_duration(&SystemTime::now())?);
+            now.checked_add(until)
+        } else {
+            // Absolute monotonic times are relative to the clock's anchor, like for `clock_gettime`.
+            this.machine.clock.anchor().checked_add(req)
+        };
+        // If the timeout overflows, let's just sleep for an hour. Waking up early is always acceptable.
+        let timeout_time =
+            timeout_time.unwrap_or_else(|| now.checked_add(Duration::from_secs(3600)).unwrap());
+
+        let active_thread = this.get_active_thread();
+        this.block_thread(active_thread, BlockReason::Sleep);
+
+        this.register_timeout_callback(
+            active_thread,
+            CallbackTime::Monotonic(timeout_time),
+            Box::new(UnblockCallback { thread_to_unblock: active_thread }),
+        );
+
+        Ok(Scalar::from_i32(0))
+    }
+
     #[allow(non_snake_case)]
     fn Sleep(&mut self, timeout: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
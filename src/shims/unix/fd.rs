@@ -2,16 +2,61 @@
 //! standard file descriptors (stdin/stdout/stderr).
 
 use std::any::Any;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::io::{self, ErrorKind, IsTerminal, Read, SeekFrom, Write};
+use std::path::PathBuf;
+use std::rc::Weak;
+use std::sync::Arc;
+use std::time::Duration;
 
+use rustc_data_structures::fx::FxHashMap;
 use rustc_middle::ty::TyCtxt;
 use rustc_target::abi::Size;
 
 use crate::shims::unix::*;
 use crate::*;
 
+/// The access mode of an open file description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessMode {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+/// The type of file a file description refers to, as reported in the `st_mode` of `fstat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdKind {
+    Regular,
+    Fifo,
+    Socket,
+}
+
+impl FdKind {
+    /// The name of the libc constant for the `S_IFMT` bits of this file type.
+    pub fn mode_name(self) -> &'static str {
+        match self {
+            FdKind::Regular => "S_IFREG",
+            FdKind::Fifo => "S_IFIFO",
+            FdKind::Socket => "S_IFSOCK",
+        }
+    }
+}
+
+/// Synthetic metadata of a file description that is not backed by a host file.
+#[derive(Debug, Clone, Copy)]
+pub struct FdStat {
+    pub kind: FdKind,
+    pub size: u64,
+}
+
 /// Represents an open file descriptor.
+///
+/// This is also how embedders can provide their own kinds of files to the interpreted program,
+/// see `MiriConfig::preloaded_fds`. Most operations have a default implementation that reports
+/// them as unsupported, so implementations only need to provide the operations they support.
+/// Note that this includes `close`: the program can only close file descriptors that implement it.
 pub trait FileDescriptor: std::fmt::Debug + Any {
     fn name(&self) -> &'static str;
 
@@ -33,6 +78,39 @@ pub trait FileDescriptor: std::fmt::Debug + Any {
         throw_unsup_format!("cannot write to {}", self.name());
     }
 
+    /// Reads as much as possible into the given buffer, and returns the number of bytes read.
+    /// With `offset: None`, this reads at (and advances) the file offset, like `read`. With
+    /// `Some(offset)`, it reads at the given offset and the file offset is not changed.
+    fn read_at<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        bytes: &mut [u8],
+        offset: Option<u64>,
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        match offset {
+            None => self.read(communicate_allowed, bytes, tcx),
+            Some(_) => throw_unsup_format!("cannot pread from {}", self.name()),
+        }
+    }
+
+    /// Writes as much as possible from the given buffer, and returns the number of bytes
+    /// written. With `offset: None`, this writes at (and advances) the file offset, like
+    /// `write`. With `Some(offset)`, it writes at the given offset and the file offset is
+    /// not changed.
+    fn write_at<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        bytes: &[u8],
+        offset: Option<u64>,
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        match offset {
+            None => self.write(communicate_allowed, bytes, tcx),
+            Some(_) => throw_unsup_format!("cannot pwrite to {}", self.name()),
+        }
+    }
+
     fn seek<'tcx>(
         &mut self,
         _communicate_allowed: bool,
@@ -48,16 +126,133 @@ pub trait FileDescriptor: std::fmt::Debug + Any {
         throw_unsup_format!("cannot close {}", self.name());
     }
 
+    /// Truncates or extends the file to the given length, filling any new part with zeros.
+    /// The file offset is not changed.
+    fn ftruncate<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        _length: u64,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        // Only regular files can be truncated.
+        Ok(Err(ErrorKind::InvalidInput.into()))
+    }
+
+    /// Flushes all data and metadata of this file description to the underlying storage.
+    fn fsync<'tcx>(&self, _communicate_allowed: bool) -> InterpResult<'tcx, io::Result<()>> {
+        // Only regular files can be synchronized.
+        Ok(Err(ErrorKind::InvalidInput.into()))
+    }
+
+    /// Flushes the data of this file description to the underlying storage. Doing a full
+    /// `fsync` is always a valid implementation of this.
+    fn fdatasync<'tcx>(&self, communicate_allowed: bool) -> InterpResult<'tcx, io::Result<()>> {
+        self.fsync(communicate_allowed)
+    }
+
+    /// Returns whether the content of this file description can be mapped into memory.
+    /// `sendfile` requires this of its source; it is only the case for regular files.
+    fn is_mmappable(&self) -> bool {
+        false
+    }
+
+    /// Returns whether this file description is a socket. Socket operations on anything
+    /// else fail with `ENOTSOCK`.
+    fn is_socket(&self) -> bool {
+        false
+    }
+
+    /// Shuts down the reading and/or writing direction of this socket.
+    fn shutdown<'tcx>(&self, _read: bool, _write: bool) -> InterpResult<'tcx> {
+        throw_unsup_format!("cannot shutdown {}", self.name());
+    }
+
+    /// Returns the metadata reported by `fstat` for file descriptions that only live in memory.
+    /// Host-backed files are queried from the host instead, and `None` means that `fstat`
+    /// is not supported on this file description.
+    fn stat(&self) -> Option<FdStat> {
+        None
+    }
+
     /// Return a new file descriptor *that refers to the same underlying object*.
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>>;
 
+    /// Returns the host file descriptor that backs this file description, for shims that pass
+    /// an operation through to the host. Only file descriptions that are backed by a host file
+    /// (which requires communication with the host) have one.
+    #[cfg(unix)]
+    fn as_unix_host_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        None
+    }
+
     fn is_tty(&self, _communicate_allowed: bool) -> bool {
         // Most FDs are not tty's and the consequence of a wrong `false` are minor,
         // so we use a default impl here.
         false
     }
+
+    /// Returns whether a read would currently not block, as reported by `poll`.
+    /// Descriptors that are not backed by a buffer (like regular files or stdin) can
+    /// always be read from, as far as they are readable at all.
+    fn is_read_ready(&self) -> bool {
+        self.access_mode() != AccessMode::WriteOnly
+    }
+
+    /// Returns whether a write would currently not block, as reported by `poll`.
+    /// Descriptors that are not backed by a buffer (like regular files or stdout) can
+    /// always be written to, as far as they are writable at all.
+    fn is_write_ready(&self) -> bool {
+        self.access_mode() != AccessMode::ReadOnly
+    }
+
+    /// Returns when this file description becomes ready just by time passing, as a duration
+    /// since the clock's anchor, if it does. `poll` and `epoll_wait` wake up then to check again.
+    fn ready_at(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns the access mode this file description was opened with, as reported by `F_GETFL`.
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::ReadWrite
+    }
+
+    /// Returns whether this file description is in non-blocking mode (`O_NONBLOCK`).
+    fn is_nonblock(&self) -> bool {
+        false
+    }
+
+    /// Enables or disables non-blocking mode (`O_NONBLOCK`) for this file description.
+    fn set_nonblock<'tcx>(&mut self, _nonblock: bool) -> InterpResult<'tcx> {
+        throw_unsup_format!("cannot change the O_NONBLOCK flag of {}", self.name());
+    }
+
+    /// Returns the capacity of the pipe this file description refers to, as reported by
+    /// `F_GETPIPE_SZ`, or `None` if it does not refer to a pipe.
+    fn pipe_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Sets the capacity of the pipe this file description refers to (`F_SETPIPE_SZ`).
+    /// Fails with `EINVAL` if it does not refer to a pipe, and with `EBUSY` if the pipe
+    /// currently holds more data than the new capacity.
+    fn set_pipe_capacity(&self, _capacity: usize) -> io::Result<()> {
+        Err(ErrorKind::InvalidInput.into())
+    }
+
+    /// Returns whether all writes to this file description append to the end (`O_APPEND`).
+    fn is_append(&self) -> bool {
+        false
+    }
+
+    /// Enables or disables append mode (`O_APPEND`) for this file description.
+    fn set_append<'tcx>(&mut self, _append: bool) -> InterpResult<'tcx> {
+        throw_unsup_format!("cannot change the O_APPEND flag of {}", self.name());
+    }
 }
 
+/// Creates a file descriptor to be opened before the program starts, see
+/// `MiriConfig::preloaded_fds`.
+pub type FileDescriptorFactory = Arc<dyn Fn() -> Box<dyn FileDescriptor> + Send + Sync>;
+
 impl dyn FileDescriptor {
     #[inline(always)]
     pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
@@ -88,10 +283,22 @@ impl FileDescriptor for io::Stdin {
         Ok(Read::read(self, bytes))
     }
 
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        // This only removes the file descriptor from the program; the host's stdin stays open.
+        Ok(Ok(0))
+    }
+
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
         Ok(Box::new(io::stdin()))
     }
 
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::ReadOnly
+    }
+
     fn is_tty(&self, communicate_allowed: bool) -> bool {
         communicate_allowed && self.is_terminal()
     }
@@ -120,10 +327,22 @@ impl FileDescriptor for io::Stdout {
         Ok(result)
     }
 
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        // This only removes the file descriptor from the program; the host's stdout stays open.
+        Ok(Ok(0))
+    }
+
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
         Ok(Box::new(io::stdout()))
     }
 
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::WriteOnly
+    }
+
     fn is_tty(&self, communicate_allowed: bool) -> bool {
         communicate_allowed && self.is_terminal()
     }
@@ -145,10 +364,22 @@ impl FileDescriptor for io::Stderr {
         Ok(Write::write(&mut { self }, bytes))
     }
 
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        // This only removes the file descriptor from the program; the host's stderr stays open.
+        Ok(Ok(0))
+    }
+
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
         Ok(Box::new(io::stderr()))
     }
 
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::WriteOnly
+    }
+
     fn is_tty(&self, communicate_allowed: bool) -> bool {
         communicate_allowed && self.is_terminal()
     }
@@ -173,15 +404,39 @@ impl FileDescriptor for NullOutput {
         Ok(Ok(bytes.len()))
     }
 
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        // Duplicates of a muted stdout or stderr stay muted.
         Ok(Box::new(NullOutput))
     }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::WriteOnly
+    }
+}
+
+/// An entry of the file descriptor table.
+#[derive(Debug)]
+struct FdEntry {
+    file_descriptor: Box<dyn FileDescriptor>,
+    /// Whether the `FD_CLOEXEC` flag is set. Unlike everything else about the file descriptor,
+    /// this flag is not shared with duplicates. Since we do not support `exec`, it has no effect
+    /// besides being reported by `F_GETFD`.
+    cloexec: bool,
 }
 
 /// The file descriptor table
 #[derive(Debug)]
 pub struct FdTable {
-    pub fds: BTreeMap<i32, Box<dyn FileDescriptor>>,
+    fds: BTreeMap<i32, FdEntry>,
+    /// The `flock` state of every file that has been opened, indexed by its (canonical) path.
+    pub(crate) flocks: FxHashMap<PathBuf, Weak<RefCell<FlockState>>>,
 }
 
 impl VisitProvenance for FdTable {
@@ -191,23 +446,43 @@ impl VisitProvenance for FdTable {
 }
 
 impl FdTable {
-    pub(crate) fn new(mute_stdout_stderr: bool) -> FdTable {
-        let mut fds: BTreeMap<_, Box<dyn FileDescriptor>> = BTreeMap::new();
-        fds.insert(0i32, Box::new(io::stdin()));
-        if mute_stdout_stderr {
-            fds.insert(1i32, Box::new(NullOutput));
-            fds.insert(2i32, Box::new(NullOutput));
+    pub(crate) fn new(config: &MiriConfig) -> FdTable {
+        let mut fds = FdTable { fds: BTreeMap::new(), flocks: FxHashMap::default() };
+        fds.insert_fd(Box::new(io::stdin()));
+        if config.mute_stdout_stderr {
+            fds.insert_fd(Box::new(NullOutput));
+            fds.insert_fd(Box::new(NullOutput));
         } else {
-            fds.insert(1i32, Box::new(io::stdout()));
-            fds.insert(2i32, Box::new(io::stderr()));
+            fds.insert_fd(Box::new(io::stdout()));
+            fds.insert_fd(Box::new(io::stderr()));
+        }
+        for (fd, make_fd) in &config.preloaded_fds {
+            fds.insert_fd_at(*fd, make_fd())
+                .expect("`create_ecx` should have rejected negative preloaded file descriptors");
         }
-        FdTable { fds }
+        fds
     }
 
+    /// Insert a new FD with the lowest unused number. The `FD_CLOEXEC` flag is not set on it;
+    /// use `set_cloexec` for that.
     pub fn insert_fd(&mut self, file_handle: Box<dyn FileDescriptor>) -> i32 {
         self.insert_fd_with_min_fd(file_handle, 0)
     }
 
+    /// Insert a new FD as exactly `fd`, and return the file descriptor previously at `fd`, if any.
+    /// Returns `None` if `fd` is negative, as that is not a valid file descriptor.
+    pub fn insert_fd_at(
+        &mut self,
+        fd: i32,
+        file_handle: Box<dyn FileDescriptor>,
+    ) -> Option<Option<Box<dyn FileDescriptor>>> {
+        if fd < 0 {
+            return None;
+        }
+        let entry = FdEntry { file_descriptor: file_handle, cloexec: false };
+        Some(self.fds.insert(fd, entry).map(|entry| entry.file_descriptor))
+    }
+
     /// Insert a new FD that is at least `min_fd`.
     pub fn insert_fd_with_min_fd(
         &mut self,
@@ -235,25 +510,63 @@ impl FdTable {
             self.fds.last_key_value().map(|(fd, _)| fd.checked_add(1).unwrap()).unwrap_or(min_fd)
         });
 
-        self.fds.try_insert(new_fd, file_handle).unwrap();
+        self.fds
+            .try_insert(new_fd, FdEntry { file_descriptor: file_handle, cloexec: false })
+            .unwrap();
         new_fd
     }
 
+    /// Duplicates `old_fd` onto exactly `new_fd`, and returns the file descriptor previously at
+    /// `new_fd`, if any, so that the caller can close it. The `FD_CLOEXEC` flag of `new_fd` is
+    /// cleared. Returns `None` if `old_fd` is not open or `new_fd` is negative.
+    pub fn dup_to(
+        &mut self,
+        old_fd: i32,
+        new_fd: i32,
+    ) -> Option<io::Result<Option<Box<dyn FileDescriptor>>>> {
+        let dup = self.get_mut(old_fd)?.dup();
+        dup.map(|dup| self.insert_fd_at(new_fd, dup)).transpose()
+    }
+
     pub fn get(&self, fd: i32) -> Option<&dyn FileDescriptor> {
-        Some(&**self.fds.get(&fd)?)
+        Some(&*self.fds.get(&fd)?.file_descriptor)
     }
 
     pub fn get_mut(&mut self, fd: i32) -> Option<&mut dyn FileDescriptor> {
-        Some(&mut **self.fds.get_mut(&fd)?)
+        Some(&mut *self.fds.get_mut(&fd)?.file_descriptor)
     }
 
     pub fn remove(&mut self, fd: i32) -> Option<Box<dyn FileDescriptor>> {
-        self.fds.remove(&fd)
+        Some(self.fds.remove(&fd)?.file_descriptor)
+    }
+
+    /// Removes all open FDs from `first` to `last` (inclusive) and returns them, in order.
+    pub fn remove_range(&mut self, first: i32, last: i32) -> Vec<Box<dyn FileDescriptor>> {
+        let fds: Vec<i32> = self.fds.range(first..=last).map(|(fd, _)| *fd).collect();
+        fds.into_iter().map(|fd| self.fds.remove(&fd).unwrap().file_descriptor).collect()
     }
 
     pub fn is_fd(&self, fd: i32) -> bool {
         self.fds.contains_key(&fd)
     }
+
+    /// Returns whether the `FD_CLOEXEC` flag is set, or `None` if `fd` is not open.
+    pub fn is_cloexec(&self, fd: i32) -> Option<bool> {
+        Some(self.fds.get(&fd)?.cloexec)
+    }
+
+    /// Sets or clears the `FD_CLOEXEC` flag. Returns `None` if `fd` is not open.
+    pub fn set_cloexec(&mut self, fd: i32, cloexec: bool) -> Option<()> {
+        self.fds.get_mut(&fd)?.cloexec = cloexec;
+        Some(())
+    }
+
+    /// Sets the `FD_CLOEXEC` flag on all open FDs from `first` to `last` (inclusive).
+    pub fn set_cloexec_range(&mut self, first: i32, last: i32) {
+        for (_fd, entry) in self.fds.range_mut(first..=last) {
+            entry.cloexec = true;
+        }
+    }
 }
 
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
@@ -270,24 +583,32 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         let fd = this.read_scalar(&args[0])?.to_i32()?;
         let cmd = this.read_scalar(&args[1])?.to_i32()?;
 
-        // We only support getting the flags for a descriptor.
         if cmd == this.eval_libc_i32("F_GETFD") {
-            // Currently this is the only flag that `F_GETFD` returns. It is OK to just return the
-            // `FD_CLOEXEC` value without checking if the flag is set for the file because `std`
-            // always sets this flag when opening a file. However we still need to check that the
-            // file itself is open.
-            if this.machine.fds.is_fd(fd) {
-                Ok(this.eval_libc_i32("FD_CLOEXEC"))
-            } else {
-                this.fd_not_found()
+            // `FD_CLOEXEC` is the only file descriptor flag.
+            match this.machine.fds.is_cloexec(fd) {
+                Some(true) => Ok(this.eval_libc_i32("FD_CLOEXEC")),
+                Some(false) => Ok(0),
+                None => this.fd_not_found(),
+            }
+        } else if cmd == this.eval_libc_i32("F_SETFD") {
+            if args.len() < 3 {
+                throw_ub_format!(
+                    "incorrect number of arguments for fcntl with cmd=`F_SETFD`: got {}, expected at least 3",
+                    args.len()
+                );
+            }
+            let flags = this.read_scalar(&args[2])?.to_i32()?;
+
+            let fd_cloexec = this.eval_libc_i32("FD_CLOEXEC");
+            match this.machine.fds.set_cloexec(fd, flags & fd_cloexec == fd_cloexec) {
+                Some(()) => Ok(0),
+                None => this.fd_not_found(),
             }
         } else if cmd == this.eval_libc_i32("F_DUPFD")
             || cmd == this.eval_libc_i32("F_DUPFD_CLOEXEC")
         {
-            // Note that we always assume the FD_CLOEXEC flag is set for every open file, in part
-            // because exec() isn't supported. The F_DUPFD and F_DUPFD_CLOEXEC commands only
-            // differ in whether the FD_CLOEXEC flag is pre-set on the new file descriptor,
-            // thus they can share the same implementation here.
+            // The F_DUPFD and F_DUPFD_CLOEXEC commands only differ in whether the FD_CLOEXEC flag
+            // is set on the new file descriptor, thus they can share the same implementation here.
             if args.len() < 3 {
                 throw_ub_format!(
                     "incorrect number of arguments for fcntl with cmd=`F_DUPFD`/`F_DUPFD_CLOEXEC`: got {}, expected at least 3",
@@ -300,7 +621,13 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 Some(file_descriptor) => {
                     let dup_result = file_descriptor.dup();
                     match dup_result {
-                        Ok(dup_fd) => Ok(this.machine.fds.insert_fd_with_min_fd(dup_fd, start)),
+                        Ok(dup_fd) => {
+                            let new_fd = this.machine.fds.insert_fd_with_min_fd(dup_fd, start);
+                            if cmd == this.eval_libc_i32("F_DUPFD_CLOEXEC") {
+                                this.machine.fds.set_cloexec(new_fd, true).unwrap();
+                            }
+                            Ok(new_fd)
+                        }
                         Err(e) => {
                             this.set_last_error_from_io_error(e.kind())?;
                             Ok(-1)
@@ -309,6 +636,114 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 }
                 None => this.fd_not_found(),
             }
+        } else if cmd == this.eval_libc_i32("F_GETFL") {
+            let Some(file_descriptor) = this.machine.fds.get(fd) else {
+                return this.fd_not_found();
+            };
+            let mut flags = match file_descriptor.access_mode() {
+                AccessMode::ReadOnly => this.eval_libc_i32("O_RDONLY"),
+                AccessMode::WriteOnly => this.eval_libc_i32("O_WRONLY"),
+                AccessMode::ReadWrite => this.eval_libc_i32("O_RDWR"),
+            };
+            if file_descriptor.is_nonblock() {
+                flags |= this.eval_libc_i32("O_NONBLOCK");
+            }
+            if file_descriptor.is_append() {
+                flags |= this.eval_libc_i32("O_APPEND");
+            }
+            Ok(flags)
+        } else if cmd == this.eval_libc_i32("F_SETFL") {
+            if args.len() < 3 {
+                throw_ub_format!(
+                    "incorrect number of arguments for fcntl with cmd=`F_SETFL`: got {}, expected at least 3",
+                    args.len()
+                );
+            }
+            let flags = this.read_scalar(&args[2])?.to_i32()?;
+
+            let o_nonblock = this.eval_libc_i32("O_NONBLOCK");
+            let o_append = this.eval_libc_i32("O_APPEND");
+            // `F_SETFL` ignores the access mode and the file creation flags, so we ignore them as
+            // well. This way, programs can pass back whatever `F_GETFL` or `open` gave them.
+            let mut ignored = this.eval_libc_i32("O_RDONLY")
+                | this.eval_libc_i32("O_WRONLY")
+                | this.eval_libc_i32("O_RDWR");
+            let creation_flags = [
+                "O_CLOEXEC",
+                "O_CREAT",
+                "O_DIRECTORY",
+                "O_EXCL",
+                "O_NOCTTY",
+                "O_NOFOLLOW",
+                "O_TRUNC",
+            ];
+            for name in creation_flags {
+                ignored |= this.eval_libc_i32(name);
+            }
+            // Linux also ignores `O_LARGEFILE`, which a real `F_GETFL` may report.
+            if this.tcx.sess.target.os == "linux" {
+                ignored |= this.eval_libc_i32("O_LARGEFILE");
+            }
+            let unsupported = flags & !(o_nonblock | o_append | ignored);
+            if unsupported != 0 {
+                throw_unsup_format!("fcntl: F_SETFL flag {:#x} is unsupported", unsupported);
+            }
+
+            let Some(file_descriptor) = this.machine.fds.get_mut(fd) else {
+                return this.fd_not_found();
+            };
+            let append = flags & o_append == o_append;
+            if append != file_descriptor.is_append() {
+                file_descriptor.set_append(append)?;
+            }
+            let nonblock = flags & o_nonblock == o_nonblock;
+            if nonblock != file_descriptor.is_nonblock() {
+                file_descriptor.set_nonblock(nonblock)?;
+            }
+            Ok(0)
+        } else if this.tcx.sess.target.os == "linux" && cmd == this.eval_libc_i32("F_GETPIPE_SZ") {
+            let Some(file_descriptor) = this.machine.fds.get(fd) else {
+                return this.fd_not_found();
+            };
+            match file_descriptor.pipe_capacity() {
+                Some(capacity) => Ok(i32::try_from(capacity).unwrap()),
+                None => {
+                    let einval = this.eval_libc("EINVAL");
+                    this.set_last_error(einval)?;
+                    Ok(-1)
+                }
+            }
+        } else if this.tcx.sess.target.os == "linux" && cmd == this.eval_libc_i32("F_SETPIPE_SZ") {
+            if args.len() < 3 {
+                throw_ub_format!(
+                    "incorrect number of arguments for fcntl with cmd=`F_SETPIPE_SZ`: got {}, expected at least 3",
+                    args.len()
+                );
+            }
+            let size = this.read_scalar(&args[2])?.to_i32()?;
+            let Ok(size) = usize::try_from(size) else {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                return Ok(-1);
+            };
+            // Like Linux, we round up to a power of two that is at least the page size. Requests
+            // larger than the default `/proc/sys/fs/pipe-max-size` of 1 MiB need privileges that
+            // the program does not have.
+            const PIPE_MAX_SIZE: usize = 1 << 20;
+            let page_size = usize::try_from(this.machine.page_size).unwrap();
+            let capacity = size.max(page_size).next_power_of_two();
+
+            let Some(is_pipe) = this.machine.fds.get(fd).map(|fd| fd.pipe_capacity().is_some())
+            else {
+                return this.fd_not_found();
+            };
+            if capacity > PIPE_MAX_SIZE && is_pipe {
+                let eperm = this.eval_libc("EPERM");
+                this.set_last_error(eperm)?;
+                return Ok(-1);
+            }
+            let result = this.machine.fds.get(fd).unwrap().set_pipe_capacity(capacity);
+            this.try_unwrap_io_result(result.map(|()| i32::try_from(capacity).unwrap()))
         } else if this.tcx.sess.target.os == "macos" && cmd == this.eval_libc_i32("F_FULLFSYNC") {
             // Reject if isolation is enabled.
             if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
@@ -323,6 +758,120 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         }
     }
 
+    fn ioctl(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        if args.len() < 2 {
+            throw_ub_format!(
+                "incorrect number of arguments for ioctl: got {}, expected at least 2",
+                args.len()
+            );
+        }
+        let fd = this.read_scalar(&args[0])?.to_i32()?;
+        let request = this.read_scalar(&args[1])?;
+        let request = request.to_uint(request.size())?;
+
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            return this.fd_not_found();
+        };
+        let is_tty = file_descriptor.is_tty(this.machine.communicate());
+
+        // The request that `tcgetattr` is implemented with.
+        let get_termios =
+            this.eval_libc(if this.tcx.sess.target.os == "linux" { "TCGETS" } else { "TIOCGETA" });
+        let get_termios = get_termios.to_uint(get_termios.size())?;
+        let get_winsize = this.eval_libc("TIOCGWINSZ");
+        let get_winsize = get_winsize.to_uint(get_winsize.size())?;
+
+        if (request == get_termios || request == get_winsize) && !is_tty {
+            let enotty = this.eval_libc("ENOTTY");
+            this.set_last_error(enotty)?;
+            return Ok(-1);
+        }
+
+        if request == get_termios {
+            throw_unsup_format!("ioctl: getting the terminal attributes is unsupported");
+        } else if request == get_winsize {
+            if args.len() < 3 {
+                throw_ub_format!(
+                    "incorrect number of arguments for ioctl with request=`TIOCGWINSZ`: got {}, expected at least 3",
+                    args.len()
+                );
+            }
+            let winsize = this.deref_pointer_as(&args[2], this.libc_ty_layout("winsize"))?;
+            // We do not ask the host for the size of its terminal, so that the result is
+            // deterministic. Instead, we report the classic 80x24 characters.
+            this.write_int_fields_named(
+                &[("ws_row", 24), ("ws_col", 80), ("ws_xpixel", 0), ("ws_ypixel", 0)],
+                &winsize,
+            )?;
+            Ok(0)
+        } else {
+            throw_unsup_format!("ioctl: request {request:#x} is unsupported");
+        }
+    }
+
+    fn dup(&mut self, old_fd: i32) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        match this.machine.fds.get_mut(old_fd) {
+            Some(file_descriptor) =>
+                match file_descriptor.dup() {
+                    Ok(dup_fd) => Ok(this.machine.fds.insert_fd(dup_fd)),
+                    Err(e) => {
+                        this.set_last_error_from_io_error(e.kind())?;
+                        Ok(-1)
+                    }
+                },
+            None => this.fd_not_found(),
+        }
+    }
+
+    fn dup2(&mut self, old_fd: i32, new_fd: i32) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        if old_fd == new_fd {
+            // Nothing to do, but we still need to check that the file descriptor is open.
+            return if this.machine.fds.is_fd(old_fd) { Ok(new_fd) } else { this.fd_not_found() };
+        }
+        this.dup3(old_fd, new_fd, 0)
+    }
+
+    fn dup3(&mut self, old_fd: i32, new_fd: i32, flags: i32) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        // Unlike `dup2`, `dup3` does not accept equal file descriptors.
+        if old_fd == new_fd {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        }
+        let o_cloexec = this.eval_libc_i32("O_CLOEXEC");
+        if flags & !o_cloexec != 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        }
+        match this.machine.fds.dup_to(old_fd, new_fd) {
+            Some(Ok(replaced)) => {
+                if let Some(file_descriptor) = replaced {
+                    // Like the real `dup2`, we silently ignore errors from closing it (but
+                    // still propagate interpreter errors).
+                    let _: io::Result<i32> = file_descriptor.close(this.machine.communicate())?;
+                }
+                if flags & o_cloexec == o_cloexec {
+                    this.machine.fds.set_cloexec(new_fd, true).unwrap();
+                }
+                Ok(new_fd)
+            }
+            Some(Err(e)) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                Ok(-1)
+            }
+            None => this.fd_not_found(),
+        }
+    }
+
     fn close(&mut self, fd_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
@@ -336,6 +885,40 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         }))
     }
 
+    /// Closes all open file descriptors from `first` to `last` (inclusive). With
+    /// `CLOSE_RANGE_CLOEXEC`, they are only marked as close-on-exec instead. File descriptors in
+    /// the range that are not open are skipped.
+    fn close_range(&mut self, first: u32, last: u32, flags: i32) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let close_range_cloexec = this.eval_libc_i32("CLOSE_RANGE_CLOEXEC");
+        // We never share the file descriptor table with another process, so
+        // `CLOSE_RANGE_UNSHARE` has no effect.
+        let close_range_unshare = this.eval_libc_i32("CLOSE_RANGE_UNSHARE");
+        if first > last || flags & !(close_range_cloexec | close_range_unshare) != 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        }
+
+        // File descriptors are `i32`, so nothing above `i32::MAX` can be open.
+        let Ok(first) = i32::try_from(first) else {
+            return Ok(0);
+        };
+        let last = i32::try_from(last).unwrap_or(i32::MAX);
+
+        if flags & close_range_cloexec != 0 {
+            this.machine.fds.set_cloexec_range(first, last);
+        } else {
+            for file_descriptor in this.machine.fds.remove_range(first, last) {
+                // Like the real `close_range`, we ignore errors from closing the individual
+                // file descriptors (but still propagate interpreter errors).
+                let _: io::Result<i32> = file_descriptor.close(this.machine.communicate())?;
+            }
+        }
+        Ok(0)
+    }
+
     /// Function used when a file descriptor does not exist. It returns `Ok(-1)`and sets
     /// the last OS error to `libc::EBADF` (invalid file descriptor). This function uses
     /// `T: From<i32>` instead of `i32` directly because some fs functions return different integer
@@ -347,17 +930,27 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         Ok((-1).into())
     }
 
+    /// Returns whether `fd` refers to a file description that provides its own metadata,
+    /// so querying it does not need to communicate with the host.
+    fn is_in_memory_fd(&self, fd: i32) -> bool {
+        let this = self.eval_context_ref();
+        this.machine.fds.get(fd).is_some_and(|file_descriptor| file_descriptor.stat().is_some())
+    }
+
+    /// Reads up to `count` bytes from `fd` into `buf`. If `offset` is given, this reads at that
+    /// position like `pread`, without changing the file offset of `fd`.
     fn read(
         &mut self,
         fd: i32,
         buf: Pointer<Option<Provenance>>,
         count: u64,
+        offset: Option<i128>,
     ) -> InterpResult<'tcx, i64> {
         let this = self.eval_context_mut();
 
         // Isolation check is done via `FileDescriptor` trait.
 
-        trace!("Reading from FD {}, size {}", fd, count);
+        trace!("Reading from FD {} at offset {:?}, size {}", fd, offset, count);
 
         // Check that the *entire* buffer is actually valid memory.
         this.check_ptr_access(buf, Size::from_bytes(count), CheckInAllocMsg::MemoryAccessTest)?;
@@ -369,6 +962,13 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             .min(u64::try_from(isize::MAX).unwrap());
         let communicate = this.machine.communicate();
 
+        // A negative offset is invalid.
+        let Ok(offset) = offset.map(u64::try_from).transpose() else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+
         if let Some(file_descriptor) = this.machine.fds.get_mut(fd) {
             trace!("read: FD mapped to {:?}", file_descriptor);
             // We want to read at most `count` bytes. We are sure that `count` is not negative
@@ -378,7 +978,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             // `File::read` never returns a value larger than `count`,
             // so this cannot fail.
             let result = file_descriptor
-                .read(communicate, &mut bytes, *this.tcx)?
+                .read_at(communicate, &mut bytes, offset, *this.tcx)?
                 .map(|c| i64::try_from(c).unwrap());
 
             match result {
@@ -398,11 +998,14 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         }
     }
 
+    /// Writes up to `count` bytes from `buf` to `fd`. If `offset` is given, this writes at that
+    /// position like `pwrite`, without changing the file offset of `fd`.
     fn write(
         &mut self,
         fd: i32,
         buf: Pointer<Option<Provenance>>,
         count: u64,
+        offset: Option<i128>,
     ) -> InterpResult<'tcx, i64> {
         let this = self.eval_context_mut();
 
@@ -418,14 +1021,313 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             .min(u64::try_from(isize::MAX).unwrap());
         let communicate = this.machine.communicate();
 
+        // A negative offset is invalid.
+        let Ok(offset) = offset.map(u64::try_from).transpose() else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+
         let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(count))?.to_owned();
         if let Some(file_descriptor) = this.machine.fds.get_mut(fd) {
             let result = file_descriptor
-                .write(communicate, &bytes, *this.tcx)?
+                .write_at(communicate, &bytes, offset, *this.tcx)?
                 .map(|c| i64::try_from(c).unwrap());
             this.try_unwrap_io_result(result)
         } else {
             this.fd_not_found()
         }
     }
+
+    /// Copies up to `count` bytes from `in_fd` to `out_fd`, and returns the number of bytes
+    /// copied. If `offset` is given, reading starts at `*offset`, which is then advanced by the
+    /// number of bytes copied, and the file offset of `in_fd` is not changed. Otherwise, reading
+    /// starts at (and advances) the file offset of `in_fd`.
+    fn sendfile(
+        &mut self,
+        out_fd: i32,
+        in_fd: i32,
+        offset: Option<&MPlaceTy<'tcx, Provenance>>,
+        count: u64,
+    ) -> InterpResult<'tcx, i64> {
+        let this = self.eval_context_mut();
+
+        // Isolation check is done via `FileDescriptor` trait.
+
+        trace!("Copying from FD {} to FD {}, size {}", in_fd, out_fd, count);
+
+        // We cap the number of copied bytes to the largest value that we are able to fit in both
+        // the host's and target's `isize`. This saves us from having to handle overflows later.
+        let count = count
+            .min(u64::try_from(this.target_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let communicate = this.machine.communicate();
+
+        let start = match offset {
+            Some(offset) => {
+                let start = this.read_scalar(offset)?.to_int(offset.layout.size)?;
+                // A negative offset is invalid.
+                let Ok(start) = u64::try_from(start) else {
+                    let einval = this.eval_libc("EINVAL");
+                    this.set_last_error(einval)?;
+                    return Ok(-1);
+                };
+                Some(start)
+            }
+            None => None,
+        };
+
+        // Check both file descriptors before consuming any input.
+        if !this.machine.fds.is_fd(out_fd) {
+            return this.fd_not_found();
+        }
+        let Some(file_descriptor) = this.machine.fds.get_mut(in_fd) else {
+            return this.fd_not_found();
+        };
+        if !file_descriptor.is_mmappable() {
+            // This is the case for pipes and sockets, for example.
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        }
+
+        let mut bytes = vec![0; usize::try_from(count).unwrap()];
+        let result = file_descriptor.read_at(communicate, &mut bytes, start, *this.tcx)?;
+        let read_bytes = match result {
+            Ok(read_bytes) => read_bytes,
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(-1);
+            }
+        };
+        bytes.truncate(read_bytes);
+
+        let file_descriptor = this.machine.fds.get_mut(out_fd).unwrap();
+        let result = file_descriptor.write(communicate, &bytes, *this.tcx)?;
+
+        // Only the bytes that were actually written count as consumed.
+        let written = *result.as_ref().unwrap_or(&0);
+        match (start, offset) {
+            (Some(start), Some(offset)) => {
+                let end = start.checked_add(u64::try_from(written).unwrap()).unwrap();
+                this.write_int(end, offset)?;
+            }
+            _ if written < read_bytes => {
+                let unconsumed = i64::try_from(read_bytes.checked_sub(written).unwrap()).unwrap();
+                let file_descriptor = this.machine.fds.get_mut(in_fd).unwrap();
+                if let Err(e) = file_descriptor.seek(communicate, SeekFrom::Current(-unconsumed))? {
+                    this.set_last_error_from_io_error(e.kind())?;
+                    return Ok(-1);
+                }
+            }
+            _ => {}
+        }
+
+        let result = result.map(|c| i64::try_from(c).unwrap());
+        this.try_unwrap_io_result(result)
+    }
+
+    /// Reads into the buffers of the `iovec` array `iov`. If `offset` is given, this reads at that
+    /// position like `preadv`, without changing the file offset of `fd`.
+    fn readv(
+        &mut self,
+        fd: i32,
+        iov: &OpTy<'tcx, Provenance>,
+        iovcnt: i32,
+        offset: Option<i128>,
+    ) -> InterpResult<'tcx, i64> {
+        let this = self.eval_context_mut();
+
+        // Isolation check is done via `FileDescriptor` trait.
+
+        trace!("Reading from FD {} into {} buffers at offset {:?}", fd, iovcnt, offset);
+
+        let Some(iovecs) = this.read_iovecs(iov, iovcnt)? else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+        // A negative offset is invalid.
+        let Ok(offset) = offset.map(u64::try_from).transpose() else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+
+        // Check that the *entire* buffers are actually valid memory.
+        let mut count: u64 = 0;
+        for &(buf, len) in &iovecs {
+            this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+            count = count.saturating_add(len);
+        }
+
+        // We cap the number of read bytes to the largest value that we are able to fit in both the
+        // host's and target's `isize`. This saves us from having to handle overflows later.
+        let count = count
+            .min(u64::try_from(this.target_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let communicate = this.machine.communicate();
+
+        if let Some(file_descriptor) = this.machine.fds.get_mut(fd) {
+            trace!("readv: FD mapped to {:?}", file_descriptor);
+            let mut bytes = vec![0; usize::try_from(count).unwrap()];
+            // We read everything with a single call, and then scatter the result into the buffers.
+            let result = file_descriptor.read_at(communicate, &mut bytes, offset, *this.tcx)?;
+
+            match result {
+                Ok(read_bytes) => {
+                    let mut rest = &bytes[..read_bytes];
+                    for (buf, len) in iovecs {
+                        if rest.is_empty() {
+                            break;
+                        }
+                        let chunk = rest.len().min(usize::try_from(len).unwrap());
+                        this.write_bytes_ptr(buf, rest[..chunk].iter().copied())?;
+                        rest = &rest[chunk..];
+                    }
+                    Ok(i64::try_from(read_bytes).unwrap())
+                }
+                Err(e) => {
+                    this.set_last_error_from_io_error(e.kind())?;
+                    Ok(-1)
+                }
+            }
+        } else {
+            trace!("readv: FD not found");
+            this.fd_not_found()
+        }
+    }
+
+    /// Writes the buffers of the `iovec` array `iov`. If `offset` is given, this writes at that
+    /// position like `pwritev`, without changing the file offset of `fd`.
+    fn writev(
+        &mut self,
+        fd: i32,
+        iov: &OpTy<'tcx, Provenance>,
+        iovcnt: i32,
+        offset: Option<i128>,
+    ) -> InterpResult<'tcx, i64> {
+        let this = self.eval_context_mut();
+
+        // Isolation check is done via `FileDescriptor` trait.
+
+        let Some(iovecs) = this.read_iovecs(iov, iovcnt)? else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+        // A negative offset is invalid.
+        let Ok(offset) = offset.map(u64::try_from).transpose() else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+
+        // Check that the *entire* buffers are actually valid memory.
+        for &(buf, len) in &iovecs {
+            this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+        }
+
+        // We cap the number of written bytes to the largest value that we are able to fit in both the
+        // host's and target's `isize`. This saves us from having to handle overflows later.
+        let mut count =
+            u64::try_from(this.target_isize_max()).unwrap().min(u64::try_from(isize::MAX).unwrap());
+        let communicate = this.machine.communicate();
+
+        // Gather all buffers, and then write everything with a single call.
+        let mut bytes = Vec::new();
+        for (buf, len) in iovecs {
+            let len = len.min(count);
+            bytes.extend_from_slice(
+                this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(len))?,
+            );
+            count = count.checked_sub(len).unwrap();
+        }
+
+        if let Some(file_descriptor) = this.machine.fds.get_mut(fd) {
+            let result = file_descriptor.write_at(communicate, &bytes, offset, *this.tcx)?;
+            let result = result.map(|c| i64::try_from(c).unwrap());
+            this.try_unwrap_io_result(result)
+        } else {
+            this.fd_not_found()
+        }
+    }
+
+    /// Reads the `iovec` array of `readv`/`writev`/`preadv`/`pwritev` and returns the buffers it describes as
+    /// pointer-length pairs. Returns `None` if `iovcnt` is out of range.
+    fn read_iovecs(
+        &self,
+        iov: &OpTy<'tcx, Provenance>,
+        iovcnt: i32,
+    ) -> InterpResult<'tcx, Option<Vec<(Pointer<Option<Provenance>>, u64)>>> {
+        let this = self.eval_context_ref();
+
+        // This is `IOV_MAX` on all supported targets.
+        if !(0..=1024).contains(&iovcnt) {
+            return Ok(None);
+        }
+        if iovcnt == 0 {
+            return Ok(Some(Vec::new()));
+        }
+
+        let iovec_layout = this.libc_ty_layout("iovec");
+        let first = this.deref_pointer_as(iov, iovec_layout)?;
+        let mut iovecs = Vec::new();
+        for i in 0..u64::try_from(iovcnt).unwrap() {
+            let iovec = first.offset(iovec_layout.size * i, iovec_layout, this)?;
+            let buf = this.read_pointer(&this.project_field_named(&iovec, "iov_base")?)?;
+            let len = this.read_target_usize(&this.project_field_named(&iovec, "iov_len")?)?;
+            iovecs.push((buf, len));
+        }
+        Ok(Some(iovecs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file descriptor type that Miri does not know about, as an embedder would provide it.
+    #[derive(Debug)]
+    struct MockSocket;
+
+    impl FileDescriptor for MockSocket {
+        fn name(&self) -> &'static str {
+            "mock socket"
+        }
+
+        fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+            Ok(Box::new(MockSocket))
+        }
+
+        fn close<'tcx>(
+            self: Box<Self>,
+            _communicate_allowed: bool,
+        ) -> InterpResult<'tcx, io::Result<i32>> {
+            Ok(Ok(0))
+        }
+    }
+
+    #[test]
+    fn preloaded_fds() {
+        let mut config = MiriConfig::default();
+        let make_fd: FileDescriptorFactory =
+            Arc::new(|| -> Box<dyn FileDescriptor> { Box::new(MockSocket) });
+        config.preloaded_fds.push((5, make_fd));
+        let mut fds = FdTable::new(&config);
+        assert_eq!(fds.get(5).unwrap().name(), "mock socket");
+        #[cfg(unix)]
+        assert_eq!(fds.get(5).unwrap().as_unix_host_fd(), None);
+        // New file descriptors fill the gap before the preloaded one.
+        assert_eq!(fds.insert_fd(Box::new(NullOutput)), 3);
+
+        // Preloaded file descriptors can be duplicated and closed like any other.
+        assert!(fds.dup_to(5, 1).unwrap().is_ok());
+        assert_eq!(fds.get(1).unwrap().name(), "mock socket");
+        let dup = fds.get_mut(5).unwrap().dup().unwrap();
+        assert_eq!(fds.insert_fd(dup), 4);
+        assert_eq!(fds.remove(5).unwrap().close(false).unwrap().unwrap(), 0);
+        assert!(!fds.is_fd(5));
+        assert_eq!(fds.get(4).unwrap().name(), "mock socket");
+    }
 }
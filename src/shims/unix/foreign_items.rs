@@ -83,7 +83,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let fd = this.read_scalar(fd)?.to_i32()?;
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(count)?;
-                let result = this.read(fd, buf, count)?;
+                let result = this.read(fd, buf, count, None)?;
                 this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
             }
             "write" => {
@@ -92,15 +92,92 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(n)?;
                 trace!("Called write({:?}, {:?}, {:?})", fd, buf, count);
-                let result = this.write(fd, buf, count)?;
+                let result = this.write(fd, buf, count, None)?;
                 // Now, `result` is the value we return back to the program.
                 this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
             }
+            "readv" => {
+                let [fd, iov, iovcnt] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let result = this.readv(fd, iov, iovcnt, None)?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "writev" => {
+                let [fd, iov, iovcnt] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let result = this.writev(fd, iov, iovcnt, None)?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "preadv" => {
+                let [fd, iov, iovcnt, offset] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                let result = this.readv(fd, iov, iovcnt, Some(offset))?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "pwritev" => {
+                let [fd, iov, iovcnt, offset] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                let result = this.writev(fd, iov, iovcnt, Some(offset))?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "pread" => {
+                let [fd, buf, count, offset] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let buf = this.read_pointer(buf)?;
+                let count = this.read_target_usize(count)?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                let result = this.read(fd, buf, count, Some(offset))?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "pwrite" => {
+                let [fd, buf, n, offset] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let buf = this.read_pointer(buf)?;
+                let count = this.read_target_usize(n)?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                trace!("Called pwrite({:?}, {:?}, {:?}, {:?})", fd, buf, count, offset);
+                let result = this.write(fd, buf, count, Some(offset))?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
             "close" => {
                 let [fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 let result = this.close(fd)?;
                 this.write_scalar(result, dest)?;
             }
+            "dup" => {
+                let [old_fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let old_fd = this.read_scalar(old_fd)?.to_i32()?;
+                let new_fd = this.dup(old_fd)?;
+                this.write_scalar(Scalar::from_i32(new_fd), dest)?;
+            }
+            "dup2" => {
+                let [old_fd, new_fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let old_fd = this.read_scalar(old_fd)?.to_i32()?;
+                let new_fd = this.read_scalar(new_fd)?.to_i32()?;
+                let result = this.dup2(old_fd, new_fd)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "dup3" => {
+                // Currently this function does not exist on all Unixes, e.g. on macOS.
+                if !matches!(&*this.tcx.sess.target.os, "linux" | "freebsd") {
+                    throw_unsup_format!(
+                        "`dup3` is not supported on {}",
+                        this.tcx.sess.target.os
+                    );
+                }
+                let [old_fd, new_fd, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let old_fd = this.read_scalar(old_fd)?.to_i32()?;
+                let new_fd = this.read_scalar(new_fd)?.to_i32()?;
+                let flags = this.read_scalar(flags)?.to_i32()?;
+                let result = this.dup3(old_fd, new_fd, flags)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
             "fcntl" => {
                 // `fcntl` is variadic. The argument count is checked based on the first argument
                 // in `this.fcntl()`, so we do not use `check_shim` here.
@@ -108,12 +185,39 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let result = this.fcntl(args)?;
                 this.write_scalar(Scalar::from_i32(result), dest)?;
             }
+            "ioctl" => {
+                // `ioctl` is variadic. The argument count is checked based on the request
+                // in `this.ioctl()`, so we do not use `check_shim` here.
+                this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
+                let result = this.ioctl(args)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
 
             // File and file system access
             "open" | "open64" => {
                 // `open` is variadic, the third argument is only present when the second argument has O_CREAT (or on linux O_TMPFILE, but miri doesn't support that) set
                 this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
-                let result = this.open(args)?;
+                let result = this.open(this.eval_libc_i32("AT_FDCWD"), args)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "openat" | "openat64" => {
+                // Like `open`, `openat` is variadic.
+                this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
+                let [dirfd, args @ ..] = args else {
+                    throw_ub_format!("incorrect number of arguments for `openat`: got 0, expected at least 3");
+                };
+                let dirfd = this.read_scalar(dirfd)?.to_i32()?;
+                let result = this.open(dirfd, args)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "fstat" | "fstat64" | "fstat$INODE64" | "fstat@FBSD_1.0" => {
+                let [fd, buf] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.fstat(fd, buf)?;
+                this.write_scalar(result, dest)?;
+            }
+            "fstatat" | "fstatat64" | "fstatat$INODE64" => {
+                let [dirfd, path, buf, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.fstatat(dirfd, path, buf, flags)?;
                 this.write_scalar(Scalar::from_i32(result), dest)?;
             }
             "unlink" => {
@@ -183,6 +287,13 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let result = this.ftruncate64(fd, length)?;
                 this.write_scalar(result, dest)?;
             }
+            "flock" => {
+                let [fd, op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let op = this.read_scalar(op)?.to_i32()?;
+                let result = this.flock(fd, op)?;
+                this.write_scalar(result, dest)?;
+            }
             "fsync" => {
                 let [fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 let result = this.fsync(fd)?;
@@ -195,31 +306,102 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             }
             "readlink" => {
                 let [pathname, buf, bufsize] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
-                let result = this.readlink(pathname, buf, bufsize)?;
+                let result = this.readlinkat(this.eval_libc_i32("AT_FDCWD"), pathname, buf, bufsize)?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "readlinkat" => {
+                let [dirfd, pathname, buf, bufsize] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let dirfd = this.read_scalar(dirfd)?.to_i32()?;
+                let result = this.readlinkat(dirfd, pathname, buf, bufsize)?;
                 this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
             }
             "posix_fadvise" => {
                 let [fd, offset, len, advice] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
-                this.read_scalar(fd)?.to_i32()?;
-                this.read_target_isize(offset)?;
-                this.read_target_isize(len)?;
-                this.read_scalar(advice)?.to_i32()?;
-                // fadvise is only informational, we can ignore it.
-                this.write_null(dest)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                let len = this.read_scalar(len)?.to_int(this.libc_ty_layout("off_t").size)?;
+                let advice = this.read_scalar(advice)?.to_i32()?;
+                let result = this.posix_fadvise(fd, len, advice)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_fallocate" => {
+                let [fd, offset, len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                let len = this.read_scalar(len)?.to_int(this.libc_ty_layout("off_t").size)?;
+                let result = this.posix_fallocate(fd, offset, len)?;
+                this.write_scalar(result, dest)?;
             }
             "realpath" => {
                 let [path, resolved_path] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 let result = this.realpath(path, resolved_path)?;
                 this.write_scalar(result, dest)?;
             }
+            "statvfs" => {
+                let [path, buf] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.statvfs(path, buf)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "fstatvfs" => {
+                let [fd, buf] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.fstatvfs(fd, buf)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "fchmod" => {
+                let [fd, mode] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.fchmod(fd, mode)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "fchmodat" => {
+                let [dirfd, path, mode, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.fchmodat(dirfd, path, mode, flags)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "fchown" => {
+                let [fd, owner, group] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.fchown(fd, owner, group)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
             "mkstemp" => {
                 let [template] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
-                let result = this.mkstemp(template)?;
+                let result = this.mkstemp(template, 0)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "mkstemps" => {
+                let [template, suffix_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let suffix_len = this.read_scalar(suffix_len)?.to_i32()?;
+                let result = this.mkstemp(template, suffix_len)?;
                 this.write_scalar(Scalar::from_i32(result), dest)?;
             }
+            "mkdtemp" => {
+                let [template] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.mkdtemp(template)?;
+                this.write_pointer(result, dest)?;
+            }
+            "poll" => {
+                let [fds, nfds, timeout] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.poll(fds, nfds, timeout, dest)?;
+            }
 
-            // Sockets
+            // Pipes and sockets
+            "pipe" => {
+                let [pipefd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pipe2(pipefd, /*flags*/ None)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pipe2" => {
+                // Currently this function does not exist on all Unixes, e.g. on macOS.
+                if !matches!(&*this.tcx.sess.target.os, "linux" | "freebsd") {
+                    throw_unsup_format!(
+                        "`pipe2` is not supported on {}",
+                        this.tcx.sess.target.os
+                    );
+                }
+                let [pipefd, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pipe2(pipefd, Some(flags))?;
+                this.write_scalar(result, dest)?;
+            }
             "socketpair" => {
                 let [domain, type_, protocol, sv] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -227,6 +409,19 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let result = this.socketpair(domain, type_, protocol, sv)?;
                 this.write_scalar(result, dest)?;
             }
+            "getsockname" | "getpeername" => {
+                let [fd, addr, addrlen] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                // Both ends of a socket pair have the same address.
+                let result = this.getsockname(fd, addr, addrlen)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "shutdown" => {
+                let [fd, how] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.shutdown(fd, how)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
 
             // Time
             "gettimeofday" => {
@@ -283,6 +478,16 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let result = this.munmap(addr, length)?;
                 this.write_scalar(result, dest)?;
             }
+            "madvise" => {
+                let [addr, length, advice] = this.check_shim(abi, Abi::C {unwind: false}, link_name, args)?;
+                let result = this.madvise(addr, length, advice)?;
+                this.write_scalar(result, dest)?;
+            }
+            "mprotect" => {
+                let [addr, length, prot] = this.check_shim(abi, Abi::C {unwind: false}, link_name, args)?;
+                let result = this.mprotect(addr, length, prot)?;
+                this.write_scalar(result, dest)?;
+            }
 
             "reallocarray" => {
                 // Currently this function does not exist on all Unixes, e.g. on macOS.
@@ -555,6 +760,18 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let result = this.nanosleep(req, rem)?;
                 this.write_scalar(Scalar::from_i32(result), dest)?;
             }
+            "clock_nanosleep" => {
+                // Currently this function does not exist on all Unixes, e.g. on macOS.
+                if !matches!(&*this.tcx.sess.target.os, "linux" | "freebsd") {
+                    throw_unsup_format!(
+                        "`clock_nanosleep` is not supported on {}",
+                        this.tcx.sess.target.os
+                    );
+                }
+                let [clock_id, flags, req, rem] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.clock_nanosleep(clock_id, flags, req, rem)?;
+                this.write_scalar(result, dest)?;
+            }
 
             // Miscellaneous
             "isatty" => {
@@ -618,6 +835,86 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                     this.write_scalar(Scalar::from_i32(0), dest)?;
                 }
             }
+            "arc4random_buf" => {
+                // This function is non-standard but exists with the same signature and behavior on
+                // macOS, FreeBSD, and Linux (glibc 2.36+).
+                if !matches!(&*this.tcx.sess.target.os, "linux" | "macos" | "freebsd") {
+                    throw_unsup_format!(
+                        "`arc4random_buf` is not supported on {}",
+                        this.tcx.sess.target.os
+                    );
+                }
+
+                let [buf, nbytes] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let buf = this.read_pointer(buf)?;
+                let nbytes = this.read_target_usize(nbytes)?;
+
+                // arc4random_buf cannot fail and has no return value.
+                this.gen_random(buf, nbytes)?;
+            }
+
+            // Process spawning
+            // Miri cannot spawn processes, but libraries typically prepare the arguments of
+            // `posix_spawn` before calling it. We let that preparation succeed without recording
+            // anything, so that the program gets to the actual spawn and fails there.
+            | "posix_spawn_file_actions_init"
+            | "posix_spawnattr_init"
+            => {
+                let [obj] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let ty = if link_name.as_str() == "posix_spawnattr_init" {
+                    "posix_spawnattr_t"
+                } else {
+                    "posix_spawn_file_actions_t"
+                };
+                // The object is opaque to the program; zeroing it makes it initialized memory.
+                let obj = this.deref_pointer_as(obj, this.libc_ty_layout(ty))?;
+                let size = usize::try_from(obj.layout.size.bytes()).unwrap();
+                this.write_bytes_ptr(obj.ptr(), std::iter::repeat(0u8).take(size))?;
+                this.write_null(dest)?;
+            }
+            | "posix_spawn_file_actions_destroy"
+            | "posix_spawnattr_destroy"
+            => {
+                let [obj] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.read_pointer(obj)?;
+                this.write_null(dest)?;
+            }
+            "posix_spawn_file_actions_addopen" => {
+                let [actions, fd, path, oflag, mode] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.read_pointer(actions)?;
+                this.read_scalar(fd)?.to_i32()?;
+                // Make sure the path is a valid C string.
+                this.read_c_str(this.read_pointer(path)?)?;
+                this.read_scalar(oflag)?.to_i32()?;
+                this.read_scalar(mode)?;
+                this.write_null(dest)?;
+            }
+            "posix_spawn_file_actions_adddup2" => {
+                let [actions, fd, newfd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.read_pointer(actions)?;
+                this.read_scalar(fd)?.to_i32()?;
+                this.read_scalar(newfd)?.to_i32()?;
+                this.write_null(dest)?;
+            }
+            "posix_spawn_file_actions_addclose" => {
+                let [actions, fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.read_pointer(actions)?;
+                this.read_scalar(fd)?.to_i32()?;
+                this.write_null(dest)?;
+            }
+            | "posix_spawnattr_setflags"
+            | "posix_spawnattr_setpgroup"
+            | "posix_spawnattr_setsigmask"
+            | "posix_spawnattr_setsigdefault"
+            => {
+                let [attr, _value] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.read_pointer(attr)?;
+                this.write_null(dest)?;
+            }
+            "posix_spawn" | "posix_spawnp" => {
+                throw_unsup_format!("`{link_name}`: process spawning is not supported by Miri");
+            }
 
             // Incomplete shims that we "stub out" just to get pre-main initialization code to work.
             // These shims are enabled only when the caller is in the standard library.
@@ -1,13 +1,19 @@
 //! File and file system access
 
 use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
 use std::fs::{
-    read_dir, remove_dir, remove_file, rename, DirBuilder, File, FileType, OpenOptions, ReadDir,
+    read_dir, remove_dir, remove_file, rename, DirBuilder, DirEntry, File, FileType, OpenOptions,
+    ReadDir,
 };
 use std::io::{self, ErrorKind, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
 use std::time::SystemTime;
 
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
 use rustc_data_structures::fx::FxHashMap;
 use rustc_middle::ty::TyCtxt;
 use rustc_target::abi::Size;
@@ -20,7 +26,74 @@ use shims::time::system_time_to_duration;
 #[derive(Debug)]
 struct FileHandle {
     file: File,
+    readable: bool,
     writable: bool,
+    /// Whether every write goes to the end of the file (`O_APPEND`), shared by all duplicates
+    /// of this open file description. We do not let the host handle this flag, since it can be
+    /// changed with `F_SETFL`.
+    append: Rc<Cell<bool>>,
+    /// The `flock` lock of this open file description, shared with all its duplicates.
+    flock: Rc<FlockHandle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlockKind {
+    Shared,
+    Exclusive,
+}
+
+/// The `flock` locks held on a file, shared by all open file descriptions of that file.
+#[derive(Debug, Default)]
+pub struct FlockState {
+    /// The locks, indexed by the ID of the open file description holding them.
+    locks: FxHashMap<u64, FlockKind>,
+    /// ID number to be used by the next open file description of this file.
+    next_id: u64,
+}
+
+/// The handle of an open file description on the `flock` state of its file. Since it is shared
+/// by all duplicates of a file descriptor, the lock is released when the last of them is closed.
+#[derive(Debug)]
+struct FlockHandle {
+    id: u64,
+    state: Rc<RefCell<FlockState>>,
+}
+
+impl FlockHandle {
+    #[allow(clippy::arithmetic_side_effects)]
+    fn new(state: Rc<RefCell<FlockState>>) -> Self {
+        let id = {
+            let mut state = state.borrow_mut();
+            let id = state.next_id;
+            state.next_id += 1;
+            id
+        };
+        FlockHandle { id, state }
+    }
+
+    /// Acquires a lock of the given kind, or converts the lock already held to that kind.
+    /// Returns `false` if another open file description holds a conflicting lock.
+    fn lock(&self, kind: FlockKind) -> bool {
+        let mut state = self.state.borrow_mut();
+        let conflict = state.locks.iter().any(|(&id, &other)| {
+            id != self.id && (kind == FlockKind::Exclusive || other == FlockKind::Exclusive)
+        });
+        if conflict {
+            return false;
+        }
+        state.locks.insert(self.id, kind);
+        true
+    }
+
+    fn unlock(&self) {
+        self.state.borrow_mut().locks.remove(&self.id);
+    }
+}
+
+impl Drop for FlockHandle {
+    fn drop(&mut self) {
+        self.unlock();
+    }
 }
 
 impl FileDescriptor for FileHandle {
@@ -45,9 +118,70 @@ impl FileDescriptor for FileHandle {
         _tcx: TyCtxt<'tcx>,
     ) -> InterpResult<'tcx, io::Result<usize>> {
         assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        if self.append.get() {
+            // Seeking and writing are not one atomic operation on the host, but Miri runs
+            // sequentially, so no other write can happen in between.
+            if let Err(e) = self.file.seek(SeekFrom::End(0)) {
+                return Ok(Err(e));
+            }
+        }
         Ok(self.file.write(bytes))
     }
 
+    fn read_at<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        bytes: &mut [u8],
+        offset: Option<u64>,
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(offset) = offset else {
+            return self.read(communicate_allowed, bytes, tcx);
+        };
+        assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        // Emulates pread using seek + read + seek to restore cursor position.
+        // Correctness of this emulation relies on sequential nature of Miri execution.
+        // The closure is used to emulate `try` block, since we "bubble" `io::Error` using `?`.
+        let mut f = || {
+            let cursor_pos = self.file.stream_position()?;
+            self.file.seek(SeekFrom::Start(offset))?;
+            let res = self.file.read(bytes);
+            // Attempt to restore cursor position even if the read has failed
+            self.file
+                .seek(SeekFrom::Start(cursor_pos))
+                .expect("failed to restore file position, this shouldn't be possible");
+            res
+        };
+        Ok(f())
+    }
+
+    fn write_at<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        bytes: &[u8],
+        offset: Option<u64>,
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(offset) = offset else {
+            return self.write(communicate_allowed, bytes, tcx);
+        };
+        assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        // Emulates pwrite using seek + write + seek to restore cursor position.
+        // Correctness of this emulation relies on sequential nature of Miri execution.
+        // The closure is used to emulate `try` block, since we "bubble" `io::Error` using `?`.
+        let mut f = || {
+            let cursor_pos = self.file.stream_position()?;
+            self.file.seek(SeekFrom::Start(offset))?;
+            let res = self.file.write(bytes);
+            // Attempt to restore cursor position even if the write has failed
+            self.file
+                .seek(SeekFrom::Start(cursor_pos))
+                .expect("failed to restore file position, this shouldn't be possible");
+            res
+        };
+        Ok(f())
+    }
+
     fn seek<'tcx>(
         &mut self,
         communicate_allowed: bool,
@@ -83,26 +217,298 @@ impl FileDescriptor for FileHandle {
         }
     }
 
+    fn ftruncate<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        length: u64,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        if !self.writable {
+            // The file is not writable
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        }
+        Ok(self.file.set_len(length))
+    }
+
+    fn fsync<'tcx>(&self, communicate_allowed: bool) -> InterpResult<'tcx, io::Result<()>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        Ok(maybe_sync_file(&self.file, self.writable, File::sync_all))
+    }
+
+    fn fdatasync<'tcx>(&self, communicate_allowed: bool) -> InterpResult<'tcx, io::Result<()>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        Ok(maybe_sync_file(&self.file, self.writable, File::sync_data))
+    }
+
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
         let duplicated = self.file.try_clone()?;
-        Ok(Box::new(FileHandle { file: duplicated, writable: self.writable }))
+        Ok(Box::new(FileHandle {
+            file: duplicated,
+            readable: self.readable,
+            writable: self.writable,
+            append: Rc::clone(&self.append),
+            flock: Rc::clone(&self.flock),
+        }))
     }
 
     fn is_tty(&self, communicate_allowed: bool) -> bool {
         communicate_allowed && self.file.is_terminal()
     }
+
+    fn access_mode(&self) -> AccessMode {
+        match (self.readable, self.writable) {
+            (true, true) => AccessMode::ReadWrite,
+            (false, true) => AccessMode::WriteOnly,
+            _ => AccessMode::ReadOnly,
+        }
+    }
+
+    fn is_append(&self) -> bool {
+        self.append.get()
+    }
+
+    fn set_append<'tcx>(&mut self, append: bool) -> InterpResult<'tcx> {
+        self.append.set(append);
+        Ok(())
+    }
+
+    fn is_mmappable(&self) -> bool {
+        true
+    }
+
+    #[cfg(unix)]
+    fn as_unix_host_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        use std::os::unix::io::AsRawFd;
+        Some(self.file.as_raw_fd())
+    }
+}
+
+/// Emulates `/dev/urandom` and `/dev/random`. Reads are served from an RNG seeded
+/// by Miri's RNG, so they are deterministic and also work with isolation enabled.
+/// Only read-only access is supported.
+#[derive(Debug)]
+struct RandomDevice {
+    /// The path this device was opened with.
+    path: &'static str,
+    /// The RNG state, shared by all duplicates of the file descriptor.
+    rng: Rc<RefCell<StdRng>>,
+}
+
+impl FileDescriptor for RandomDevice {
+    fn name(&self) -> &'static str {
+        self.path
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        self.rng.borrow_mut().fill_bytes(bytes);
+        Ok(Ok(bytes.len()))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(RandomDevice { path: self.path, rng: Rc::clone(&self.rng) }))
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::ReadOnly
+    }
+}
+
+/// An open directory, as created by `open`ing a directory. Besides `fstat` and `fsync`, it can
+/// be used as the `dirfd` of the `*at` functions, which resolve relative paths against `path`.
+#[derive(Debug)]
+struct DirHandle {
+    file: File,
+    /// The absolute path the directory was opened with.
+    path: PathBuf,
+    /// How far `getdents64` has read the directory, shared by all duplicates of this open file
+    /// description.
+    read_state: Rc<RefCell<DirReadState>>,
+}
+
+/// The state of reading the entries of a directory with `getdents64`.
+#[derive(Debug, Default)]
+struct DirReadState {
+    /// The entries read by `getdents64`, created on its first call.
+    entries: Option<ReadDir>,
+    /// An entry that was taken from `entries`, but did not fit into the buffer passed to
+    /// `getdents64`, so it is returned by the next call.
+    pending: Option<DirEntry>,
+    /// The number of entries returned by `getdents64` so far.
+    position: u64,
+}
+
+impl DirHandle {
+    fn new(file: File, path: PathBuf) -> Self {
+        DirHandle { file, path, read_state: Rc::default() }
+    }
+}
+
+impl DirReadState {
+    /// Returns the entry of the directory at `path` that `getdents64` returns next, without
+    /// consuming it, or `None` at the end of the directory.
+    fn peek_entry(&mut self, path: &Path) -> io::Result<Option<&DirEntry>> {
+        if self.pending.is_none() {
+            let entries = match &mut self.entries {
+                Some(entries) => entries,
+                None => self.entries.insert(read_dir(path)?),
+            };
+            self.pending = entries.next().transpose()?;
+        }
+        Ok(self.pending.as_ref())
+    }
+}
+
+impl FileDescriptor for DirHandle {
+    fn name(&self) -> &'static str {
+        "directory"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a directory");
+        // The host reports the appropriate error.
+        Ok(self.file.read(bytes))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a directory");
+        drop(self);
+        Ok(Ok(0))
+    }
+
+    fn fsync<'tcx>(&self, communicate_allowed: bool) -> InterpResult<'tcx, io::Result<()>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a directory");
+        Ok(self.file.sync_all())
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        let duplicated = self.file.try_clone()?;
+        Ok(Box::new(DirHandle {
+            file: duplicated,
+            path: self.path.clone(),
+            read_state: Rc::clone(&self.read_state),
+        }))
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::ReadOnly
+    }
+
+    #[cfg(unix)]
+    fn as_unix_host_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        use std::os::unix::io::AsRawFd;
+        Some(self.file.as_raw_fd())
+    }
 }
 
 impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
 trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
-    fn macos_stat_write_buf(
+    /// Returns the `flock` state of the file at `path`, shared by all its open file descriptions.
+    fn flock_state(&mut self, path: &Path) -> Rc<RefCell<FlockState>> {
+        let this = self.eval_context_mut();
+        // Different paths can refer to the same file, so we identify the file by its canonical
+        // path where possible.
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+        let flocks = &mut this.machine.fds.flocks;
+        // Entries of files that are not open anymore stay in the map; they get replaced here.
+        if let Some(state) = flocks.get(&path).and_then(Weak::upgrade) {
+            return state;
+        }
+        let state = Rc::new(RefCell::new(FlockState::default()));
+        flocks.insert(path, Rc::downgrade(&state));
+        state
+    }
+
+    /// Resolves `path` like the `*at` functions do: absolute (and empty) paths are used as they
+    /// are, and relative paths are resolved against the current working directory if `dirfd` is
+    /// `AT_FDCWD`, or against the directory `dirfd` otherwise. Returns `None` and sets the last
+    /// error if `dirfd` is not an open directory.
+    fn resolve_path_at(&mut self, dirfd: i32, path: &Path) -> InterpResult<'tcx, Option<PathBuf>> {
+        let this = self.eval_context_mut();
+
+        if path.is_absolute()
+            || path.as_os_str().is_empty()
+            || dirfd == this.eval_libc_i32("AT_FDCWD")
+        {
+            return Ok(Some(path.to_owned()));
+        }
+        let Some(file_descriptor) = this.machine.fds.get(dirfd) else {
+            return this.fd_not_found().map(|_: i32| None);
+        };
+        let Some(dir) = file_descriptor.downcast_ref::<DirHandle>() else {
+            let enotdir = this.eval_libc("ENOTDIR");
+            this.set_last_error(enotdir)?;
+            return Ok(None);
+        };
+        Ok(Some(dir.path.join(path)))
+    }
+
+    /// Implements `lseek` with `SEEK_HOLE` (if `hole` is true) or `SEEK_DATA`. We do not know
+    /// where the holes of a file are, so we treat the whole file as data: the next data is at
+    /// `offset` itself, and the next hole is the implicit one at the end of the file. If `offset`
+    /// is not within the file, this fails with `ENXIO` and the file offset is not changed.
+    fn seek_data_or_hole(&mut self, fd: i32, offset: i128, hole: bool) -> InterpResult<'tcx, i64> {
+        let this = self.eval_context_mut();
+
+        let communicate = this.machine.communicate();
+        let Some(file_descriptor) = this.machine.fds.get_mut(fd) else {
+            return this.fd_not_found();
+        };
+        let pos = file_descriptor.seek(communicate, SeekFrom::Current(0))?;
+        let size = file_descriptor.seek(communicate, SeekFrom::End(0))?;
+        let (pos, size) = match (pos, size) {
+            (Ok(pos), Ok(size)) => (pos, size),
+            (Err(e), _) | (_, Err(e)) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(-1);
+            }
+        };
+
+        let Some(offset) = u64::try_from(offset).ok().filter(|&offset| offset < size) else {
+            // Restore the file offset that we changed to determine the size.
+            if let Err(e) = file_descriptor.seek(communicate, SeekFrom::Start(pos))? {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(-1);
+            }
+            let enxio = this.eval_libc("ENXIO");
+            this.set_last_error(enxio)?;
+            return Ok(-1);
+        };
+        let target = if hole { size } else { offset };
+        let result = file_descriptor
+            .seek(communicate, SeekFrom::Start(target))?
+            .map(|offset| i64::try_from(offset).unwrap());
+        this.try_unwrap_io_result(result)
+    }
+
+    fn write_stat_buf(
         &mut self,
         metadata: FileMetadata,
         buf_op: &OpTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, i32> {
         let this = self.eval_context_mut();
 
-        let mode: u16 = metadata.mode.to_u16()?;
+        // `mode_t` is a `u16` on macOS and a `u32` on Linux and FreeBSD.
+        let mode = metadata.mode.to_uint(metadata.mode.size())?;
 
         let (access_sec, access_nsec) = metadata.accessed.unwrap_or((0, 0));
         let (created_sec, created_nsec) = metadata.created.unwrap_or((0, 0));
@@ -110,31 +516,69 @@ trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx
 
         let buf = this.deref_pointer_as(buf_op, this.libc_ty_layout("stat"))?;
 
-        this.write_int_fields_named(
-            &[
-                ("st_dev", 0),
-                ("st_mode", mode.into()),
-                ("st_nlink", 0),
-                ("st_ino", 0),
-                ("st_uid", 0),
-                ("st_gid", 0),
-                ("st_rdev", 0),
-                ("st_atime", access_sec.into()),
-                ("st_atime_nsec", access_nsec.into()),
-                ("st_mtime", modified_sec.into()),
-                ("st_mtime_nsec", modified_nsec.into()),
-                ("st_ctime", 0),
-                ("st_ctime_nsec", 0),
+        let mut fields = vec![
+            ("st_dev", 0),
+            ("st_mode", mode.try_into().unwrap()),
+            ("st_nlink", 0),
+            ("st_ino", 0),
+            ("st_uid", 0),
+            ("st_gid", 0),
+            ("st_rdev", 0),
+            ("st_atime", access_sec.into()),
+            ("st_atime_nsec", access_nsec.into()),
+            ("st_mtime", modified_sec.into()),
+            ("st_mtime_nsec", modified_nsec.into()),
+            ("st_ctime", 0),
+            ("st_ctime_nsec", 0),
+            ("st_size", metadata.size.into()),
+            ("st_blocks", 0),
+            ("st_blksize", 0),
+        ];
+        if matches!(&*this.tcx.sess.target.os, "macos" | "freebsd") {
+            fields.extend([
                 ("st_birthtime", created_sec.into()),
                 ("st_birthtime_nsec", created_nsec.into()),
-                ("st_size", metadata.size.into()),
-                ("st_blocks", 0),
-                ("st_blksize", 0),
                 ("st_flags", 0),
                 ("st_gen", 0),
-            ],
-            &buf,
-        )?;
+            ]);
+        }
+        this.write_int_fields_named(&fields, &buf)?;
+
+        Ok(0)
+    }
+
+    /// Writes `stats` to the `statvfs` struct at `buf_op`. Like the real function, this fails with
+    /// `EOVERFLOW` if a value does not fit into the target's field.
+    fn write_statvfs_buf(
+        &mut self,
+        stats: FsStats,
+        buf_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let buf = this.deref_pointer_as(buf_op, this.libc_ty_layout("statvfs"))?;
+        let fields = [
+            ("f_bsize", stats.block_size),
+            ("f_frsize", stats.fragment_size),
+            ("f_blocks", stats.blocks),
+            ("f_bfree", stats.blocks_free),
+            ("f_bavail", stats.blocks_available),
+            ("f_files", stats.files),
+            ("f_ffree", stats.files_free),
+            ("f_favail", stats.files_available),
+            ("f_fsid", stats.fsid),
+            ("f_flag", stats.flags),
+            ("f_namemax", stats.max_name_len),
+        ];
+        for (name, value) in fields {
+            let field = this.project_field_named(&buf, name)?;
+            if u128::from(value) >> field.layout.size.bits() != 0 {
+                let eoverflow = this.eval_libc("EOVERFLOW");
+                this.set_last_error(eoverflow)?;
+                return Ok(-1);
+            }
+            this.write_int(value, &field)?;
+        }
 
         Ok(0)
     }
@@ -258,21 +702,22 @@ fn maybe_sync_file(
     file: &File,
     writable: bool,
     operation: fn(&File) -> std::io::Result<()>,
-) -> std::io::Result<i32> {
+) -> std::io::Result<()> {
     if !writable && cfg!(windows) {
         // sync_all() and sync_data() will return an error on Windows hosts if the file is not opened
         // for writing. (FlushFileBuffers requires that the file handle have the
         // GENERIC_WRITE right)
-        Ok(0i32)
+        Ok(())
     } else {
-        let result = operation(file);
-        result.map(|_| 0i32)
+        operation(file)
     }
 }
 
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
 pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
-    fn open(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
+    /// Implements `open` and `openat`. `args` are the arguments of `open`, and relative paths are
+    /// resolved against `dirfd` (which is `AT_FDCWD` for `open`).
+    fn open(&mut self, dirfd: i32, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
         if args.len() < 2 {
             throw_ub_format!(
                 "incorrect number of arguments for `open`: got {}, expected at least 2",
@@ -296,6 +741,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         if (o_rdonly | o_wronly | o_rdwr) & !0b11 != 0 {
             throw_unsup_format!("access mode flags on this target are unsupported");
         }
+        let mut readable = true;
         let mut writable = true;
 
         // Now we check the access mode
@@ -305,6 +751,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             writable = false;
             options.read(true);
         } else if access_mode == o_wronly {
+            readable = false;
             options.write(true);
         } else if access_mode == o_rdwr {
             options.read(true).write(true);
@@ -317,8 +764,8 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         let mut mirror = access_mode;
 
         let o_append = this.eval_libc_i32("O_APPEND");
-        if flag & o_append == o_append {
-            options.append(true);
+        let append = flag & o_append == o_append;
+        if append {
             mirror |= o_append;
         }
         let o_trunc = this.eval_libc_i32("O_TRUNC");
@@ -368,11 +815,15 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             }
         }
         let o_cloexec = this.eval_libc_i32("O_CLOEXEC");
-        if flag & o_cloexec == o_cloexec {
-            // We do not need to do anything for this flag because `std` already sets it.
-            // (Technically we do not support *not* setting this flag, but we ignore that.)
+        let cloexec = flag & o_cloexec == o_cloexec;
+        if cloexec {
             mirror |= o_cloexec;
         }
+        let o_directory = this.eval_libc_i32("O_DIRECTORY");
+        let directory = flag & o_directory == o_directory;
+        if directory {
+            mirror |= o_directory;
+        }
         if this.tcx.sess.target.os == "linux" {
             let o_tmpfile = this.eval_libc_i32("O_TMPFILE");
             if flag & o_tmpfile == o_tmpfile {
@@ -389,6 +840,38 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         }
 
         let path = this.read_path_from_c_str(path)?;
+        let Some(path) = this.resolve_path_at(dirfd, &path)? else {
+            return Ok(-1);
+        };
+
+        // The random devices are emulated using Miri's RNG, so they can be opened
+        // even with isolation enabled.
+        let random_device =
+            ["/dev/urandom", "/dev/random"].into_iter().find(|&p| path == Path::new(p));
+        if let Some(device_path) = random_device {
+            if access_mode != o_rdonly {
+                throw_unsup_format!("opening `{device_path}` for writing is not supported");
+            }
+            // The device already exists, and it is not a directory.
+            let o_excl = this.eval_libc_i32("O_EXCL");
+            let error = if flag & (o_creat | o_excl) == o_creat | o_excl {
+                Some("EEXIST")
+            } else if directory {
+                Some("ENOTDIR")
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                let error = this.eval_libc(error);
+                this.set_last_error(error)?;
+                return Ok(-1);
+            }
+            let rng = StdRng::seed_from_u64(this.machine.rng.get_mut().gen());
+            let device = RandomDevice { path: device_path, rng: Rc::new(RefCell::new(rng)) };
+            let fd = this.machine.fds.insert_fd(Box::new(device));
+            this.machine.fds.set_cloexec(fd, cloexec).unwrap();
+            return Ok(fd);
+        }
 
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
@@ -397,12 +880,46 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             return Ok(-1);
         }
 
-        let fd = options.open(path).map(|file| {
-            let fh = &mut this.machine.fds;
-            fh.insert_fd(Box::new(FileHandle { file, writable }))
-        });
+        let file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(-1);
+            }
+        };
+        let is_dir = match file.metadata() {
+            Ok(metadata) => metadata.is_dir(),
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(-1);
+            }
+        };
+        if directory && !is_dir {
+            let enotdir = this.eval_libc("ENOTDIR");
+            this.set_last_error(enotdir)?;
+            return Ok(-1);
+        }
 
-        this.try_unwrap_io_result(fd)
+        let file_descriptor: Box<dyn FileDescriptor> = if is_dir {
+            // The host only lets us open directories read-only. We remember the absolute path,
+            // so that relative paths of the `*at` functions still resolve after a `chdir`.
+            let path = match std::env::current_dir() {
+                Ok(cwd) => cwd.join(&path),
+                Err(e) => {
+                    this.set_last_error_from_io_error(e.kind())?;
+                    return Ok(-1);
+                }
+            };
+            Box::new(DirHandle::new(file, path))
+        } else {
+            let flock = Rc::new(FlockHandle::new(this.flock_state(&path)));
+            let append = Rc::new(Cell::new(append));
+            Box::new(FileHandle { file, readable, writable, append, flock })
+        };
+        let fh = &mut this.machine.fds;
+        let fd = fh.insert_fd(file_descriptor);
+        fh.set_cloexec(fd, cloexec).unwrap();
+        Ok(fd)
     }
 
     fn lseek64(
@@ -421,6 +938,10 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             SeekFrom::Current(i64::try_from(offset).unwrap())
         } else if whence == this.eval_libc_i32("SEEK_END") {
             SeekFrom::End(i64::try_from(offset).unwrap())
+        } else if whence == this.eval_libc_i32("SEEK_DATA") {
+            return Ok(Scalar::from_i64(this.seek_data_or_hole(fd, offset, false)?));
+        } else if whence == this.eval_libc_i32("SEEK_HOLE") {
+            return Ok(Scalar::from_i64(this.seek_data_or_hole(fd, offset, true)?));
         } else {
             let einval = this.eval_libc("EINVAL");
             this.set_last_error(einval)?;
@@ -513,7 +1034,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             None => return Ok(Scalar::from_i32(-1)), // `FileMetadata` has set errno
         };
 
-        Ok(Scalar::from_i32(this.macos_stat_write_buf(metadata, buf_op)?))
+        Ok(Scalar::from_i32(this.write_stat_buf(metadata, buf_op)?))
     }
 
     // `lstat` is used to get symlink metadata.
@@ -544,24 +1065,24 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             None => return Ok(Scalar::from_i32(-1)), // `FileMetadata` has set errno
         };
 
-        Ok(Scalar::from_i32(this.macos_stat_write_buf(metadata, buf_op)?))
+        Ok(Scalar::from_i32(this.write_stat_buf(metadata, buf_op)?))
     }
 
-    fn macos_fbsd_fstat(
+    fn fstat(
         &mut self,
         fd_op: &OpTy<'tcx, Provenance>,
         buf_op: &OpTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
-        if !matches!(&*this.tcx.sess.target.os, "macos" | "freebsd") {
-            panic!("`macos_fbsd_fstat` should not be called on {}", this.tcx.sess.target.os);
-        }
+        this.assert_target_os_is_unix("fstat");
 
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        // Reject if isolation is enabled, unless the file description only lives in memory.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.is_in_memory_fd(fd)
+        {
             this.reject_in_isolation("`fstat`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return Ok(Scalar::from_i32(this.fd_not_found()?));
@@ -571,7 +1092,50 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             Some(metadata) => metadata,
             None => return Ok(Scalar::from_i32(-1)),
         };
-        Ok(Scalar::from_i32(this.macos_stat_write_buf(metadata, buf_op)?))
+        Ok(Scalar::from_i32(this.write_stat_buf(metadata, buf_op)?))
+    }
+
+    fn fstatat(
+        &mut self,
+        dirfd_op: &OpTy<'tcx, Provenance>,
+        path_op: &OpTy<'tcx, Provenance>,
+        buf_op: &OpTy<'tcx, Provenance>,
+        flags_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let dirfd = this.read_scalar(dirfd_op)?.to_i32()?;
+        let path_scalar = this.read_pointer(path_op)?;
+        let path = this.read_path_from_c_str(path_scalar)?.into_owned();
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+
+        let at_symlink_nofollow = this.eval_libc_i32("AT_SYMLINK_NOFOLLOW");
+        if flags & !at_symlink_nofollow != 0 {
+            throw_unsup_format!(
+                "unsupported flags {:#x} for `fstatat`",
+                flags & !at_symlink_nofollow
+            );
+        }
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`fstatat`", reject_with)?;
+            let eacc = this.eval_libc("EACCES");
+            this.set_last_error(eacc)?;
+            return Ok(-1);
+        }
+
+        let Some(path) = this.resolve_path_at(dirfd, &path)? else {
+            return Ok(-1);
+        };
+        // Like `lstat`, `AT_SYMLINK_NOFOLLOW` queries a symlink itself.
+        let follow_symlink = flags & at_symlink_nofollow == 0;
+        let metadata = match FileMetadata::from_path(this, &path, follow_symlink)? {
+            Some(metadata) => metadata,
+            None => return Ok(-1), // `FileMetadata` has set errno
+        };
+
+        this.write_stat_buf(metadata, buf_op)
     }
 
     fn linux_statx(
@@ -623,8 +1187,11 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             )
         }
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        // Reject if isolation is enabled, unless we query a file description that only lives
+        // in memory.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !(path.as_os_str().is_empty() && empty_path_flag && this.is_in_memory_fd(dirfd))
+        {
             this.reject_in_isolation("`statx`", reject_with)?;
             let ecode = if path.is_absolute() || dirfd == this.eval_libc_i32("AT_FDCWD") {
                 // since `path` is provided, either absolute or
@@ -973,11 +1540,129 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         Ok(Scalar::from_maybe_pointer(entry.unwrap_or_else(Pointer::null), this))
     }
 
-    fn macos_fbsd_readdir_r(
+    fn linux_getdents64(
         &mut self,
-        dirp_op: &OpTy<'tcx, Provenance>,
-        entry_op: &OpTy<'tcx, Provenance>,
-        result_op: &OpTy<'tcx, Provenance>,
+        fd: i32,
+        dirp: Pointer<Option<Provenance>>,
+        count: u64,
+    ) -> InterpResult<'tcx, i64> {
+        let this = self.eval_context_mut();
+
+        this.assert_target_os("linux", "getdents64");
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`getdents64`", reject_with)?;
+            return this.fd_not_found();
+        }
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access(dirp, Size::from_bytes(count), CheckInAllocMsg::MemoryAccessTest)?;
+
+        // The buffer is filled with `linux_dirent64` records, which have the same layout as
+        // `dirent64`, except that `d_name` is only as long as needed, and each record is padded
+        // to the alignment of `dirent64`.
+        let dirent64_layout = this.libc_ty_layout("dirent64");
+        let d_name_offset = dirent64_layout.fields.offset(4 /* d_name */).bytes();
+        let align = dirent64_layout.align.abi.bytes();
+
+        let Some(file_descriptor) = this.machine.fds.get_mut(fd) else {
+            return this.fd_not_found();
+        };
+        let Some(dir) = file_descriptor.downcast_mut::<DirHandle>() else {
+            let enotdir = this.eval_libc("ENOTDIR");
+            this.set_last_error(enotdir)?;
+            return Ok(-1);
+        };
+
+        // Take as many entries as fit into the buffer.
+        let mut records = Vec::new();
+        let mut size: u64 = 0;
+        let mut read_state = dir.read_state.borrow_mut();
+        let end = loop {
+            let entry = match read_state.peek_entry(&dir.path) {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break Ok(true),
+                Err(e) => break Err(e),
+            };
+            let mut name = entry.file_name(); // not a Path as there are no separators!
+            name.push("\0"); // Add a NUL terminator
+            let reclen = d_name_offset
+                .checked_add(u64::try_from(name.len()).unwrap())
+                .unwrap()
+                .next_multiple_of(align);
+            if size.checked_add(reclen).unwrap() > count {
+                break Ok(false);
+            }
+            // If the host is a Unix system, fill in the inode number with its real value.
+            // If not, use 0 as a fallback value.
+            #[cfg(unix)]
+            let ino = std::os::unix::fs::DirEntryExt::ino(entry);
+            #[cfg(not(unix))]
+            let ino = 0u64;
+            let file_type = entry.file_type();
+
+            read_state.pending = None;
+            read_state.position = read_state.position.checked_add(1).unwrap();
+            // `d_off` is the position of the next entry.
+            records.push((ino, read_state.position, reclen, file_type, name));
+            size = size.checked_add(reclen).unwrap();
+        };
+        drop(read_state);
+        if records.is_empty() {
+            match end {
+                // The end of the directory has been reached.
+                Ok(true) => return Ok(0),
+                // Not even a single entry fits into the buffer.
+                Ok(false) => {
+                    let einval = this.eval_libc("EINVAL");
+                    this.set_last_error(einval)?;
+                    return Ok(-1);
+                }
+                Err(e) => {
+                    this.set_last_error_from_io_error(e.kind())?;
+                    return Ok(-1);
+                }
+            }
+        }
+
+        let mut offset: u64 = 0;
+        for (ino, off, reclen, file_type, name) in records {
+            let record = dirp.offset(Size::from_bytes(offset), this)?;
+            let file_type = this.file_type_to_d_type(file_type)?;
+            this.write_int_fields_named(
+                &[
+                    ("d_ino", ino.into()),
+                    ("d_off", off.into()),
+                    ("d_reclen", reclen.into()),
+                    ("d_type", file_type.into()),
+                ],
+                &this.ptr_to_mplace(record, dirent64_layout),
+            )?;
+            // Write the name, followed by zeros up to the end of the record.
+            let name_ptr = record.offset(Size::from_bytes(d_name_offset), this)?;
+            let padding = reclen
+                .checked_sub(d_name_offset)
+                .and_then(|len| len.checked_sub(u64::try_from(name.len()).unwrap()))
+                .unwrap();
+            this.write_bytes_ptr(
+                name_ptr,
+                name.as_encoded_bytes()
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(0).take(usize::try_from(padding).unwrap())),
+            )?;
+            offset = offset.checked_add(reclen).unwrap();
+        }
+
+        Ok(i64::try_from(offset).unwrap())
+    }
+
+    fn macos_fbsd_readdir_r(
+        &mut self,
+        dirp_op: &OpTy<'tcx, Provenance>,
+        entry_op: &OpTy<'tcx, Provenance>,
+        result_op: &OpTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
@@ -1124,32 +1809,24 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
     fn ftruncate64(&mut self, fd: i32, length: i128) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        // Reject if isolation is enabled. In-memory files can still be truncated.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && this.machine.fds.is_fd(fd)
+            && !this.is_in_memory_fd(fd)
+        {
             this.reject_in_isolation("`ftruncate64`", reject_with)?;
-            // Set error code as "EBADF" (bad fd)
-            return Ok(Scalar::from_i32(this.fd_not_found()?));
+            let eperm = this.eval_libc("EPERM");
+            this.set_last_error(eperm)?;
+            return Ok(Scalar::from_i32(-1));
         }
 
+        let communicate = this.machine.communicate();
         Ok(Scalar::from_i32(if let Some(file_descriptor) = this.machine.fds.get_mut(fd) {
-            // FIXME: Support ftruncate64 for all FDs
-            let FileHandle { file, writable } =
-                file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
-                    err_unsup_format!(
-                        "`ftruncate64` is only supported on file-backed file descriptors"
-                    )
-                })?;
-            if *writable {
-                if let Ok(length) = length.try_into() {
-                    let result = file.set_len(length);
-                    this.try_unwrap_io_result(result.map(|_| 0i32))?
-                } else {
-                    let einval = this.eval_libc("EINVAL");
-                    this.set_last_error(einval)?;
-                    -1
-                }
+            // Negative lengths are rejected.
+            if let Ok(length) = length.try_into() {
+                let result = file_descriptor.ftruncate(communicate, length)?;
+                this.try_unwrap_io_result(result.map(|()| 0i32))?
             } else {
-                // The file is not writable
                 let einval = this.eval_libc("EINVAL");
                 this.set_last_error(einval)?;
                 -1
@@ -1159,6 +1836,127 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         }))
     }
 
+    /// Implements `posix_fadvise`. The advice is only a hint, so we just validate the arguments.
+    /// Like the real function, this returns an error number instead of setting `errno`.
+    fn posix_fadvise(
+        &mut self,
+        fd: i32,
+        len: i128,
+        advice: i32,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        if !this.machine.fds.is_fd(fd) {
+            return Ok(this.eval_libc("EBADF"));
+        }
+        let advices = [
+            "POSIX_FADV_NORMAL",
+            "POSIX_FADV_RANDOM",
+            "POSIX_FADV_SEQUENTIAL",
+            "POSIX_FADV_WILLNEED",
+            "POSIX_FADV_DONTNEED",
+            "POSIX_FADV_NOREUSE",
+        ];
+        if len < 0 || !advices.iter().any(|name| this.eval_libc_i32(name) == advice) {
+            return Ok(this.eval_libc("EINVAL"));
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Implements `posix_fallocate`: grows the file to `offset + len` bytes, filling it with zeros,
+    /// unless it already is at least that long. Like the real function, this returns an error
+    /// number instead of setting `errno`.
+    fn posix_fallocate(
+        &mut self,
+        fd: i32,
+        offset: i128,
+        len: i128,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        // Isolation check is done via `FileDescriptor` trait, as for `ftruncate`: host files
+        // cannot even be opened with isolation enabled, but in-memory files can still grow.
+
+        let communicate = this.machine.communicate();
+        let Some(file_descriptor) = this.machine.fds.get_mut(fd) else {
+            return Ok(this.eval_libc("EBADF"));
+        };
+        if file_descriptor.access_mode() == AccessMode::ReadOnly {
+            return Ok(this.eval_libc("EBADF"));
+        }
+        if offset < 0 || len <= 0 {
+            return Ok(this.eval_libc("EINVAL"));
+        }
+        let Some(new_size) = offset.checked_add(len).and_then(|end| u64::try_from(end).ok()) else {
+            return Ok(this.eval_libc("EFBIG"));
+        };
+        // Only regular files can be allocated; those are exactly the ones that can be mapped.
+        if !file_descriptor.is_mmappable() {
+            return Ok(this.eval_libc("ENODEV"));
+        }
+
+        // Determine the size of the file, and restore the file offset that this changes.
+        let pos = file_descriptor.seek(communicate, SeekFrom::Current(0))?;
+        let size = file_descriptor.seek(communicate, SeekFrom::End(0))?;
+        let size = match (pos, size) {
+            (Ok(pos), Ok(size)) =>
+                file_descriptor.seek(communicate, SeekFrom::Start(pos))?.map(|_| size),
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+        let result = match size {
+            Ok(size) if new_size > size => file_descriptor.ftruncate(communicate, new_size)?,
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => Ok(Scalar::from_i32(0)),
+            Err(e) => this.io_error_to_errnum(e.kind()),
+        }
+    }
+
+    fn flock(&mut self, fd: i32, op: i32) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let lock_sh = this.eval_libc_i32("LOCK_SH");
+        let lock_ex = this.eval_libc_i32("LOCK_EX");
+        let lock_nb = this.eval_libc_i32("LOCK_NB");
+        let lock_un = this.eval_libc_i32("LOCK_UN");
+
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        };
+        // FIXME: Support flock for all FDs
+        let FileHandle { flock, .. } =
+            file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
+                err_unsup_format!("`flock` is only supported on file-backed file descriptors")
+            })?;
+        let flock = Rc::clone(flock);
+
+        let nonblocking = op & lock_nb == lock_nb;
+        let kind = match op & !lock_nb {
+            op if op == lock_sh => FlockKind::Shared,
+            op if op == lock_ex => FlockKind::Exclusive,
+            op if op == lock_un => {
+                flock.unlock();
+                return Ok(Scalar::from_i32(0));
+            }
+            _ => {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+        };
+        if flock.lock(kind) {
+            Ok(Scalar::from_i32(0))
+        } else if nonblocking {
+            let ewouldblock = this.eval_libc("EWOULDBLOCK");
+            this.set_last_error(ewouldblock)?;
+            Ok(Scalar::from_i32(-1))
+        } else {
+            throw_unsup_format!("blocking on a conflicting `flock` lock is unsupported");
+        }
+    }
+
     fn fsync(&mut self, fd_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, i32> {
         // On macOS, `fsync` (unlike `fcntl(F_FULLFSYNC)`) does not wait for the
         // underlying disk to finish writing. In the interest of host compatibility,
@@ -1169,11 +1967,13 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        // Reject if isolation is enabled, unless the file description only lives in memory.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.is_in_memory_fd(fd)
+        {
             this.reject_in_isolation("`fsync`", reject_with)?;
-            // Set error code as "EBADF" (bad fd)
-            return this.fd_not_found();
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(-1);
         }
 
         return self.ffullsync_fd(fd);
@@ -1181,15 +1981,11 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
     fn ffullsync_fd(&mut self, fd: i32) -> InterpResult<'tcx, i32> {
         let this = self.eval_context_mut();
+        let communicate = this.machine.communicate();
         let Some(file_descriptor) = this.machine.fds.get(fd) else {
             return Ok(this.fd_not_found()?);
         };
-        // Only regular files support synchronization.
-        let FileHandle { file, writable } =
-            file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
-                err_unsup_format!("`fsync` is only supported on file-backed file descriptors")
-            })?;
-        let io_result = maybe_sync_file(file, *writable, File::sync_all);
+        let io_result = file_descriptor.fsync(communicate)?.map(|()| 0i32);
         this.try_unwrap_io_result(io_result)
     }
 
@@ -1198,22 +1994,20 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        // Reject if isolation is enabled, unless the file description only lives in memory.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.is_in_memory_fd(fd)
+        {
             this.reject_in_isolation("`fdatasync`", reject_with)?;
-            // Set error code as "EBADF" (bad fd)
-            return this.fd_not_found();
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(-1);
         }
 
+        let communicate = this.machine.communicate();
         let Some(file_descriptor) = this.machine.fds.get(fd) else {
             return Ok(this.fd_not_found()?);
         };
-        // Only regular files support synchronization.
-        let FileHandle { file, writable } =
-            file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
-                err_unsup_format!("`fdatasync` is only supported on file-backed file descriptors")
-            })?;
-        let io_result = maybe_sync_file(file, *writable, File::sync_data);
+        let io_result = file_descriptor.fdatasync(communicate)?.map(|()| 0i32);
         this.try_unwrap_io_result(io_result)
     }
 
@@ -1245,29 +2039,27 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             return Ok(Scalar::from_i32(-1));
         }
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        // Reject if isolation is enabled, unless the file description only lives in memory.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.is_in_memory_fd(fd)
+        {
             this.reject_in_isolation("`sync_file_range`", reject_with)?;
-            // Set error code as "EBADF" (bad fd)
-            return Ok(Scalar::from_i32(this.fd_not_found()?));
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(Scalar::from_i32(-1));
         }
 
+        let communicate = this.machine.communicate();
         let Some(file_descriptor) = this.machine.fds.get(fd) else {
             return Ok(Scalar::from_i32(this.fd_not_found()?));
         };
-        // Only regular files support synchronization.
-        let FileHandle { file, writable } =
-            file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
-                err_unsup_format!(
-                    "`sync_data_range` is only supported on file-backed file descriptors"
-                )
-            })?;
-        let io_result = maybe_sync_file(file, *writable, File::sync_data);
+        let io_result = file_descriptor.fdatasync(communicate)?.map(|()| 0i32);
         Ok(Scalar::from_i32(this.try_unwrap_io_result(io_result)?))
     }
 
-    fn readlink(
+    /// Implements `readlink` (with `dirfd` set to `AT_FDCWD`) and `readlinkat`.
+    fn readlinkat(
         &mut self,
+        dirfd: i32,
         pathname_op: &OpTy<'tcx, Provenance>,
         buf_op: &OpTy<'tcx, Provenance>,
         bufsize_op: &OpTy<'tcx, Provenance>,
@@ -1286,6 +2078,10 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             return Ok(-1);
         }
 
+        let Some(pathname) = this.resolve_path_at(dirfd, &pathname)? else {
+            return Ok(-1);
+        };
+        // This fails with `EINVAL` if `pathname` is not a symlink.
         let result = std::fs::read_link(pathname);
         match result {
             Ok(resolved) => {
@@ -1394,14 +2190,204 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             }
         }
     }
-    fn mkstemp(&mut self, template_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, i32> {
+
+    fn statvfs(
+        &mut self,
+        path_op: &OpTy<'tcx, Provenance>,
+        buf_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        this.assert_target_os_is_unix("statvfs");
+
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
+
+        // Under isolation, we describe a made-up filesystem that every path lives on.
+        let stats = if this.machine.communicate() {
+            match FsStats::from_host_path(&path) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    this.set_last_error_from_io_error(e.kind())?;
+                    return Ok(-1);
+                }
+            }
+        } else {
+            FsStats::deterministic()
+        };
+        this.write_statvfs_buf(stats, buf_op)
+    }
+
+    fn fstatvfs(
+        &mut self,
+        fd_op: &OpTy<'tcx, Provenance>,
+        buf_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        this.assert_target_os_is_unix("fstatvfs");
+
+        let fd = this.read_scalar(fd_op)?.to_i32()?;
+
+        let communicate = this.machine.communicate();
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            return this.fd_not_found();
+        };
+        // Only file-backed file descriptors live on a host filesystem we could ask about.
+        let stats = match file_descriptor.downcast_ref::<FileHandle>() {
+            Some(file_handle) if communicate => FsStats::from_host_file(&file_handle.file),
+            _ => Ok(FsStats::deterministic()),
+        };
+        match stats {
+            Ok(stats) => this.write_statvfs_buf(stats, buf_op),
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                Ok(-1)
+            }
+        }
+    }
+
+    fn fchmod(
+        &mut self,
+        fd_op: &OpTy<'tcx, Provenance>,
+        mode_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        this.assert_target_os_is_unix("fchmod");
+
+        let fd = this.read_scalar(fd_op)?.to_i32()?;
+        let mode = if matches!(&*this.tcx.sess.target.os, "macos" | "freebsd") {
+            u32::from(this.read_scalar(mode_op)?.to_u16()?)
+        } else {
+            this.read_scalar(mode_op)?.to_u32()?
+        };
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`fchmod`", reject_with)?;
+            let eperm = this.eval_libc("EPERM");
+            this.set_last_error(eperm)?;
+            return Ok(-1);
+        }
+
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            return this.fd_not_found();
+        };
+        let result = host_fchmod(file_descriptor, mode)?;
+        this.try_unwrap_io_result(result.map(|()| 0))
+    }
+
+    fn fchmodat(
+        &mut self,
+        dirfd_op: &OpTy<'tcx, Provenance>,
+        path_op: &OpTy<'tcx, Provenance>,
+        mode_op: &OpTy<'tcx, Provenance>,
+        flags_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        this.assert_target_os_is_unix("fchmodat");
+
+        let dirfd = this.read_scalar(dirfd_op)?.to_i32()?;
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
+        let mode = if matches!(&*this.tcx.sess.target.os, "macos" | "freebsd") {
+            u32::from(this.read_scalar(mode_op)?.to_u16()?)
+        } else {
+            this.read_scalar(mode_op)?.to_u32()?
+        };
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+
+        // Linux cannot change the mode of a symlink itself, so we do not support
+        // `AT_SYMLINK_NOFOLLOW` on any target.
+        if flags != 0 {
+            throw_unsup_format!("unsupported flags {:#x} for `fchmodat`", flags);
+        }
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`fchmodat`", reject_with)?;
+            let eperm = this.eval_libc("EPERM");
+            this.set_last_error(eperm)?;
+            return Ok(-1);
+        }
+
+        let Some(path) = this.resolve_path_at(dirfd, &path)? else {
+            return Ok(-1);
+        };
+        let result = host_chmod(&path, mode)?;
+        this.try_unwrap_io_result(result.map(|()| 0))
+    }
+
+    fn fchown(
+        &mut self,
+        fd_op: &OpTy<'tcx, Provenance>,
+        owner_op: &OpTy<'tcx, Provenance>,
+        group_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        this.assert_target_os_is_unix("fchown");
+
+        let fd = this.read_scalar(fd_op)?.to_i32()?;
+        // `uid_t` and `gid_t` are `u32` on all supported targets.
+        let owner = this.read_scalar(owner_op)?.to_u32()?;
+        let group = this.read_scalar(group_op)?.to_u32()?;
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`fchown`", reject_with)?;
+            let eperm = this.eval_libc("EPERM");
+            this.set_last_error(eperm)?;
+            return Ok(-1);
+        }
+
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            return this.fd_not_found();
+        };
+        // Whether the owner may be changed is up to the host, so its error is reported as-is.
+        let result = host_fchown(file_descriptor, owner, group)?;
+        this.try_unwrap_io_result(result.map(|()| 0))
+    }
+
+    /// Implements `mkstemp` and `mkstemps` (which keeps the last `suffix_len` bytes of the
+    /// template).
+    fn mkstemp(
+        &mut self,
+        template_op: &OpTy<'tcx, Provenance>,
+        suffix_len: i32,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        this.create_temp(template_op, TempKind::File { suffix_len })
+    }
+
+    fn mkdtemp(
+        &mut self,
+        template_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Pointer<Option<Provenance>>> {
+        let this = self.eval_context_mut();
+
+        // On success, the template has been modified in place to the name of the directory.
+        if this.create_temp(template_op, TempKind::Directory)? == -1 {
+            Ok(Pointer::null())
+        } else {
+            this.read_pointer(template_op)
+        }
+    }
+
+    /// Creates a file or directory with a unique name generated from the template. Returns the
+    /// file descriptor of the new file, or 0 for a new directory.
+    fn create_temp(
+        &mut self,
+        template_op: &OpTy<'tcx, Provenance>,
+        kind: TempKind,
+    ) -> InterpResult<'tcx, i32> {
         use rand::seq::SliceRandom;
 
         // POSIX defines the template string.
         const TEMPFILE_TEMPLATE_STR: &str = "XXXXXX";
 
         let this = self.eval_context_mut();
-        this.assert_target_os_is_unix("mkstemp");
+        let (name, suffix_len) = match kind {
+            TempKind::File { suffix_len: 0 } => ("mkstemp", 0),
+            TempKind::File { suffix_len } => ("mkstemps", suffix_len),
+            TempKind::Directory => ("mkdtemp", 0),
+        };
+        this.assert_target_os_is_unix(name);
 
         // POSIX defines the maximum number of attempts before failure.
         //
@@ -1422,7 +2408,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
-            this.reject_in_isolation("`mkstemp`", reject_with)?;
+            this.reject_in_isolation(&format!("`{name}`"), reject_with)?;
             let eacc = this.eval_libc("EACCES");
             this.set_last_error(eacc)?;
             return Ok(-1);
@@ -1434,9 +2420,17 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         // At this point we have one `&[u8]` that represents the template and one `&[u8]`
         // that represents the expected suffix.
 
-        // Now we figure out the index of the slice we expect to contain the suffix.
-        let start_pos = template_bytes.len().saturating_sub(suffix_bytes.len());
-        let end_pos = template_bytes.len();
+        // Now we figure out the index of the slice we expect to contain the suffix, which is
+        // followed by `suffix_len` bytes that are kept as they are.
+        let Some(end_pos) = usize::try_from(suffix_len)
+            .ok()
+            .and_then(|suffix_len| template_bytes.len().checked_sub(suffix_len))
+        else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+        let start_pos = end_pos.saturating_sub(suffix_bytes.len());
         let last_six_char_bytes = &template_bytes[start_pos..end_pos];
 
         // If we don't find the suffix, it is an error.
@@ -1493,12 +2487,39 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
             let possibly_unique = std::env::temp_dir().join::<PathBuf>(p.into());
 
-            let file = fopts.open(possibly_unique);
+            if let TempKind::Directory = kind {
+                #[cfg_attr(not(unix), allow(unused_mut))]
+                let mut builder = DirBuilder::new();
+                // Do not allow others to access this directory.
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::DirBuilderExt;
+                    builder.mode(0o700);
+                }
+                match builder.create(&possibly_unique) {
+                    Ok(()) => return Ok(0),
+                    // If the random directory already exists, keep trying.
+                    Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                    Err(e) => {
+                        this.set_last_error_from_io_error(e.kind())?;
+                        return Ok(-1);
+                    }
+                }
+            }
+
+            let file = fopts.open(&possibly_unique);
 
             match file {
                 Ok(f) => {
+                    let flock = Rc::new(FlockHandle::new(this.flock_state(&possibly_unique)));
                     let fh = &mut this.machine.fds;
-                    let fd = fh.insert_fd(Box::new(FileHandle { file: f, writable: true }));
+                    let fd = fh.insert_fd(Box::new(FileHandle {
+                        file: f,
+                        readable: true,
+                        writable: true,
+                        append: Rc::default(),
+                        flock,
+                    }));
                     return Ok(fd);
                 }
                 Err(e) =>
@@ -1523,6 +2544,16 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
     }
 }
 
+/// What `create_temp` creates.
+#[derive(Clone, Copy)]
+pub enum TempKind {
+    /// A file, as created by `mkstemp` and `mkstemps`. The last `suffix_len` bytes of the
+    /// template are kept as they are.
+    File { suffix_len: i32 },
+    /// A directory, as created by `mkdtemp`.
+    Directory,
+}
+
 /// Extracts the number of seconds and nanoseconds elapsed between `time` and the unix epoch when
 /// `time` is Ok. Returns `None` if `time` is an error. Fails if `time` happens before the unix
 /// epoch.
@@ -1537,6 +2568,160 @@ fn extract_sec_and_nsec<'tcx>(
         .transpose()
 }
 
+/// Describes a filesystem, as reported by `statvfs`.
+struct FsStats {
+    block_size: u64,
+    fragment_size: u64,
+    blocks: u64,
+    blocks_free: u64,
+    blocks_available: u64,
+    files: u64,
+    files_free: u64,
+    files_available: u64,
+    fsid: u64,
+    flags: u64,
+    max_name_len: u64,
+}
+
+impl FsStats {
+    /// A plausible filesystem that does not depend on the host: 4 GiB in 4 KiB blocks, half of
+    /// them free.
+    fn deterministic() -> FsStats {
+        FsStats {
+            block_size: 4096,
+            fragment_size: 4096,
+            blocks: 1 << 20,
+            blocks_free: 1 << 19,
+            blocks_available: 1 << 19,
+            files: 1 << 18,
+            files_free: 1 << 17,
+            files_available: 1 << 17,
+            fsid: 0,
+            flags: 0,
+            max_name_len: 255,
+        }
+    }
+
+    #[cfg(unix)]
+    fn from_host_path(path: &Path) -> io::Result<FsStats> {
+        use std::os::unix::ffi::OsStrExt;
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+        let mut buf = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: `path` is a valid C string and `buf` is valid for writes.
+        if unsafe { libc::statvfs(path.as_ptr(), buf.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `statvfs` succeeded, so it initialized `buf`.
+        Ok(FsStats::from_host(unsafe { buf.assume_init() }))
+    }
+
+    #[cfg(unix)]
+    fn from_host_file(file: &File) -> io::Result<FsStats> {
+        use std::os::unix::io::AsRawFd;
+        let mut buf = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: the file descriptor is open and `buf` is valid for writes.
+        if unsafe { libc::fstatvfs(file.as_raw_fd(), buf.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fstatvfs` succeeded, so it initialized `buf`.
+        Ok(FsStats::from_host(unsafe { buf.assume_init() }))
+    }
+
+    // The field types differ between hosts, so some of these conversions are no-ops.
+    #[cfg(unix)]
+    #[allow(clippy::useless_conversion)]
+    fn from_host(stat: libc::statvfs) -> FsStats {
+        FsStats {
+            block_size: u64::from(stat.f_bsize),
+            fragment_size: u64::from(stat.f_frsize),
+            blocks: u64::from(stat.f_blocks),
+            blocks_free: u64::from(stat.f_bfree),
+            blocks_available: u64::from(stat.f_bavail),
+            files: u64::from(stat.f_files),
+            files_free: u64::from(stat.f_ffree),
+            files_available: u64::from(stat.f_favail),
+            fsid: u64::from(stat.f_fsid),
+            flags: u64::from(stat.f_flag),
+            max_name_len: u64::from(stat.f_namemax),
+        }
+    }
+
+    // Non-unix hosts have no `statvfs`, so we fall back to the made-up filesystem.
+    #[cfg(not(unix))]
+    fn from_host_path(_path: &Path) -> io::Result<FsStats> {
+        Ok(FsStats::deterministic())
+    }
+
+    #[cfg(not(unix))]
+    fn from_host_file(_file: &File) -> io::Result<FsStats> {
+        Ok(FsStats::deterministic())
+    }
+}
+
+/// Turns the return value of a host libc function that returns 0 on success into an `io::Result`.
+#[cfg(unix)]
+fn host_io_result(ret: libc::c_int) -> io::Result<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+// `mode_t` is a `u16` on some hosts, so the conversion is a no-op on others.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_fallible_conversions)]
+fn host_fchmod<'tcx>(fd: &dyn FileDescriptor, mode: u32) -> InterpResult<'tcx, io::Result<()>> {
+    let Some(host_fd) = fd.as_unix_host_fd() else {
+        throw_unsup_format!("`fchmod` is only supported on file-backed file descriptors");
+    };
+    // Only the permission bits are meaningful, and they fit into the host's `mode_t` even where
+    // it is 16 bits wide (as on macOS).
+    let mode = libc::mode_t::try_from(mode & 0o7777).unwrap();
+    // SAFETY: `host_fd` is kept open by `fd`.
+    Ok(host_io_result(unsafe { libc::fchmod(host_fd, mode) }))
+}
+
+#[cfg(unix)]
+fn host_chmod<'tcx>(path: &Path, mode: u32) -> InterpResult<'tcx, io::Result<()>> {
+    use std::os::unix::fs::PermissionsExt;
+    // Like in `host_fchmod`, only the permission bits are passed on.
+    Ok(std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode & 0o7777)))
+}
+
+#[cfg(unix)]
+fn host_fchown<'tcx>(
+    fd: &dyn FileDescriptor,
+    owner: u32,
+    group: u32,
+) -> InterpResult<'tcx, io::Result<()>> {
+    let Some(host_fd) = fd.as_unix_host_fd() else {
+        throw_unsup_format!("`fchown` is only supported on file-backed file descriptors");
+    };
+    // SAFETY: `host_fd` is kept open by `fd`.
+    Ok(host_io_result(unsafe { libc::fchown(host_fd, owner, group) }))
+}
+
+// Non-unix hosts have no notion of Unix permissions and owners.
+#[cfg(not(unix))]
+fn host_fchmod<'tcx>(_fd: &dyn FileDescriptor, _mode: u32) -> InterpResult<'tcx, io::Result<()>> {
+    throw_unsup_format!("`fchmod` is not supported on non-Unix hosts");
+}
+
+#[cfg(not(unix))]
+fn host_chmod<'tcx>(_path: &Path, _mode: u32) -> InterpResult<'tcx, io::Result<()>> {
+    throw_unsup_format!("`fchmodat` is not supported on non-Unix hosts");
+}
+
+#[cfg(not(unix))]
+fn host_fchown<'tcx>(
+    _fd: &dyn FileDescriptor,
+    _owner: u32,
+    _group: u32,
+) -> InterpResult<'tcx, io::Result<()>> {
+    throw_unsup_format!("`fchown` is not supported on non-Unix hosts");
+}
+
 /// Stores a file's metadata in order to avoid code duplication in the different metadata related
 /// shims.
 struct FileMetadata {
@@ -1563,18 +2748,28 @@ impl FileMetadata {
         ecx: &mut MiriInterpCx<'_, 'tcx>,
         fd: i32,
     ) -> InterpResult<'tcx, Option<FileMetadata>> {
-        let option = ecx.machine.fds.get(fd);
-        let file = match option {
-            Some(file_descriptor) =>
-                &file_descriptor
-                    .downcast_ref::<FileHandle>()
-                    .ok_or_else(|| {
-                        err_unsup_format!(
-                            "obtaining metadata is only supported on file-backed file descriptors"
-                        )
-                    })?
-                    .file,
-            None => return ecx.fd_not_found().map(|_: i32| None),
+        let Some(file_descriptor) = ecx.machine.fds.get(fd) else {
+            return ecx.fd_not_found().map(|_: i32| None);
+        };
+        if let Some(stat) = file_descriptor.stat() {
+            // There is no host file to take the timestamps from.
+            let mode = ecx.eval_libc(stat.kind.mode_name());
+            return Ok(Some(FileMetadata {
+                mode,
+                size: stat.size,
+                created: None,
+                accessed: None,
+                modified: None,
+            }));
+        }
+        let file = if let Some(file_handle) = file_descriptor.downcast_ref::<FileHandle>() {
+            &file_handle.file
+        } else if let Some(dir_handle) = file_descriptor.downcast_ref::<DirHandle>() {
+            &dir_handle.file
+        } else {
+            throw_unsup_format!(
+                "obtaining metadata is only supported on file-backed file descriptors"
+            );
         };
         let metadata = file.metadata();
 
@@ -1615,3 +2810,32 @@ impl FileMetadata {
         Ok(Some(FileMetadata { mode, size, created, accessed, modified }))
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_handle_host_fd() {
+        let path = std::env::temp_dir().join("miri_test_file_handle_host_fd.txt");
+        std::fs::write(&path, b"host").unwrap();
+        let file = File::open(&path).unwrap();
+        let flock = Rc::new(FlockHandle::new(Rc::default()));
+        let fh: Box<dyn FileDescriptor> = Box::new(FileHandle {
+            file,
+            readable: true,
+            writable: false,
+            append: Rc::default(),
+            flock,
+        });
+
+        // The host file descriptor refers to the same file.
+        let fd = fh.as_unix_host_fd().unwrap();
+        let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+        assert_eq!(unsafe { libc::fstat(fd, stat.as_mut_ptr()) }, 0);
+        assert_eq!(unsafe { stat.assume_init() }.st_size, 4);
+
+        drop(fh);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
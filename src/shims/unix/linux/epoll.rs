@@ -1,19 +1,21 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::io;
-
-use rustc_data_structures::fx::FxHashMap;
+use std::rc::Rc;
+use std::time::Duration;
 
 use crate::shims::unix::*;
 use crate::*;
 
 /// An `Epoll` file descriptor connects file handles and epoll events
-#[derive(Clone, Debug, Default)]
+#[derive(Debug, Default)]
 struct Epoll {
     /// The file descriptors we are watching, and what we are watching for.
-    file_descriptors: FxHashMap<i32, EpollEvent>,
+    /// This is shared by all duplicates of the epoll file descriptor.
+    file_descriptors: Rc<RefCell<BTreeMap<i32, EpollEvent>>>,
 }
 
 /// Epoll Events associate events with data.
-/// These fields are currently unused by miri.
 /// This matches the `epoll_event` struct defined
 /// by the epoll_ctl man page. For more information
 /// see the man page:
@@ -21,23 +23,104 @@ struct Epoll {
 /// <https://man7.org/linux/man-pages/man2/epoll_ctl.2.html>
 #[derive(Clone, Debug)]
 struct EpollEvent {
-    #[allow(dead_code)]
     events: u32,
     /// `Scalar<Provenance>` is used to represent the
     /// `epoll_data` type union.
-    #[allow(dead_code)]
     data: Scalar<Provenance>,
 }
 
+/// The readiness check of `epoll_wait`.
+#[derive(Clone)]
+struct EpollWaitCheck {
+    epfd: i32,
+    events: Pointer<Option<Provenance>>,
+    maxevents: u64,
+}
+
+impl VisitProvenance for EpollWaitCheck {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        let EpollWaitCheck { epfd: _, events, maxevents: _ } = self;
+        events.visit_provenance(visit);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> ReadinessCheck<'mir, 'tcx> for EpollWaitCheck {
+    /// Writes the events of up to `maxevents` ready file descriptors to the `events` array, and
+    /// returns their number, if there are any.
+    ///
+    /// All file descriptors are level-triggered: they are reported as long as they are ready.
+    /// Reporting events more often than needed is fine for edge-triggered ones, too.
+    fn check(
+        &self,
+        this: &mut MiriInterpCx<'mir, 'tcx>,
+    ) -> InterpResult<'tcx, Option<Scalar<Provenance>>> {
+        let epollin = this.eval_libc("EPOLLIN").to_u32()?;
+        let epollout = this.eval_libc("EPOLLOUT").to_u32()?;
+
+        let Some(epoll) = this.machine.fds.get(self.epfd).and_then(|fd| fd.downcast_ref::<Epoll>())
+        else {
+            // The epoll file descriptor was closed while we were waiting.
+            let ebadf = this.eval_libc("EBADF");
+            this.set_last_error(ebadf)?;
+            return Ok(Some(Scalar::from_i32(-1)));
+        };
+        let mut ready = Vec::new();
+        for (&fd, event) in epoll.file_descriptors.borrow().iter() {
+            // Closed file descriptors are not reported anymore.
+            let Some(file_descriptor) = this.machine.fds.get(fd) else { continue };
+            let mut events = 0;
+            if file_descriptor.is_read_ready() {
+                events |= event.events & epollin;
+            }
+            if file_descriptor.is_write_ready() {
+                events |= event.events & epollout;
+            }
+            if events != 0 {
+                ready.push((events, event.data));
+            }
+            if u64::try_from(ready.len()).unwrap() == self.maxevents {
+                break;
+            }
+        }
+        if ready.is_empty() {
+            return Ok(None);
+        }
+
+        let event_layout = this.libc_ty_layout("epoll_event");
+        let first = this.ptr_to_mplace(self.events, event_layout);
+        for (i, &(events, data)) in ready.iter().enumerate() {
+            let event =
+                first.offset(event_layout.size * u64::try_from(i).unwrap(), event_layout, this)?;
+            this.write_scalar(Scalar::from_u32(events), &this.project_field(&event, 0)?)?;
+            this.write_scalar(data, &this.project_field(&event, 1)?)?;
+        }
+        Ok(Some(Scalar::from_i32(ready.len().try_into().unwrap())))
+    }
+
+    fn ready_at(
+        &self,
+        this: &mut MiriInterpCx<'mir, 'tcx>,
+    ) -> InterpResult<'tcx, Option<Duration>> {
+        let Some(epoll) = this.machine.fds.get(self.epfd).and_then(|fd| fd.downcast_ref::<Epoll>())
+        else {
+            return Ok(None);
+        };
+        Ok(epoll
+            .file_descriptors
+            .borrow()
+            .keys()
+            .filter_map(|&fd| this.machine.fds.get(fd)?.ready_at())
+            .min())
+    }
+}
+
 impl FileDescriptor for Epoll {
     fn name(&self) -> &'static str {
         "epoll"
     }
 
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
-        // FIXME: this is probably wrong -- check if the `dup`ed descriptor truly uses an
-        // independent event set.
-        Ok(Box::new(self.clone()))
+        Ok(Box::new(Epoll { file_descriptors: Rc::clone(&self.file_descriptors) }))
     }
 
     fn close<'tcx>(
@@ -64,13 +147,12 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         let flags = this.read_scalar(flags)?.to_i32()?;
 
         let epoll_cloexec = this.eval_libc_i32("EPOLL_CLOEXEC");
-        if flags == epoll_cloexec {
-            // Miri does not support exec, so this flag has no effect.
-        } else if flags != 0 {
+        if flags != epoll_cloexec && flags != 0 {
             throw_unsup_format!("epoll_create1 flags {flags} are not implemented");
         }
 
         let fd = this.machine.fds.insert_fd(Box::new(Epoll::default()));
+        this.machine.fds.set_cloexec(fd, flags == epoll_cloexec).unwrap();
         Ok(Scalar::from_i32(fd))
     }
 
@@ -105,7 +187,21 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         let epoll_ctl_mod = this.eval_libc_i32("EPOLL_CTL_MOD");
         let epoll_ctl_del = this.eval_libc_i32("EPOLL_CTL_DEL");
 
-        if op == epoll_ctl_add || op == epoll_ctl_mod {
+        let Some(epoll) = this.machine.fds.get(epfd) else {
+            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        };
+        let file_descriptors = Rc::clone(
+            &epoll
+                .downcast_ref::<Epoll>()
+                .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?
+                .file_descriptors,
+        );
+        if !this.machine.fds.is_fd(fd) {
+            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        }
+        let mut file_descriptors = file_descriptors.borrow_mut();
+
+        let error = if op == epoll_ctl_add || op == epoll_ctl_mod {
             let event = this.deref_pointer_as(event, this.libc_ty_layout("epoll_event"))?;
 
             let events = this.project_field(&event, 0)?;
@@ -114,31 +210,35 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             let data = this.read_scalar(&data)?;
             let event = EpollEvent { events, data };
 
-            if let Some(epfd) = this.machine.fds.get_mut(epfd) {
-                let epfd = epfd
-                    .downcast_mut::<Epoll>()
-                    .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
-
-                epfd.file_descriptors.insert(fd, event);
-                Ok(Scalar::from_i32(0))
+            if op == epoll_ctl_add {
+                match file_descriptors.try_insert(fd, event) {
+                    Ok(_) => None,
+                    Err(_) => Some("EEXIST"),
+                }
             } else {
-                Ok(Scalar::from_i32(this.fd_not_found()?))
+                match file_descriptors.get_mut(&fd) {
+                    Some(old_event) => {
+                        *old_event = event;
+                        None
+                    }
+                    None => Some("ENOENT"),
+                }
             }
         } else if op == epoll_ctl_del {
-            if let Some(epfd) = this.machine.fds.get_mut(epfd) {
-                let epfd = epfd
-                    .downcast_mut::<Epoll>()
-                    .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
-
-                epfd.file_descriptors.remove(&fd);
-                Ok(Scalar::from_i32(0))
-            } else {
-                Ok(Scalar::from_i32(this.fd_not_found()?))
+            match file_descriptors.remove(&fd) {
+                Some(_) => None,
+                None => Some("ENOENT"),
             }
         } else {
-            let einval = this.eval_libc("EINVAL");
-            this.set_last_error(einval)?;
+            Some("EINVAL")
+        };
+
+        if let Some(error) = error {
+            let error = this.eval_libc(error);
+            this.set_last_error(error)?;
             Ok(Scalar::from_i32(-1))
+        } else {
+            Ok(Scalar::from_i32(0))
         }
     }
 
@@ -148,28 +248,21 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
     /// list about file descriptors in the interest list that have some
     /// events available. Up to `maxevents` are returned by `epoll_wait()`.
     /// The `maxevents` argument must be greater than zero.
-
+    ///
     /// The `timeout` argument specifies the number of milliseconds that
-    /// `epoll_wait()` will block. Time is measured against the
-    /// CLOCK_MONOTONIC clock.
-
-    /// A call to `epoll_wait()` will block until either:
-    /// • a file descriptor delivers an event;
-    /// • the call is interrupted by a signal handler; or
-    /// • the timeout expires.
-
-    /// Note that the timeout interval will be rounded up to the system
-    /// clock granularity, and kernel scheduling delays mean that the
-    /// blocking interval may overrun by a small amount. Specifying a
-    /// timeout of -1 causes `epoll_wait()` to block indefinitely, while
-    /// specifying a timeout equal to zero cause `epoll_wait()` to return
-    /// immediately, even if no events are available.
+    /// `epoll_wait()` will block. Specifying a timeout of -1 causes
+    /// `epoll_wait()` to block indefinitely, while specifying a timeout
+    /// equal to zero cause `epoll_wait()` to return immediately, even if
+    /// no events are available.
     ///
     /// On success, `epoll_wait()` returns the number of file descriptors
     /// ready for the requested I/O, or zero if no file descriptor became
     /// ready during the requested timeout milliseconds. On failure,
     /// `epoll_wait()` returns -1 and errno is set to indicate the error.
     ///
+    /// We only report `EPOLLIN` and `EPOLLOUT`, and treat all file
+    /// descriptors as level-triggered.
+    ///
     /// <https://man7.org/linux/man-pages/man2/epoll_wait.2.html>
     fn epoll_wait(
         &mut self,
@@ -177,23 +270,31 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         events: &OpTy<'tcx, Provenance>,
         maxevents: &OpTy<'tcx, Provenance>,
         timeout: &OpTy<'tcx, Provenance>,
-    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         let epfd = this.read_scalar(epfd)?.to_i32()?;
-        let _events = this.read_scalar(events)?.to_pointer(this)?;
-        let _maxevents = this.read_scalar(maxevents)?.to_i32()?;
-        let _timeout = this.read_scalar(timeout)?.to_i32()?;
-
-        if let Some(epfd) = this.machine.fds.get_mut(epfd) {
-            let _epfd = epfd
-                .downcast_mut::<Epoll>()
-                .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
+        let events = this.read_pointer(events)?;
+        let maxevents = this.read_scalar(maxevents)?.to_i32()?;
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
 
-            // FIXME return number of events ready when scheme for marking events ready exists
-            throw_unsup_format!("returning ready events from epoll_wait is not yet implemented");
-        } else {
-            Ok(Scalar::from_i32(this.fd_not_found()?))
+        let Some(epoll) = this.machine.fds.get(epfd) else {
+            let result = this.fd_not_found()?;
+            this.write_scalar(Scalar::from_i32(result), dest)?;
+            return Ok(());
+        };
+        if epoll.downcast_ref::<Epoll>().is_none() {
+            throw_unsup_format!("non-epoll FD passed to `epoll_wait`");
         }
+        let Some(maxevents) = u64::try_from(maxevents).ok().filter(|&maxevents| maxevents > 0)
+        else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            this.write_scalar(Scalar::from_i32(-1), dest)?;
+            return Ok(());
+        };
+
+        this.wait_for_readiness(EpollWaitCheck { epfd, events, maxevents }, timeout, dest)
     }
 }
@@ -1,6 +1,8 @@
 //! Linux `eventfd` implementation.
-//! Currently just a stub.
+use std::cell::Cell;
 use std::io;
+use std::io::ErrorKind;
+use std::rc::Rc;
 
 use rustc_middle::ty::TyCtxt;
 use rustc_target::abi::Endian;
@@ -8,8 +10,10 @@ use rustc_target::abi::Endian;
 use crate::shims::unix::*;
 use crate::*;
 
+/// Maximum value that the eventfd counter can hold.
+const MAX_COUNTER: u64 = u64::MAX - 1;
+
 /// A kind of file descriptor created by `eventfd`.
-/// The `Event` type isn't currently written to by `eventfd`.
 /// The interface is meant to keep track of objects associated
 /// with a file descriptor. For more information see the man
 /// page below:
@@ -19,7 +23,12 @@ use crate::*;
 struct Event {
     /// The object contains an unsigned 64-bit integer (uint64_t) counter that is maintained by the
     /// kernel. This counter is initialized with the value specified in the argument initval.
-    val: u64,
+    /// It is shared between all file descriptors referring to the same event object.
+    counter: Rc<Cell<u64>>,
+    /// Whether reads and writes fail with EAGAIN instead of blocking. Like the counter, it
+    /// is shared between all file descriptors referring to the same event object.
+    is_nonblock: Rc<Cell<bool>>,
+    is_semaphore: bool,
 }
 
 impl FileDescriptor for Event {
@@ -28,8 +37,29 @@ impl FileDescriptor for Event {
     }
 
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
-        // FIXME: this is wrong, the new and old FD should refer to the same event object!
-        Ok(Box::new(Event { val: self.val }))
+        Ok(Box::new(Event {
+            counter: Rc::clone(&self.counter),
+            is_nonblock: Rc::clone(&self.is_nonblock),
+            is_semaphore: self.is_semaphore,
+        }))
+    }
+
+    fn is_read_ready(&self) -> bool {
+        self.counter.get() > 0
+    }
+
+    fn is_write_ready(&self) -> bool {
+        // At least a value of 1 can be added without blocking.
+        self.counter.get() < MAX_COUNTER
+    }
+
+    fn is_nonblock(&self) -> bool {
+        self.is_nonblock.get()
+    }
+
+    fn set_nonblock<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.is_nonblock.set(nonblock);
+        Ok(())
     }
 
     fn close<'tcx>(
@@ -39,6 +69,41 @@ impl FileDescriptor for Event {
         Ok(Ok(0))
     }
 
+    /// A read call returns the current value of the counter as an 8-byte integer
+    /// (in native endianness) and resets the counter to zero. In semaphore mode,
+    /// it instead returns 1 and decrements the counter by 1.
+    /// If the counter is zero, the read either blocks until the counter becomes
+    /// nonzero, or fails with the error EAGAIN if the file descriptor has been
+    /// made nonblocking.
+    ///
+    /// A read fails with the error EINVAL if the size of the supplied buffer is
+    /// less than 8 bytes.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(bytes) = bytes.first_chunk_mut::<8>() else {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        };
+        let counter = self.counter.get();
+        if counter == 0 {
+            if self.is_nonblock.get() {
+                return Ok(Err(ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("eventfd: blocking is unsupported");
+        }
+        let num = if self.is_semaphore { 1 } else { counter };
+        self.counter.set(counter.checked_sub(num).unwrap());
+        // Convert from host endianness to target endianness.
+        *bytes = match tcx.sess.target.endian {
+            Endian::Little => num.to_le_bytes(),
+            Endian::Big => num.to_be_bytes(),
+        };
+        Ok(Ok(8))
+    }
+
     /// A write call adds the 8-byte integer value supplied in
     /// its buffer (in native endianness) to the counter.  The maximum value that may be
     /// stored in the counter is the largest unsigned 64-bit value
@@ -47,7 +112,7 @@ impl FileDescriptor for Event {
     /// write either blocks until a read is performed on the
     /// file descriptor, or fails with the error EAGAIN if the
     /// file descriptor has been made nonblocking.
-
+    ///
     /// A write fails with the error EINVAL if the size of the
     /// supplied buffer is less than 8 bytes, or if an attempt is
     /// made to write the value 0xffffffffffffffff.
@@ -57,15 +122,26 @@ impl FileDescriptor for Event {
         bytes: &[u8],
         tcx: TyCtxt<'tcx>,
     ) -> InterpResult<'tcx, io::Result<usize>> {
-        let bytes: [u8; 8] = bytes.try_into().unwrap(); // FIXME fail gracefully when this has the wrong size
+        let Some(&bytes) = bytes.first_chunk::<8>() else {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        };
         // Convert from target endianness to host endianness.
         let num = match tcx.sess.target.endian {
             Endian::Little => u64::from_le_bytes(bytes),
             Endian::Big => u64::from_be_bytes(bytes),
         };
-        // FIXME handle blocking when addition results in exceeding the max u64 value
-        // or fail with EAGAIN if the file descriptor is nonblocking.
-        self.val = self.val.checked_add(num).unwrap();
+        if num == u64::MAX {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        }
+        match self.counter.get().checked_add(num) {
+            Some(new) if new <= MAX_COUNTER => self.counter.set(new),
+            _ => {
+                if self.is_nonblock.get() {
+                    return Ok(Err(ErrorKind::WouldBlock.into()));
+                }
+                throw_unsup_format!("eventfd: blocking is unsupported");
+            }
+        }
         Ok(Ok(8))
     }
 }
@@ -81,11 +157,12 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
     /// `select`, and `close` operations can be performed on the file descriptor. For more
     /// information on these operations, see the man page linked below.
     ///
-    /// The `flags` are not currently implemented for eventfd.
     /// The `flags` may be bitwise ORed to change the behavior of `eventfd`:
     /// `EFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
     /// `EFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
-    /// `EFD_SEMAPHORE` - miri does not support semaphore-like semantics.
+    /// `EFD_SEMAPHORE` - Provide semaphore-like semantics for reads from the new file descriptor.
+    ///
+    /// Operations that would block are not supported.
     ///
     /// <https://linux.die.net/man/2/eventfd>
     fn eventfd(
@@ -105,17 +182,16 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         if flags & (efd_cloexec | efd_nonblock | efd_semaphore) != flags {
             throw_unsup_format!("eventfd: flag {flags:#x} is unsupported");
         }
-        if flags & efd_cloexec == efd_cloexec {
-            // cloexec does nothing as we don't support `exec`
-        }
-        if flags & efd_nonblock == efd_nonblock {
-            // FIXME remember the nonblock flag
-        }
-        if flags & efd_semaphore == efd_semaphore {
-            throw_unsup_format!("eventfd: EFD_SEMAPHORE is unsupported");
-        }
+        let cloexec = flags & efd_cloexec == efd_cloexec;
+        let is_nonblock = flags & efd_nonblock == efd_nonblock;
+        let is_semaphore = flags & efd_semaphore == efd_semaphore;
 
-        let fd = this.machine.fds.insert_fd(Box::new(Event { val: val.into() }));
+        let fd = this.machine.fds.insert_fd(Box::new(Event {
+            counter: Rc::new(Cell::new(val.into())),
+            is_nonblock: Rc::new(Cell::new(is_nonblock)),
+            is_semaphore,
+        }));
+        this.machine.fds.set_cloexec(fd, cloexec).unwrap();
         Ok(Scalar::from_i32(fd))
     }
 }
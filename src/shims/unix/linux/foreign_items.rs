@@ -9,10 +9,12 @@ use shims::foreign_items::EmulateForeignItemResult;
 use shims::unix::linux::epoll::EvalContextExt as _;
 use shims::unix::linux::eventfd::EvalContextExt as _;
 use shims::unix::linux::mem::EvalContextExt as _;
+use shims::unix::linux::memfd::EvalContextExt as _;
 use shims::unix::linux::sync::futex;
+use shims::unix::linux::timerfd::EvalContextExt as _;
 
 pub fn is_dyn_sym(name: &str) -> bool {
-    matches!(name, "getrandom")
+    matches!(name, "getrandom" | "statx")
 }
 
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
@@ -30,11 +32,71 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
         match link_name.as_str() {
             // File related shims (but also see "syscall" below for statx)
+            "statx" => {
+                let [dirfd, pathname, flags, mask, statxbuf] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.linux_statx(dirfd, pathname, flags, mask, statxbuf)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
             "readdir64" => {
                 let [dirp] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 let result = this.linux_readdir64(dirp)?;
                 this.write_scalar(result, dest)?;
             }
+            "getdents64" => {
+                let [fd, dirp, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let dirp = this.read_pointer(dirp)?;
+                let count = this.read_target_usize(count)?;
+                let result = this.linux_getdents64(fd, dirp, count)?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "pread64" => {
+                let [fd, buf, count, offset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let buf = this.read_pointer(buf)?;
+                let count = this.read_target_usize(count)?;
+                let offset = this.read_scalar(offset)?.to_i64()?;
+                let result = this.read(fd, buf, count, Some(offset.into()))?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "pwrite64" => {
+                let [fd, buf, n, offset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let buf = this.read_pointer(buf)?;
+                let count = this.read_target_usize(n)?;
+                let offset = this.read_scalar(offset)?.to_i64()?;
+                trace!("Called pwrite64({:?}, {:?}, {:?}, {:?})", fd, buf, count, offset);
+                let result = this.write(fd, buf, count, Some(offset.into()))?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "sendfile" | "sendfile64" => {
+                let [out_fd, in_fd, offset, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let out_fd = this.read_scalar(out_fd)?.to_i32()?;
+                let in_fd = this.read_scalar(in_fd)?.to_i32()?;
+                let offset_ty = if link_name.as_str() == "sendfile" { "off_t" } else { "off64_t" };
+                let offset = if this.ptr_is_null(this.read_pointer(offset)?)? {
+                    None
+                } else {
+                    Some(this.deref_pointer_as(offset, this.libc_ty_layout(offset_ty))?)
+                };
+                let count = this.read_target_usize(count)?;
+                let result = this.sendfile(out_fd, in_fd, offset.as_ref(), count)?;
+                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+            }
+            "close_range" => {
+                let [first, last, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let first = this.read_scalar(first)?.to_u32()?;
+                let last = this.read_scalar(last)?.to_u32()?;
+                let flags = this.read_scalar(flags)?.to_i32()?;
+                let result = this.close_range(first, last, flags)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
             "sync_file_range" => {
                 let [fd, offset, nbytes, flags] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -42,7 +104,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 this.write_scalar(result, dest)?;
             }
 
-            // epoll, eventfd
+            // epoll, eventfd, memfd, timerfd
             "epoll_create1" => {
                 let [flag] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 let result = this.epoll_create1(flag)?;
@@ -57,8 +119,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             "epoll_wait" => {
                 let [epfd, events, maxevents, timeout] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
-                let result = this.epoll_wait(epfd, events, maxevents, timeout)?;
-                this.write_scalar(result, dest)?;
+                this.epoll_wait(epfd, events, maxevents, timeout, dest)?;
             }
             "eventfd" => {
                 let [val, flag] =
@@ -66,6 +127,30 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 let result = this.eventfd(val, flag)?;
                 this.write_scalar(result, dest)?;
             }
+            "memfd_create" => {
+                let [name, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.memfd_create(name, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "timerfd_create" => {
+                let [clockid, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.timerfd_create(clockid, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "timerfd_settime" => {
+                let [fd, flags, new_value, old_value] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.timerfd_settime(fd, flags, new_value, old_value)?;
+                this.write_scalar(result, dest)?;
+            }
+            "timerfd_gettime" => {
+                let [fd, curr_value] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.timerfd_gettime(fd, curr_value)?;
+                this.write_scalar(result, dest)?;
+            }
 
             // Threading
             "pthread_condattr_setclock" => {
@@ -101,6 +186,17 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                 )?;
                 this.write_scalar(res, dest)?;
             }
+            "prctl" => {
+                // We do not use `check_shim` here because `prctl` is variadic. The argument
+                // count is checked in `prctl`.
+                this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
+                prctl(this, args, dest)?;
+            }
+            "gettid" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.gettid()?;
+                this.write_scalar(result, dest)?;
+            }
 
             // Dynamically invoked syscalls
             "syscall" => {
@@ -118,6 +214,12 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
                 let sys_futex = this.eval_libc("SYS_futex").to_target_usize(this)?;
 
+                let sys_close_range = this.eval_libc("SYS_close_range").to_target_usize(this)?;
+
+                let sys_getdents64 = this.eval_libc("SYS_getdents64").to_target_usize(this)?;
+
+                let sys_gettid = this.eval_libc("SYS_gettid").to_target_usize(this)?;
+
                 if args.is_empty() {
                     throw_ub_format!(
                         "incorrect number of arguments for syscall: got 0, expected at least 1"
@@ -154,6 +256,42 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
                     id if id == sys_futex => {
                         futex(this, &args[1..], dest)?;
                     }
+                    // `close_range` is used when spawning processes, to close all file
+                    // descriptors that the child should not inherit.
+                    id if id == sys_close_range => {
+                        // The first argument is the syscall id, so skip over it.
+                        if args.len() < 4 {
+                            throw_ub_format!(
+                                "incorrect number of arguments for `close_range` syscall: got {}, expected at least 4",
+                                args.len()
+                            );
+                        }
+                        let first = this.read_scalar(&args[1])?.to_u32()?;
+                        let last = this.read_scalar(&args[2])?.to_u32()?;
+                        let flags = this.read_scalar(&args[3])?.to_i32()?;
+                        let result = this.close_range(first, last, flags)?;
+                        this.write_scalar(Scalar::from_target_isize(result.into(), this), dest)?;
+                    }
+                    // `getdents64` is used to list directories without going through `opendir`.
+                    id if id == sys_getdents64 => {
+                        // The first argument is the syscall id, so skip over it.
+                        if args.len() < 4 {
+                            throw_ub_format!(
+                                "incorrect number of arguments for `getdents64` syscall: got {}, expected at least 4",
+                                args.len()
+                            );
+                        }
+                        let fd = this.read_scalar(&args[1])?.to_i32()?;
+                        let dirp = this.read_pointer(&args[2])?;
+                        let count = this.read_target_usize(&args[3])?;
+                        let result = this.linux_getdents64(fd, dirp, count)?;
+                        this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                    }
+                    // `gettid` only got a libc wrapper in glibc 2.30, so it is often invoked directly.
+                    id if id == sys_gettid => {
+                        let result = this.gettid()?.to_i32()?;
+                        this.write_scalar(Scalar::from_target_isize(result.into(), this), dest)?;
+                    }
                     id => {
                         this.handle_unsupported(format!("can't execute syscall with ID {id}"))?;
                         return Ok(EmulateForeignItemResult::AlreadyJumped);
@@ -231,3 +369,51 @@ fn getrandom<'tcx>(
     this.write_scalar(Scalar::from_target_usize(len, this), dest)?;
     Ok(())
 }
+
+/// Only the `PR_SET_NAME` and `PR_GET_NAME` options are supported; all other options fail with
+/// `EINVAL`.
+fn prctl<'tcx>(
+    this: &mut MiriInterpCx<'_, 'tcx>,
+    args: &[OpTy<'tcx, Provenance>],
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    // The kernel's limit on thread names, including the null terminator.
+    const TASK_COMM_LEN: usize = 16;
+
+    if args.is_empty() {
+        throw_ub_format!("incorrect number of arguments for `prctl`: got 0, expected at least 1");
+    }
+    let option = this.read_scalar(&args[0])?.to_i32()?;
+    let pr_set_name = this.eval_libc_i32("PR_SET_NAME");
+    let pr_get_name = this.eval_libc_i32("PR_GET_NAME");
+
+    if option != pr_set_name && option != pr_get_name {
+        let einval = this.eval_libc("EINVAL");
+        this.set_last_error(einval)?;
+        this.write_scalar(Scalar::from_i32(-1), dest)?;
+        return Ok(());
+    }
+    if args.len() < 2 {
+        throw_ub_format!(
+            "incorrect number of arguments for `prctl` with option {option}: got {}, expected at least 2",
+            args.len()
+        );
+    }
+    let name = this.read_pointer(&args[1])?;
+    let thread = this.get_active_thread();
+
+    if option == pr_set_name {
+        // Longer names are silently truncated.
+        let mut new_name = this.read_c_str(name)?.to_owned();
+        new_name.truncate(TASK_COMM_LEN - 1);
+        this.set_thread_name(thread, new_name);
+    } else {
+        // FIXME: we should use the program name if the thread name is not set
+        let mut thread_name = this.get_thread_name(thread).unwrap_or(b"<unnamed>").to_owned();
+        // The buffer is always `TASK_COMM_LEN` bytes long.
+        thread_name.truncate(TASK_COMM_LEN - 1);
+        this.write_c_str(&thread_name, name, TASK_COMM_LEN.try_into().unwrap())?;
+    }
+    this.write_scalar(Scalar::from_i32(0), dest)?;
+    Ok(())
+}
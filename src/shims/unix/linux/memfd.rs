@@ -0,0 +1,190 @@
+//! Linux `memfd_create` implementation.
+use std::cell::RefCell;
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+
+use rustc_middle::ty::TyCtxt;
+
+use crate::shims::unix::*;
+use crate::*;
+
+/// The maximum size of a `MemFile`. Its content is held in a `Vec` in Miri's own memory, so we
+/// report `EFBIG` for anything larger instead of letting the program make Miri run out of memory.
+const MAX_MEM_FILE_SIZE: u64 = 1 << 30;
+
+/// An anonymous file created by `memfd_create`. Its content only lives in memory, so it can
+/// also be used with isolation enabled.
+#[derive(Debug)]
+struct MemFile {
+    /// The content and file offset, shared by all duplicates of the file descriptor.
+    file: Rc<RefCell<Cursor<Vec<u8>>>>,
+}
+
+impl MemFile {
+    /// Performs `op` at the given offset without changing the file offset.
+    fn at_offset<T>(
+        &self,
+        offset: u64,
+        op: impl FnOnce(&mut Cursor<Vec<u8>>) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut file = self.file.borrow_mut();
+        let cursor_pos = file.position();
+        file.set_position(offset);
+        let res = op(&mut file);
+        file.set_position(cursor_pos);
+        res
+    }
+}
+
+/// Writes `bytes` at the current position of `file`, without growing it past `MAX_MEM_FILE_SIZE`.
+/// Like writes beyond the file size limit on Linux, this is a short write if only part of `bytes`
+/// fits, and fails with `EFBIG` if nothing does.
+fn write_capped(file: &mut Cursor<Vec<u8>>, bytes: &[u8]) -> io::Result<usize> {
+    let space = MAX_MEM_FILE_SIZE.saturating_sub(file.position());
+    if space == 0 && !bytes.is_empty() {
+        return Err(io::ErrorKind::FileTooLarge.into());
+    }
+    let len = bytes.len().min(usize::try_from(space).unwrap_or(usize::MAX));
+    file.write(&bytes[..len])
+}
+
+impl FileDescriptor for MemFile {
+    fn name(&self) -> &'static str {
+        "memfd"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(self.file.borrow_mut().read(bytes))
+    }
+
+    fn write<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        // Writing past the end of the file fills the gap with zeros.
+        Ok(write_capped(&mut self.file.borrow_mut(), bytes))
+    }
+
+    fn read_at<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        bytes: &mut [u8],
+        offset: Option<u64>,
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        match offset {
+            None => self.read(communicate_allowed, bytes, tcx),
+            Some(offset) => Ok(self.at_offset(offset, |file| file.read(bytes))),
+        }
+    }
+
+    fn write_at<'tcx>(
+        &mut self,
+        communicate_allowed: bool,
+        bytes: &[u8],
+        offset: Option<u64>,
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        match offset {
+            None => self.write(communicate_allowed, bytes, tcx),
+            Some(offset) => Ok(self.at_offset(offset, |file| write_capped(file, bytes))),
+        }
+    }
+
+    fn seek<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        offset: SeekFrom,
+    ) -> InterpResult<'tcx, io::Result<u64>> {
+        Ok(self.file.borrow_mut().seek(offset))
+    }
+
+    fn ftruncate<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        length: u64,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        if length > MAX_MEM_FILE_SIZE {
+            return Ok(Err(io::ErrorKind::FileTooLarge.into()));
+        }
+        let length = usize::try_from(length).unwrap();
+        // Growing the file fills the new part with zeros. The file offset is not changed.
+        self.file.borrow_mut().get_mut().resize(length, 0);
+        Ok(Ok(()))
+    }
+
+    fn fsync<'tcx>(&self, _communicate_allowed: bool) -> InterpResult<'tcx, io::Result<()>> {
+        // There is no storage to synchronize with.
+        Ok(Ok(()))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn is_mmappable(&self) -> bool {
+        true
+    }
+
+    fn stat(&self) -> Option<FdStat> {
+        let size = self.file.borrow().get_ref().len();
+        Some(FdStat { kind: FdKind::Regular, size: size.try_into().unwrap() })
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(MemFile { file: Rc::clone(&self.file) }))
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// This function creates an anonymous file that behaves like a regular file, but lives in
+    /// memory. It is initially empty. The `name` is only used for debugging purposes on Linux,
+    /// so we ignore it.
+    ///
+    /// The `flags` may be bitwise ORed to change the behavior of `memfd_create`:
+    /// `MFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
+    ///
+    /// File sealing (`MFD_ALLOW_SEALING`) and huge pages (`MFD_HUGETLB`) are not supported.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/memfd_create.2.html>
+    fn memfd_create(
+        &mut self,
+        name: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let name = this.read_pointer(name)?;
+        let flags = this.read_scalar(flags)?.to_u32()?;
+
+        // The name must be a valid string, and its length is limited.
+        if this.read_c_str(name)?.len() > 249 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let mfd_cloexec = this.eval_libc_u32("MFD_CLOEXEC");
+        if flags & mfd_cloexec != flags {
+            throw_unsup_format!("memfd_create: flag {flags:#x} is unsupported");
+        }
+
+        let fd = this
+            .machine
+            .fds
+            .insert_fd(Box::new(MemFile { file: Rc::new(RefCell::new(Cursor::new(Vec::new()))) }));
+        this.machine.fds.set_cloexec(fd, flags & mfd_cloexec == mfd_cloexec).unwrap();
+        Ok(Scalar::from_i32(fd))
+    }
+}
@@ -2,4 +2,6 @@ pub mod epoll;
 pub mod eventfd;
 pub mod foreign_items;
 pub mod mem;
+pub mod memfd;
 pub mod sync;
+pub mod timerfd;
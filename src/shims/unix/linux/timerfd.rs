@@ -0,0 +1,345 @@
+//! Linux `timerfd` implementation.
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::io::ErrorKind;
+use std::rc::Rc;
+use std::time::Duration;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_target::abi::Endian;
+
+use crate::shims::unix::*;
+use crate::*;
+
+/// The state of a timer, shared between all file descriptors referring to it.
+#[derive(Debug, Default)]
+struct Timer {
+    /// When the timer expires next, as a duration since the clock's anchor.
+    /// `None` means that the timer is disarmed.
+    next_expiration: Option<Duration>,
+    /// The period of the timer after its first expiration. Zero means that the timer only
+    /// expires once.
+    interval: Duration,
+    /// The number of expirations that have not been read yet.
+    expirations: u64,
+    /// Whether this timer uses `CLOCK_REALTIME`, for which absolute times are unsupported.
+    realtime: bool,
+}
+
+impl Timer {
+    /// Accounts for all expirations up to `now`.
+    fn update(&mut self, now: Duration) {
+        let Some(next_expiration) = self.next_expiration else {
+            return;
+        };
+        let Some(elapsed) = now.checked_sub(next_expiration) else {
+            // The timer has not expired yet.
+            return;
+        };
+        // The timer expired at `next_expiration`, and then once more every `interval`.
+        let Some(periods) = elapsed.as_nanos().checked_div(self.interval.as_nanos()) else {
+            // A zero interval means that the timer only expires once.
+            self.expirations = self.expirations.saturating_add(1);
+            self.next_expiration = None;
+            return;
+        };
+        let expirations = u64::try_from(periods).unwrap_or(u64::MAX).saturating_add(1);
+        self.expirations = self.expirations.saturating_add(expirations);
+        self.next_expiration = u32::try_from(expirations)
+            .ok()
+            .and_then(|expirations| self.interval.checked_mul(expirations))
+            .and_then(|offset| next_expiration.checked_add(offset));
+    }
+
+    /// Returns the time until the next expiration and the interval, as reported by
+    /// `timerfd_gettime`.
+    fn setting(&self, now: Duration) -> (Duration, Duration) {
+        let value = self.next_expiration.map_or(Duration::ZERO, |next| next.saturating_sub(now));
+        (value, self.interval)
+    }
+}
+
+/// A kind of file descriptor created by `timerfd_create`. It becomes readable whenever the
+/// timer expires. Since the timer is tied to Miri's clock, it expires in simulated time when
+/// isolation is enabled.
+///
+/// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+#[derive(Debug)]
+struct TimerFd {
+    timer: Rc<RefCell<Timer>>,
+    clock: Rc<Clock>,
+    is_nonblock: Rc<Cell<bool>>,
+}
+
+impl TimerFd {
+    /// The current time, as a duration since the clock's anchor.
+    fn now(&self) -> Duration {
+        self.clock.now().duration_since(self.clock.anchor())
+    }
+}
+
+impl FileDescriptor for TimerFd {
+    fn name(&self) -> &'static str {
+        "timerfd"
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(TimerFd {
+            timer: Rc::clone(&self.timer),
+            clock: Rc::clone(&self.clock),
+            is_nonblock: Rc::clone(&self.is_nonblock),
+        }))
+    }
+
+    fn is_read_ready(&self) -> bool {
+        let mut timer = self.timer.borrow_mut();
+        timer.update(self.now());
+        timer.expirations > 0
+    }
+
+    fn is_write_ready(&self) -> bool {
+        false
+    }
+
+    fn ready_at(&self) -> Option<Duration> {
+        self.timer.borrow().next_expiration
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::ReadOnly
+    }
+
+    fn is_nonblock(&self) -> bool {
+        self.is_nonblock.get()
+    }
+
+    fn set_nonblock<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.is_nonblock.set(nonblock);
+        Ok(())
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    /// A read call returns the number of expirations since the last read as an 8-byte integer
+    /// (in native endianness) and resets it to zero. If the timer has not expired, the read
+    /// either blocks until it does, or fails with the error EAGAIN if the file descriptor has
+    /// been made nonblocking.
+    ///
+    /// A read fails with the error EINVAL if the size of the supplied buffer is
+    /// less than 8 bytes.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(bytes) = bytes.first_chunk_mut::<8>() else {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        };
+        let mut timer = self.timer.borrow_mut();
+        timer.update(self.now());
+        if timer.expirations == 0 {
+            if self.is_nonblock.get() {
+                return Ok(Err(ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("timerfd: blocking is unsupported, use `poll` to wait instead");
+        }
+        let num = std::mem::take(&mut timer.expirations);
+        // Convert from host endianness to target endianness.
+        *bytes = match tcx.sess.target.endian {
+            Endian::Little => num.to_le_bytes(),
+            Endian::Big => num.to_be_bytes(),
+        };
+        Ok(Ok(8))
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Writes a timer setting to the `itimerspec` at `place`.
+    fn write_itimerspec(
+        &mut self,
+        (value, interval): (Duration, Duration),
+        place: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        for (field, duration) in [("it_value", value), ("it_interval", interval)] {
+            let timespec = this.project_field_named(place, field)?;
+            let secs = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+            this.write_int_fields(&[secs.into(), duration.subsec_nanos().into()], &timespec)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the timer of the `timerfd` with the given file descriptor. If `fd` is not a
+    /// `timerfd`, sets the last error and returns `None`.
+    fn timerfd_get(&mut self, fd: i32) -> InterpResult<'tcx, Option<Rc<RefCell<Timer>>>> {
+        let this = self.eval_context_mut();
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            this.fd_not_found::<i32>()?;
+            return Ok(None);
+        };
+        let Some(timerfd) = file_descriptor.downcast_ref::<TimerFd>() else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(None);
+        };
+        Ok(Some(Rc::clone(&timerfd.timer)))
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// This function creates a new, disarmed timer and returns a file descriptor referring to it.
+    /// `CLOCK_MONOTONIC`, `CLOCK_BOOTTIME` and `CLOCK_REALTIME` are supported as clocks, but
+    /// absolute times are only supported for `CLOCK_MONOTONIC`.
+    ///
+    /// The `flags` may be bitwise ORed to change the behavior of `timerfd_create`:
+    /// `TFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
+    /// `TFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+    fn timerfd_create(
+        &mut self,
+        clockid: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let clockid = this.read_scalar(clockid)?.to_i32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+
+        // These clocks only differ in how they deal with system suspend and clock changes, which
+        // do not happen in Miri, so for relative timers it does not matter which one is used.
+        let clocks = [
+            this.eval_libc_i32("CLOCK_MONOTONIC"),
+            this.eval_libc_i32("CLOCK_BOOTTIME"),
+            this.eval_libc_i32("CLOCK_REALTIME"),
+        ];
+        if !clocks.contains(&clockid) {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let tfd_cloexec = this.eval_libc_i32("TFD_CLOEXEC");
+        let tfd_nonblock = this.eval_libc_i32("TFD_NONBLOCK");
+        if flags & (tfd_cloexec | tfd_nonblock) != flags {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let fd = this.machine.fds.insert_fd(Box::new(TimerFd {
+            timer: Rc::new(RefCell::new(Timer {
+                realtime: clockid == this.eval_libc_i32("CLOCK_REALTIME"),
+                ..Timer::default()
+            })),
+            clock: Rc::clone(&this.machine.clock),
+            is_nonblock: Rc::new(Cell::new(flags & tfd_nonblock == tfd_nonblock)),
+        }));
+        this.machine.fds.set_cloexec(fd, flags & tfd_cloexec == tfd_cloexec).unwrap();
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// This function arms or disarms the timer referred to by `fd`. The timer first expires
+    /// after `it_value` (or at `it_value`, if `TFD_TIMER_ABSTIME` is set), and then every
+    /// `it_interval`. A zero `it_value` disarms the timer. Arming the timer resets the number
+    /// of expirations. If `old_value` is not null, the previous setting is stored there.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_settime.2.html>
+    fn timerfd_settime(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        new_value: &OpTy<'tcx, Provenance>,
+        old_value: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+        let new_value = this.deref_pointer_as(new_value, this.libc_ty_layout("itimerspec"))?;
+        let old_value_ptr = this.read_pointer(old_value)?;
+
+        let tfd_timer_abstime = this.eval_libc_i32("TFD_TIMER_ABSTIME");
+        if flags & tfd_timer_abstime != flags {
+            throw_unsup_format!("timerfd_settime: flag {flags:#x} is unsupported");
+        }
+        let abstime = flags & tfd_timer_abstime == tfd_timer_abstime;
+
+        let Some(timer) = this.timerfd_get(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let (Some(value), Some(interval)) = (
+            this.read_timespec(&this.project_field_named(&new_value, "it_value")?)?,
+            this.read_timespec(&this.project_field_named(&new_value, "it_interval")?)?,
+        ) else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let clock = &this.machine.clock;
+        let now = clock.now().duration_since(clock.anchor());
+        let mut timer = timer.borrow_mut();
+        timer.update(now);
+        let old_setting = timer.setting(now);
+
+        if abstime && timer.realtime {
+            throw_unsup_format!(
+                "timerfd_settime: absolute times for `CLOCK_REALTIME` are unsupported"
+            );
+        }
+        // Absolute monotonic times are relative to the clock's anchor, like for `clock_gettime`.
+        timer.next_expiration = if value.is_zero() {
+            None
+        } else if abstime {
+            Some(value)
+        } else {
+            Some(now.saturating_add(value))
+        };
+        timer.interval = interval;
+        timer.expirations = 0;
+        drop(timer);
+
+        if !this.ptr_is_null(old_value_ptr)? {
+            let old_value = this.deref_pointer_as(old_value, this.libc_ty_layout("itimerspec"))?;
+            this.write_itimerspec(old_setting, &old_value)?;
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// This function stores the time until the next expiration of the timer referred to by `fd`,
+    /// and its interval, in `curr_value`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_gettime.2.html>
+    fn timerfd_gettime(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        curr_value: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let curr_value = this.deref_pointer_as(curr_value, this.libc_ty_layout("itimerspec"))?;
+
+        let Some(timer) = this.timerfd_get(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let clock = &this.machine.clock;
+        let now = clock.now().duration_since(clock.anchor());
+        let mut timer = timer.borrow_mut();
+        timer.update(now);
+        let setting = timer.setting(now);
+        drop(timer);
+
+        this.write_itimerspec(setting, &curr_value)?;
+        Ok(Scalar::from_i32(0))
+    }
+}
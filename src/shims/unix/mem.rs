@@ -1,11 +1,12 @@
 //! This is an incomplete implementation of mmap/munmap which is restricted in order to be
 //! implementable on top of the existing memory system. The point of these function as-written is
-//! to allow memory allocators written entirely in Rust to be executed by Miri. This implementation
-//! does not support other uses of mmap such as file mappings.
+//! to allow memory allocators written entirely in Rust to be executed by Miri.
 //!
 //! mmap/munmap behave a lot like alloc/dealloc, and for simple use they are exactly
-//! equivalent. That is the only part we support: no MAP_FIXED or MAP_SHARED or anything
-//! else that goes beyond a basic allocation API.
+//! equivalent. That is the only part we support: no MAP_FIXED or anonymous MAP_SHARED or anything
+//! else that goes beyond a basic allocation API. File mappings are supported as allocations that
+//! are filled with the file contents when they are created; for MAP_SHARED, later writes to the
+//! mapping or the file are not propagated to the other side.
 //!
 //! Note that in addition to only supporting malloc-like calls to mmap, we only support free-like
 //! calls to munmap, but for a very different reason. In principle, according to the man pages, it
@@ -13,10 +14,43 @@
 //! this amounts to partial deallocation, which LLVM does not support. So any attempt to call our
 //! munmap shim which would partily unmap a region of address space previously mapped by mmap will
 //! report UB.
+//!
+//! madvise and mprotect are only supported on memory mapped this way. The only advice with an
+//! observable effect is Linux's MADV_DONTNEED, which resets anonymous mappings to zeros. Protections are
+//! enforced by making accesses that they do not permit UB.
 
 use crate::*;
 use rustc_target::abi::Size;
 
+/// The access permissions of a part of an allocation created by `mmap`. `PROT_EXEC` is not
+/// tracked, since Miri never executes memory anyway.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MmapProtection {
+    pub read: bool,
+    pub write: bool,
+}
+
+impl MmapProtection {
+    /// Reports UB if some part of `range` does not permit an access of the given `kind`.
+    pub fn check_access<'tcx>(
+        map: &RangeMap<MmapProtection>,
+        alloc_id: AllocId,
+        range: AllocRange,
+        kind: AccessKind,
+    ) -> InterpResult<'tcx> {
+        for (_, protection) in map.iter(range.start, range.size) {
+            match kind {
+                AccessKind::Read if !protection.read =>
+                    throw_ub_format!("reading from {alloc_id:?}, which is mapped without PROT_READ"),
+                AccessKind::Write if !protection.write =>
+                    throw_ub_format!("writing to {alloc_id:?}, which is mapped without PROT_WRITE"),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
 pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
     fn mmap(
@@ -61,12 +95,7 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
             return Ok(this.eval_libc("MAP_FAILED"));
         }
 
-        // If a user tries to map a file, we want to loudly inform them that this is not going
-        // to work. It is possible that POSIX gives us enough leeway to return an error, but the
-        // outcome for the user (I need to add cfg(miri)) is the same, just more frustrating.
-        if fd != -1 {
-            throw_unsup_format!("Miri does not support file-backed memory mappings");
-        }
+        let anonymous = flags & map_anonymous != 0;
 
         // POSIX says:
         // [ENOTSUP]
@@ -75,23 +104,65 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         // * The implementation does not support the combination of accesses requested in the
         // prot argument.
         //
-        // Miri doesn't support MAP_FIXED or any any protections other than PROT_READ|PROT_WRITE.
-        if flags & map_fixed != 0 || prot != prot_read | prot_write {
+        // Miri doesn't support MAP_FIXED or PROT_EXEC (or any other protection flags besides
+        // PROT_READ and PROT_WRITE).
+        if flags & map_fixed != 0 || prot & !(prot_read | prot_write) != 0 {
             this.set_last_error(this.eval_libc("ENOTSUP"))?;
             return Ok(this.eval_libc("MAP_FAILED"));
         }
 
-        // Miri does not support shared mappings, or any of the other extensions that for example
-        // Linux has added to the flags arguments.
-        if flags != map_private | map_anonymous {
-            throw_unsup_format!(
-                "Miri only supports calls to mmap which set the flags argument to MAP_PRIVATE|MAP_ANONYMOUS"
-            );
-        }
-
-        // This is only used for file mappings, which we don't support anyway.
-        if offset != 0 {
-            throw_unsup_format!("Miri does not support non-zero offsets to mmap");
+        // For file mappings, this is the file offset to start reading at.
+        let mut file_offset = None;
+        if anonymous {
+            // Miri does not support shared mappings, or any of the other extensions that for
+            // example Linux has added to the flags arguments.
+            if flags != map_private | map_anonymous {
+                throw_unsup_format!(
+                    "Miri only supports calls to mmap which set the flags argument to MAP_PRIVATE|MAP_ANONYMOUS"
+                );
+            }
+            if offset != 0 {
+                throw_unsup_format!("Miri does not support non-zero offsets to anonymous mmap");
+            }
+        } else {
+            // A file mapping is a copy of the file contents at the time of the call. For
+            // MAP_PRIVATE, that is exactly what the program can observe. For MAP_SHARED, we do
+            // not propagate writes to the file or changes of the file to the mapping.
+            if flags & !(map_private | map_shared) != 0 {
+                throw_unsup_format!(
+                    "Miri only supports calls to mmap of a file which set the flags argument to MAP_PRIVATE or MAP_SHARED"
+                );
+            }
+            let Some(file_descriptor) = this.machine.fds.get(fd) else {
+                this.set_last_error(this.eval_libc("EBADF"))?;
+                return Ok(this.eval_libc("MAP_FAILED"));
+            };
+            if !file_descriptor.is_mmappable() {
+                this.set_last_error(this.eval_libc("ENODEV"))?;
+                return Ok(this.eval_libc("MAP_FAILED"));
+            }
+            // The file must be open for reading, and for writing if writes to a shared
+            // mapping would be carried through to it.
+            let access_mode = file_descriptor.access_mode();
+            if access_mode == AccessMode::WriteOnly
+                || (flags & map_shared != 0
+                    && prot & prot_write != 0
+                    && access_mode != AccessMode::ReadWrite)
+            {
+                this.set_last_error(this.eval_libc("EACCES"))?;
+                return Ok(this.eval_libc("MAP_FAILED"));
+            }
+            // The offset must be a multiple of the page size.
+            let Ok(offset) = u64::try_from(offset) else {
+                this.set_last_error(this.eval_libc("EINVAL"))?;
+                return Ok(this.eval_libc("MAP_FAILED"));
+            };
+            #[allow(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+            if offset % this.machine.page_size != 0 {
+                this.set_last_error(this.eval_libc("EINVAL"))?;
+                return Ok(this.eval_libc("MAP_FAILED"));
+            }
+            file_offset = Some(offset);
         }
 
         let align = this.machine.page_align();
@@ -114,6 +185,46 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         )
         .unwrap();
 
+        if let Some(file_offset) = file_offset {
+            // Copy in the file contents. The part of the mapping beyond the end of the file
+            // stays zeroed.
+            let communicate = this.machine.communicate();
+            let file_descriptor = this.machine.fds.get_mut(fd).unwrap();
+            let mut bytes = vec![0; usize::try_from(length).unwrap()];
+            let mut filled = 0;
+            while filled < bytes.len() {
+                let pos = file_offset.checked_add(u64::try_from(filled).unwrap()).unwrap();
+                match file_descriptor.read_at(
+                    communicate,
+                    &mut bytes[filled..],
+                    Some(pos),
+                    *this.tcx,
+                )? {
+                    Ok(0) => break,
+                    Ok(read) => filled = filled.checked_add(read).unwrap(),
+                    Err(e) => {
+                        this.deallocate_ptr(
+                            ptr.into(),
+                            Some((Size::from_bytes(map_length), align)),
+                            MiriMemoryKind::Mmap.into(),
+                        )?;
+                        this.set_last_error_from_io_error(e.kind())?;
+                        return Ok(this.eval_libc("MAP_FAILED"));
+                    }
+                }
+            }
+            this.write_bytes_ptr(ptr.into(), bytes[..filled].iter().copied())?;
+            let (alloc_id, _, _) = this.ptr_get_alloc_id(ptr.into())?;
+            this.get_alloc_extra_mut(alloc_id)?.0.mmap_file_backed = true;
+        }
+
+        // Only restrict accesses once the initial contents are in place.
+        if prot != prot_read | prot_write {
+            // Like for `mprotect`, writable memory is also readable.
+            let protection = MmapProtection { read: prot != 0, write: prot & prot_write != 0 };
+            this.set_mmap_protection(ptr.into(), Size::from_bytes(map_length), protection)?;
+        }
+
         Ok(Scalar::from_pointer(ptr, this))
     }
 
@@ -153,4 +264,162 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
         Ok(Scalar::from_i32(0))
     }
+
+    fn madvise(
+        &mut self,
+        addr: &OpTy<'tcx, Provenance>,
+        length: &OpTy<'tcx, Provenance>,
+        advice: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let addr = this.read_pointer(addr)?;
+        let length = this.read_target_usize(length)?;
+        let advice = this.read_scalar(advice)?.to_i32()?;
+
+        // addr must be a multiple of the page size.
+        #[allow(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+        if addr.addr().bytes() % this.machine.page_size != 0 {
+            this.set_last_error(this.eval_libc("EINVAL"))?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        let Some(length) = length.checked_next_multiple_of(this.machine.page_size) else {
+            this.set_last_error(this.eval_libc("EINVAL"))?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        let length = Size::from_bytes(length);
+
+        // `MADV_FREE` is not available on all Unixes.
+        let has_madv_free = matches!(&*this.tcx.sess.target.os, "linux" | "macos" | "freebsd");
+        let no_op = ["MADV_NORMAL", "MADV_RANDOM", "MADV_SEQUENTIAL", "MADV_WILLNEED"]
+            .into_iter()
+            .any(|name| advice == this.eval_libc_i32(name))
+            || (has_madv_free && advice == this.eval_libc_i32("MADV_FREE"));
+        let dont_need = advice == this.eval_libc_i32("MADV_DONTNEED");
+        if !no_op && !dont_need {
+            this.set_last_error(this.eval_libc("EINVAL"))?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        if length == Size::ZERO {
+            return Ok(Scalar::from_i32(0));
+        }
+        // The whole range must have been mapped by a single call to `mmap`, since we cannot
+        // tell which other parts of the address space would be mapped.
+        if !this.is_mmapped_range(addr, length) {
+            this.set_last_error(this.eval_libc("ENOMEM"))?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        // On Linux, private anonymous mappings read as zeros after `MADV_DONTNEED`. Elsewhere
+        // (and for `MADV_FREE`, where the kernel may or may not discard the pages), keeping the
+        // contents is a valid outcome, so all other advice is a no-op.
+        // File mappings are refilled from the file instead. We do not keep track of the file,
+        // so for them we keep the contents, which is only wrong for private mappings that have
+        // been written to.
+        let (alloc_id, _, _) = this.ptr_get_alloc_id(addr)?;
+        let file_backed = this.get_alloc_extra(alloc_id)?.mmap_file_backed;
+        if dont_need && !file_backed && this.tcx.sess.target.os == "linux" {
+            // This is done by the kernel, so it works regardless of the protection of the range.
+            // We lift the protections of the allocation for the duration of the write.
+            let protection = this.get_alloc_extra_mut(alloc_id)?.0.mmap_protection.take();
+            let res = this.write_bytes_ptr(
+                addr,
+                std::iter::repeat(0u8).take(usize::try_from(length.bytes()).unwrap()),
+            );
+            this.get_alloc_extra_mut(alloc_id)?.0.mmap_protection = protection;
+            res?;
+        }
+
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn mprotect(
+        &mut self,
+        addr: &OpTy<'tcx, Provenance>,
+        length: &OpTy<'tcx, Provenance>,
+        prot: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let addr = this.read_pointer(addr)?;
+        let length = this.read_target_usize(length)?;
+        let prot = this.read_scalar(prot)?.to_i32()?;
+
+        let prot_read = this.eval_libc_i32("PROT_READ");
+        let prot_write = this.eval_libc_i32("PROT_WRITE");
+        let prot_exec = this.eval_libc_i32("PROT_EXEC");
+
+        // addr must be a multiple of the page size.
+        #[allow(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+        if addr.addr().bytes() % this.machine.page_size != 0
+            || prot & !(prot_read | prot_write | prot_exec) != 0
+        {
+            this.set_last_error(this.eval_libc("EINVAL"))?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        let Some(length) = length.checked_next_multiple_of(this.machine.page_size) else {
+            this.set_last_error(this.eval_libc("ENOMEM"))?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        let length = Size::from_bytes(length);
+
+        if length == Size::ZERO {
+            return Ok(Scalar::from_i32(0));
+        }
+        // Like for `madvise`, the range must lie within memory we mapped.
+        if !this.is_mmapped_range(addr, length) {
+            this.set_last_error(this.eval_libc("ENOMEM"))?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        // On all hardware we care about, writable memory is also readable.
+        let protection = MmapProtection {
+            read: prot & (prot_read | prot_write) != 0,
+            write: prot & prot_write != 0,
+        };
+        this.set_mmap_protection(addr, length, protection)?;
+
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Sets the access permissions of `addr..addr+length`, which must lie within an allocation
+    /// created by `mmap`.
+    fn set_mmap_protection(
+        &mut self,
+        addr: Pointer<Option<Provenance>>,
+        length: Size,
+        protection: MmapProtection,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let (alloc_id, offset, _) = this.ptr_get_alloc_id(addr)?;
+        let (alloc_size, _, _) = this.get_alloc_info(alloc_id);
+        let (alloc_extra, _) = this.get_alloc_extra_mut(alloc_id)?;
+        let map = alloc_extra.mmap_protection.get_or_insert_with(|| {
+            RangeMap::new(alloc_size, MmapProtection { read: true, write: true })
+        });
+        for (_, p) in map.iter_mut(offset, length) {
+            *p = protection;
+        }
+        Ok(())
+    }
+
+    /// Returns whether `addr..addr+length` lies within a single live allocation created by
+    /// `mmap`.
+    fn is_mmapped_range(&self, addr: Pointer<Option<Provenance>>, length: Size) -> bool {
+        let this = self.eval_context_ref();
+
+        let Ok((alloc_id, offset, _)) = this.ptr_try_get_alloc_id(addr) else {
+            return false;
+        };
+        let Some((kind, alloc)) = this.memory.alloc_map().get(alloc_id) else {
+            return false;
+        };
+        *kind == MemoryKind::Machine(MiriMemoryKind::Mmap)
+            && offset
+                .bytes()
+                .checked_add(length.bytes())
+                .is_some_and(|end| end <= alloc.size().bytes())
+    }
 }
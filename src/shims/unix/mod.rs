@@ -4,6 +4,8 @@ mod env;
 mod fd;
 mod fs;
 mod mem;
+mod pipe;
+mod poll;
 mod socket;
 mod sync;
 mod thread;
@@ -13,13 +15,17 @@ mod linux;
 mod macos;
 
 pub use env::UnixEnvVars;
-pub use fd::{FdTable, FileDescriptor};
-pub use fs::DirTable;
+pub use fd::{AccessMode, FdKind, FdStat, FdTable, FileDescriptor, FileDescriptorFactory};
+pub use fs::{DirTable, FlockState};
+pub use mem::MmapProtection;
+pub use poll::ReadinessCheck;
 // All the Unix-specific extension traits
 pub use env::EvalContextExt as _;
 pub use fd::EvalContextExt as _;
 pub use fs::EvalContextExt as _;
 pub use mem::EvalContextExt as _;
+pub use pipe::EvalContextExt as _;
+pub use poll::EvalContextExt as _;
 pub use socket::EvalContextExt as _;
 pub use sync::EvalContextExt as _;
 pub use thread::EvalContextExt as _;
@@ -0,0 +1,311 @@
+//! Anonymous pipes, as created by `pipe` and `pipe2`.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io;
+use std::io::{ErrorKind, Read};
+use std::rc::{Rc, Weak};
+
+use rustc_middle::ty::TyCtxt;
+
+use crate::shims::unix::*;
+use crate::*;
+
+/// The capacity of a new pipe, as on Linux.
+const DEFAULT_PIPE_CAPACITY: usize = 1 << 16;
+
+/// The largest write that is guaranteed to be atomic, as on Linux.
+const PIPE_BUF: usize = 4096;
+
+/// The read end of a pipe.
+///
+/// The read end owns the buffer, while write ends only hold weak references to it.
+/// That way, a write end can tell when all read ends have been closed (the buffer is
+/// gone), and a read end can tell when all write ends have been closed (there are no
+/// weak references left).
+#[derive(Debug)]
+struct PipeReader {
+    buffer: Rc<RefCell<VecDeque<u8>>>,
+    /// The capacity reported by `F_GETPIPE_SZ`, shared by both ends. Only non-blocking writes
+    /// are limited by it.
+    capacity: Rc<Cell<usize>>,
+    /// Whether this end is in non-blocking mode, shared by all duplicates of this end.
+    is_nonblock: Rc<Cell<bool>>,
+}
+
+/// The write end of a pipe.
+#[derive(Debug)]
+struct PipeWriter {
+    buffer: Weak<RefCell<VecDeque<u8>>>,
+    capacity: Rc<Cell<usize>>,
+    is_nonblock: Rc<Cell<bool>>,
+}
+
+/// Sets the capacity of the pipe with the given `buffer`, unless more bytes are buffered
+/// than would fit.
+fn set_capacity(
+    buffer: Option<&RefCell<VecDeque<u8>>>,
+    capacity: &Cell<usize>,
+    new_capacity: usize,
+) -> io::Result<()> {
+    if buffer.is_some_and(|buffer| buffer.borrow().len() > new_capacity) {
+        return Err(ErrorKind::ResourceBusy.into());
+    }
+    capacity.set(new_capacity);
+    Ok(())
+}
+
+impl FileDescriptor for PipeReader {
+    fn name(&self) -> &'static str {
+        "pipe"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        if self.buffer.borrow().is_empty() && !bytes.is_empty() {
+            if Rc::weak_count(&self.buffer) == 0 {
+                // All write ends have been closed, this is EOF.
+                return Ok(Ok(0));
+            }
+            if self.is_nonblock.get() {
+                return Ok(Err(ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("pipe: blocking reads are not supported");
+        }
+        // `VecDeque` implements `Read` by draining the front of the buffer.
+        Ok(self.buffer.borrow_mut().read(bytes))
+    }
+
+    fn stat(&self) -> Option<FdStat> {
+        // Like Linux, we do not report the number of buffered bytes as the size.
+        Some(FdStat { kind: FdKind::Fifo, size: 0 })
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(PipeReader {
+            buffer: Rc::clone(&self.buffer),
+            capacity: Rc::clone(&self.capacity),
+            is_nonblock: Rc::clone(&self.is_nonblock),
+        }))
+    }
+
+    fn is_read_ready(&self) -> bool {
+        // Once all write ends are closed, reads return EOF without blocking.
+        !self.buffer.borrow().is_empty() || Rc::weak_count(&self.buffer) == 0
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::ReadOnly
+    }
+
+    fn pipe_capacity(&self) -> Option<usize> {
+        Some(self.capacity.get())
+    }
+
+    fn set_pipe_capacity(&self, capacity: usize) -> io::Result<()> {
+        set_capacity(Some(&self.buffer), &self.capacity, capacity)
+    }
+
+    fn is_nonblock(&self) -> bool {
+        self.is_nonblock.get()
+    }
+
+    fn set_nonblock<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.is_nonblock.set(nonblock);
+        Ok(())
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+}
+
+impl FileDescriptor for PipeWriter {
+    fn name(&self) -> &'static str {
+        "pipe"
+    }
+
+    fn write<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(buffer) = self.buffer.upgrade() else {
+            // All read ends have been closed.
+            return Ok(Err(ErrorKind::BrokenPipe.into()));
+        };
+        let mut buffer = buffer.borrow_mut();
+        let len = if self.is_nonblock.get() {
+            // Write as much as fits, except that writes of up to `PIPE_BUF` bytes are atomic.
+            let space = self.capacity.get().saturating_sub(buffer.len());
+            if bytes.len() > space && (space == 0 || bytes.len() <= PIPE_BUF) {
+                return Ok(Err(ErrorKind::WouldBlock.into()));
+            }
+            bytes.len().min(space)
+        } else {
+            // Blocking writes would have to wait for a reader to make room, which we do not
+            // support, so they ignore the capacity.
+            bytes.len()
+        };
+        buffer.extend(&bytes[..len]);
+        Ok(Ok(len))
+    }
+
+    fn is_write_ready(&self) -> bool {
+        // Once all read ends are closed, writes fail with `EPIPE` without blocking.
+        self.buffer.upgrade().map_or(true, |buffer| buffer.borrow().len() < self.capacity.get())
+    }
+
+    fn stat(&self) -> Option<FdStat> {
+        Some(FdStat { kind: FdKind::Fifo, size: 0 })
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(PipeWriter {
+            buffer: Weak::clone(&self.buffer),
+            capacity: Rc::clone(&self.capacity),
+            is_nonblock: Rc::clone(&self.is_nonblock),
+        }))
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::WriteOnly
+    }
+
+    fn pipe_capacity(&self) -> Option<usize> {
+        Some(self.capacity.get())
+    }
+
+    fn set_pipe_capacity(&self, capacity: usize) -> io::Result<()> {
+        set_capacity(self.buffer.upgrade().as_deref(), &self.capacity, capacity)
+    }
+
+    fn is_nonblock(&self) -> bool {
+        self.is_nonblock.get()
+    }
+
+    fn set_nonblock<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.is_nonblock.set(nonblock);
+        Ok(())
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Creates a pipe, and writes the file descriptors of its read end and write end
+    /// to `pipefd[0]` and `pipefd[1]`. `flags` is `None` for `pipe` and the flags
+    /// argument for `pipe2`.
+    ///
+    /// For more information on the arguments see the pipe manpage:
+    /// <https://linux.die.net/man/2/pipe>
+    fn pipe2(
+        &mut self,
+        pipefd: &OpTy<'tcx, Provenance>,
+        flags: Option<&OpTy<'tcx, Provenance>>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let pipefd = this.deref_pointer_as(pipefd, this.machine.layouts.i32)?;
+        let flags = match flags {
+            Some(flags) => this.read_scalar(flags)?.to_i32()?,
+            None => 0,
+        };
+
+        let o_cloexec = this.eval_libc_i32("O_CLOEXEC");
+        let o_nonblock = this.eval_libc_i32("O_NONBLOCK");
+        if flags & (o_cloexec | o_nonblock) != flags {
+            throw_unsup_format!("pipe2: flag {flags:#x} is unsupported");
+        }
+        let cloexec = flags & o_cloexec == o_cloexec;
+        let is_nonblock = flags & o_nonblock == o_nonblock;
+
+        let buffer = Rc::new(RefCell::new(VecDeque::new()));
+        let capacity = Rc::new(Cell::new(DEFAULT_PIPE_CAPACITY));
+        let writer = PipeWriter {
+            buffer: Rc::downgrade(&buffer),
+            capacity: Rc::clone(&capacity),
+            is_nonblock: Rc::new(Cell::new(is_nonblock)),
+        };
+        let reader = PipeReader { buffer, capacity, is_nonblock: Rc::new(Cell::new(is_nonblock)) };
+
+        let fds = &mut this.machine.fds;
+        let reader = fds.insert_fd(Box::new(reader));
+        let writer = fds.insert_fd(Box::new(writer));
+        fds.set_cloexec(reader, cloexec).unwrap();
+        fds.set_cloexec(writer, cloexec).unwrap();
+
+        this.write_scalar(Scalar::from_i32(reader), &pipefd)?;
+        this.write_scalar(
+            Scalar::from_i32(writer),
+            &pipefd.offset(pipefd.layout.size, pipefd.layout, this)?,
+        )?;
+
+        Ok(Scalar::from_i32(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipe() -> (PipeReader, PipeWriter) {
+        let buffer = Rc::new(RefCell::new(VecDeque::new()));
+        let capacity = Rc::new(Cell::new(DEFAULT_PIPE_CAPACITY));
+        let writer = PipeWriter {
+            buffer: Rc::downgrade(&buffer),
+            capacity: Rc::clone(&capacity),
+            is_nonblock: Rc::default(),
+        };
+        (PipeReader { buffer, capacity, is_nonblock: Rc::default() }, writer)
+    }
+
+    #[test]
+    fn read_readiness_follows_buffer() {
+        let (reader, writer) = pipe();
+        assert!(!reader.is_read_ready());
+        reader.buffer.borrow_mut().extend(b"abc");
+        assert!(reader.is_read_ready());
+        reader.buffer.borrow_mut().clear();
+        assert!(!reader.is_read_ready());
+        // Without write ends, reads return EOF, so they do not block.
+        drop(writer);
+        assert!(reader.is_read_ready());
+    }
+
+    #[test]
+    fn readiness_follows_direction() {
+        let (reader, writer) = pipe();
+        assert!(!reader.is_write_ready());
+        assert!(writer.is_write_ready());
+        reader.buffer.borrow_mut().extend(b"abc");
+        assert!(!writer.is_read_ready());
+    }
+
+    #[test]
+    fn write_readiness_follows_capacity() {
+        let (reader, writer) = pipe();
+        reader.capacity.set(3);
+        reader.buffer.borrow_mut().extend(b"ab");
+        assert!(writer.is_write_ready());
+        reader.buffer.borrow_mut().extend(b"c");
+        assert!(!writer.is_write_ready());
+        // Without read ends, writes fail with `EPIPE`, so they do not block.
+        drop(reader);
+        assert!(writer.is_write_ready());
+    }
+}
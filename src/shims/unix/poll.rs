@@ -0,0 +1,236 @@
+//! Waiting for file descriptors to become ready, as done by `poll` (and `epoll_wait`).
+
+use std::time::Duration;
+
+use crate::concurrency::thread::MachineCallback;
+use crate::shims::unix::*;
+use crate::*;
+
+/// An operation that waits for file descriptors to become ready, like `poll`.
+pub trait ReadinessCheck<'mir, 'tcx>: VisitProvenance {
+    /// Checks whether the operation can complete now. If so, writes its outputs and returns
+    /// its return value, otherwise returns `None`.
+    fn check(
+        &self,
+        this: &mut MiriInterpCx<'mir, 'tcx>,
+    ) -> InterpResult<'tcx, Option<Scalar<Provenance>>>;
+
+    /// Returns the earliest time at which one of the file descriptors becomes ready just by time
+    /// passing, as a duration since the clock's anchor, see `FileDescriptor::ready_at`.
+    fn ready_at(&self, this: &mut MiriInterpCx<'mir, 'tcx>)
+        -> InterpResult<'tcx, Option<Duration>>;
+}
+
+/// Runs the readiness check of a blocked thread again. This is registered both as a readiness
+/// callback, which the scheduler calls after other threads did something that might have made a
+/// file descriptor ready, and as a timeout callback, for when the timeout expires or a file
+/// descriptor becomes ready as time passes.
+struct ReadinessCallback<'tcx, C> {
+    thread: ThreadId,
+    check: C,
+    /// When the timeout expires, as a duration since the clock's anchor.
+    /// `None` means that we wait forever.
+    deadline: Option<Duration>,
+    dest: MPlaceTy<'tcx, Provenance>,
+}
+
+impl<'tcx, C: VisitProvenance> VisitProvenance for ReadinessCallback<'tcx, C> {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        let ReadinessCallback { thread: _, check, deadline: _, dest } = self;
+        check.visit_provenance(visit);
+        dest.visit_provenance(visit);
+    }
+}
+
+impl<'mir, 'tcx: 'mir, C: ReadinessCheck<'mir, 'tcx> + Clone + 'tcx> MachineCallback<'mir, 'tcx>
+    for ReadinessCallback<'tcx, C>
+{
+    fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+        // Whichever of the two callbacks got called, the other one is not needed anymore.
+        this.unregister_timeout_callback_if_exists(self.thread);
+        this.unregister_readiness_callback_if_exists(self.thread);
+        this.unblock_thread(self.thread, BlockReason::Readiness);
+        if let Some(result) = self.check.check(this)? {
+            this.write_scalar(result, &self.dest)?;
+            return Ok(());
+        }
+        let now = this.machine.clock.now().duration_since(this.machine.clock.anchor());
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            // Nothing became ready before the timeout expired.
+            this.write_scalar(Scalar::from_i32(0), &self.dest)?;
+        } else {
+            this.block_for_readiness(self.check.clone(), self.deadline, self.dest.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Blocks the active thread until the readiness check might succeed or the deadline passes.
+    fn block_for_readiness<C: ReadinessCheck<'mir, 'tcx> + Clone + 'tcx>(
+        &mut self,
+        check: C,
+        deadline: Option<Duration>,
+        dest: MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let thread = this.get_active_thread();
+        let wakeup = [deadline, check.ready_at(this)?].into_iter().flatten().min();
+        this.block_thread(thread, BlockReason::Readiness);
+        if let Some(wakeup) = wakeup {
+            let clock = &this.machine.clock;
+            // If adding the duration overflows, let's just wait for an hour. Waking up early is
+            // always acceptable.
+            let wakeup_time = clock
+                .anchor()
+                .checked_add(wakeup)
+                .unwrap_or_else(|| clock.now().checked_add(Duration::from_secs(3600)).unwrap());
+            this.register_timeout_callback(
+                thread,
+                CallbackTime::Monotonic(wakeup_time),
+                Box::new(ReadinessCallback {
+                    thread,
+                    check: check.clone(),
+                    deadline,
+                    dest: dest.clone(),
+                }),
+            );
+        }
+        this.register_readiness_callback(
+            thread,
+            Box::new(ReadinessCallback { thread, check, deadline, dest }),
+        );
+        Ok(())
+    }
+}
+
+/// The readiness check of `poll`.
+#[derive(Clone)]
+struct PollCheck {
+    fds: Pointer<Option<Provenance>>,
+    nfds: u64,
+}
+
+impl VisitProvenance for PollCheck {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        let PollCheck { fds, nfds: _ } = self;
+        fds.visit_provenance(visit);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> ReadinessCheck<'mir, 'tcx> for PollCheck {
+    /// Sets the `revents` of all `nfds` entries of the `pollfd` array at `fds`, and returns the
+    /// number of entries that have any events, if there are any.
+    fn check(
+        &self,
+        this: &mut MiriInterpCx<'mir, 'tcx>,
+    ) -> InterpResult<'tcx, Option<Scalar<Provenance>>> {
+        let pollin = this.eval_libc("POLLIN").to_i16()?;
+        let pollout = this.eval_libc("POLLOUT").to_i16()?;
+        let pollnval = this.eval_libc("POLLNVAL").to_i16()?;
+
+        let pollfd_layout = this.libc_ty_layout("pollfd");
+        let first = this.ptr_to_mplace(self.fds, pollfd_layout);
+        let mut ready = 0i32;
+        for i in 0..self.nfds {
+            let pollfd = first.offset(pollfd_layout.size * i, pollfd_layout, this)?;
+            let fd = this.read_scalar(&this.project_field_named(&pollfd, "fd")?)?.to_i32()?;
+            let events =
+                this.read_scalar(&this.project_field_named(&pollfd, "events")?)?.to_i16()?;
+
+            let revents = if fd < 0 {
+                // Negative file descriptors are ignored.
+                0
+            } else if let Some(file_descriptor) = this.machine.fds.get(fd) {
+                let mut revents = 0;
+                if file_descriptor.is_read_ready() {
+                    revents |= events & pollin;
+                }
+                if file_descriptor.is_write_ready() {
+                    revents |= events & pollout;
+                }
+                revents
+            } else {
+                pollnval
+            };
+            if revents != 0 {
+                ready = ready.checked_add(1).unwrap();
+            }
+            this.write_int(revents, &this.project_field_named(&pollfd, "revents")?)?;
+        }
+        Ok((ready > 0).then(|| Scalar::from_i32(ready)))
+    }
+
+    fn ready_at(
+        &self,
+        this: &mut MiriInterpCx<'mir, 'tcx>,
+    ) -> InterpResult<'tcx, Option<Duration>> {
+        let pollfd_layout = this.libc_ty_layout("pollfd");
+        let first = this.ptr_to_mplace(self.fds, pollfd_layout);
+        let mut ready_at = None;
+        for i in 0..self.nfds {
+            let pollfd = first.offset(pollfd_layout.size * i, pollfd_layout, this)?;
+            let fd = this.read_scalar(&this.project_field_named(&pollfd, "fd")?)?.to_i32()?;
+            let fd_ready_at =
+                this.machine.fds.get(fd).and_then(|file_descriptor| file_descriptor.ready_at());
+            ready_at = [ready_at, fd_ready_at].into_iter().flatten().min();
+        }
+        Ok(ready_at)
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Completes the operation of `check` as soon as it is ready, and writes its return value to
+    /// `dest`. A `timeout` (in milliseconds) of 0 returns immediately, and a negative `timeout`
+    /// waits forever. If the timeout expires, the return value is 0.
+    ///
+    /// While blocked, the check is run again whenever another thread might have made the file
+    /// descriptors ready.
+    fn wait_for_readiness<C: ReadinessCheck<'mir, 'tcx> + Clone + 'tcx>(
+        &mut self,
+        check: C,
+        timeout: i32,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if let Some(result) = check.check(this)? {
+            this.write_scalar(result, dest)?;
+            return Ok(());
+        }
+        if timeout == 0 {
+            this.write_scalar(Scalar::from_i32(0), dest)?;
+            return Ok(());
+        }
+
+        let deadline = u64::try_from(timeout).ok().map(|timeout| {
+            let clock = &this.machine.clock;
+            let now = clock.now().duration_since(clock.anchor());
+            now.saturating_add(Duration::from_millis(timeout))
+        });
+        this.block_for_readiness(check, deadline, dest.clone())
+    }
+
+    /// Waits for one of the file descriptors in the `pollfd` array `fds` to become ready.
+    ///
+    /// We only report `POLLIN`, `POLLOUT` and `POLLNVAL`.
+    fn poll(
+        &mut self,
+        fds: &OpTy<'tcx, Provenance>,
+        nfds: &OpTy<'tcx, Provenance>,
+        timeout: &OpTy<'tcx, Provenance>,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fds = this.read_pointer(fds)?;
+        let nfds = this.read_scalar(nfds)?.to_uint(this.libc_ty_layout("nfds_t").size)?;
+        let nfds = u64::try_from(nfds).unwrap();
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
+
+        this.wait_for_readiness(PollCheck { fds, nfds }, timeout, dest)
+    }
+}
@@ -1,21 +1,128 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 use std::io;
+use std::io::{ErrorKind, Read};
+use std::rc::{Rc, Weak};
+
+use rustc_middle::ty::TyCtxt;
+use rustc_target::abi::Size;
 
 use crate::shims::unix::*;
 use crate::*;
 
-/// Pair of connected sockets.
+/// The data flowing in one direction between the two ends of a socket pair.
+#[derive(Debug, Default)]
+struct SocketBuffer {
+    bytes: VecDeque<u8>,
+    /// Whether this direction has been shut down with `shutdown`. Once the remaining
+    /// bytes have been read, reads return EOF, and writes fail with `EPIPE`.
+    shut_down: bool,
+}
+
+/// One end of a pair of connected sockets.
 ///
-/// We currently don't allow sending any data through this pair, so this can be just a dummy.
+/// Each end owns the buffer that it reads from, and only holds a weak reference to the
+/// buffer of its peer, which it writes to. That way, when all file descriptors referring
+/// to one end have been closed, the other end can tell that its peer is gone.
 #[derive(Debug)]
-struct SocketPair;
+struct SocketPair {
+    /// The buffer we read from; the peer writes into it.
+    readbuf: Rc<RefCell<SocketBuffer>>,
+    /// The buffer the peer reads from; we write into it.
+    peer_readbuf: Weak<RefCell<SocketBuffer>>,
+    /// Whether this end is in non-blocking mode. Duplicates of this end share the flag,
+    /// the peer has its own.
+    is_nonblock: Rc<Cell<bool>>,
+}
 
 impl FileDescriptor for SocketPair {
     fn name(&self) -> &'static str {
         "socketpair"
     }
 
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let mut readbuf = self.readbuf.borrow_mut();
+        if readbuf.bytes.is_empty() && !bytes.is_empty() {
+            if self.peer_readbuf.strong_count() == 0 || readbuf.shut_down {
+                // The peer has been closed or this direction was shut down, this is EOF.
+                return Ok(Ok(0));
+            }
+            if self.is_nonblock.get() {
+                return Ok(Err(ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("socketpair: blocking reads are not supported");
+        }
+        // `VecDeque` implements `Read` by draining the front of the buffer.
+        Ok(readbuf.bytes.read(bytes))
+    }
+
+    fn write<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(peer_readbuf) = self.peer_readbuf.upgrade() else {
+            // The peer has been closed.
+            return Ok(Err(ErrorKind::BrokenPipe.into()));
+        };
+        let mut peer_readbuf = peer_readbuf.borrow_mut();
+        if peer_readbuf.shut_down {
+            return Ok(Err(ErrorKind::BrokenPipe.into()));
+        }
+        // We do not model a capacity limit, so writes never block.
+        peer_readbuf.bytes.extend(bytes);
+        Ok(Ok(bytes.len()))
+    }
+
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
-        Ok(Box::new(SocketPair))
+        Ok(Box::new(SocketPair {
+            readbuf: Rc::clone(&self.readbuf),
+            peer_readbuf: Weak::clone(&self.peer_readbuf),
+            is_nonblock: Rc::clone(&self.is_nonblock),
+        }))
+    }
+
+    fn is_read_ready(&self) -> bool {
+        // Once the peer is closed or this direction is shut down, reads return EOF without
+        // blocking.
+        let readbuf = self.readbuf.borrow();
+        !readbuf.bytes.is_empty() || readbuf.shut_down || self.peer_readbuf.strong_count() == 0
+    }
+
+    fn is_socket(&self) -> bool {
+        true
+    }
+
+    fn stat(&self) -> Option<FdStat> {
+        Some(FdStat { kind: FdKind::Socket, size: 0 })
+    }
+
+    fn shutdown<'tcx>(&self, read: bool, write: bool) -> InterpResult<'tcx> {
+        if read {
+            self.readbuf.borrow_mut().shut_down = true;
+        }
+        if write {
+            // If the peer is already gone, there is nobody left to notify.
+            if let Some(peer_readbuf) = self.peer_readbuf.upgrade() {
+                peer_readbuf.borrow_mut().shut_down = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_nonblock(&self) -> bool {
+        self.is_nonblock.get()
+    }
+
+    fn set_nonblock<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.is_nonblock.set(nonblock);
+        Ok(())
     }
 
     fn close<'tcx>(
@@ -28,10 +135,8 @@ impl FileDescriptor for SocketPair {
 
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
 pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
-    /// Currently this function this function is a stub. Eventually we need to
-    /// properly implement an FD type for sockets and have this function create
-    /// two sockets and associated FDs such that writing to one will produce
-    /// data that can be read from the other.
+    /// Creates a pair of connected `AF_UNIX` stream sockets, such that data written to one
+    /// of them can be read from the other.
     ///
     /// For more information on the arguments see the socketpair manpage:
     /// <https://linux.die.net/man/2/socketpair>
@@ -44,17 +149,64 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
     ) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
-        let _domain = this.read_scalar(domain)?.to_i32()?;
-        let _type_ = this.read_scalar(type_)?.to_i32()?;
-        let _protocol = this.read_scalar(protocol)?.to_i32()?;
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let mut type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
         let sv = this.deref_pointer(sv)?;
 
-        // FIXME: fail on unsupported inputs
+        let mut is_nonblock = false;
+        let mut cloexec = false;
+        // Parse and remove the type flags that we support. Only Linux supports passing
+        // these flags as part of the type.
+        if this.tcx.sess.target.os == "linux" {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            if type_ & sock_nonblock == sock_nonblock {
+                is_nonblock = true;
+                type_ &= !sock_nonblock;
+            }
+            if type_ & sock_cloexec == sock_cloexec {
+                cloexec = true;
+                type_ &= !sock_cloexec;
+            }
+        }
+
+        // Fail on unsupported input.
+        // AF_UNIX and AF_LOCAL are synonyms, so we accept both in case
+        // their values differ.
+        if domain != this.eval_libc_i32("AF_UNIX") && domain != this.eval_libc_i32("AF_LOCAL") {
+            throw_unsup_format!(
+                "socketpair: domain {domain:#x} is unsupported, only AF_UNIX and AF_LOCAL are allowed"
+            );
+        } else if type_ != this.eval_libc_i32("SOCK_STREAM") {
+            throw_unsup_format!(
+                "socketpair: type {type_:#x} is unsupported, only SOCK_STREAM, SOCK_CLOEXEC and SOCK_NONBLOCK are allowed"
+            );
+        } else if protocol != 0 {
+            throw_unsup_format!(
+                "socketpair: protocol {protocol} is unsupported, only 0 is allowed"
+            );
+        }
+
+        let buffer1 = Rc::new(RefCell::new(SocketBuffer::default()));
+        let buffer2 = Rc::new(RefCell::new(SocketBuffer::default()));
+        let socketpair_0 = SocketPair {
+            peer_readbuf: Rc::downgrade(&buffer2),
+            readbuf: Rc::clone(&buffer1),
+            is_nonblock: Rc::new(Cell::new(is_nonblock)),
+        };
+        let socketpair_1 = SocketPair {
+            peer_readbuf: Rc::downgrade(&buffer1),
+            readbuf: buffer2,
+            is_nonblock: Rc::new(Cell::new(is_nonblock)),
+        };
 
         let fds = &mut this.machine.fds;
-        let sv0 = fds.insert_fd(Box::new(SocketPair));
+        let sv0 = fds.insert_fd(Box::new(socketpair_0));
+        let sv1 = fds.insert_fd(Box::new(socketpair_1));
+        fds.set_cloexec(sv0, cloexec).unwrap();
+        fds.set_cloexec(sv1, cloexec).unwrap();
         let sv0 = Scalar::try_from_int(sv0, sv.layout.size).unwrap();
-        let sv1 = fds.insert_fd(Box::new(SocketPair));
         let sv1 = Scalar::try_from_int(sv1, sv.layout.size).unwrap();
 
         this.write_scalar(sv0, &sv)?;
@@ -62,4 +214,93 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
 
         Ok(Scalar::from_i32(0))
     }
+
+    /// Stores the address of the socket `fd` in `addr`, truncated to the length stored in
+    /// `addrlen`, and stores the actual length of the address in `addrlen`.
+    ///
+    /// This also implements `getpeername`: the only sockets we support are created by
+    /// `socketpair`, so both ends have the same unnamed `AF_UNIX` address, which consists
+    /// of just the address family.
+    fn getsockname(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+        let addrlen = this.deref_pointer_as(addrlen, this.libc_ty_layout("socklen_t"))?;
+
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            return this.fd_not_found();
+        };
+        if !file_descriptor.is_socket() {
+            let enotsock = this.eval_libc("ENOTSOCK");
+            this.set_last_error(enotsock)?;
+            return Ok(-1);
+        }
+        let len = this.read_scalar(&addrlen)?.to_u32()?;
+
+        // Build the address in a temporary place, so we can copy just the part that fits.
+        let sockaddr =
+            this.allocate(this.libc_ty_layout("sockaddr_un"), MiriMemoryKind::Machine.into())?;
+        this.write_bytes_ptr(
+            sockaddr.ptr(),
+            std::iter::repeat(0u8).take(usize::try_from(sockaddr.layout.size.bytes()).unwrap()),
+        )?;
+        // An unnamed address ends where the path would start.
+        let sun_path = this.project_field_named(&sockaddr, "sun_path")?;
+        let addr_size = sun_path.ptr().addr() - sockaddr.ptr().addr();
+        let af_unix = this.eval_libc_i32("AF_UNIX");
+        this.write_int_fields_named(&[("sun_family", af_unix.into())], &sockaddr)?;
+        if this.projectable_has_field(&sockaddr, "sun_len") {
+            // The BSDs also store the length of the address in the address itself.
+            this.write_int_fields_named(&[("sun_len", addr_size.bytes().into())], &sockaddr)?;
+        }
+
+        let copy_size = addr_size.min(Size::from_bytes(len));
+        this.mem_copy(sockaddr.ptr(), addr, copy_size, /*nonoverlapping*/ true)?;
+        this.deallocate_ptr(sockaddr.ptr(), None, MiriMemoryKind::Machine.into())?;
+        this.write_int(addr_size.bytes(), &addrlen)?;
+
+        Ok(0)
+    }
+
+    /// Shuts down the reading and/or writing direction of the socket `fd`, depending on `how`.
+    /// Unlike `close`, this affects all file descriptors referring to the socket, and the peer.
+    fn shutdown(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        how: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let how = this.read_scalar(how)?.to_i32()?;
+
+        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+            return this.fd_not_found();
+        };
+        if !file_descriptor.is_socket() {
+            let enotsock = this.eval_libc("ENOTSOCK");
+            this.set_last_error(enotsock)?;
+            return Ok(-1);
+        }
+        let (read, write) = if how == this.eval_libc_i32("SHUT_RD") {
+            (true, false)
+        } else if how == this.eval_libc_i32("SHUT_WR") {
+            (false, true)
+        } else if how == this.eval_libc_i32("SHUT_RDWR") {
+            (true, true)
+        } else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+        file_descriptor.shutdown(read, write)?;
+
+        Ok(0)
+    }
 }
@@ -111,6 +111,57 @@ pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
         Ok(if success { Scalar::from_u32(0) } else { this.eval_libc("ERANGE") })
     }
 
+    /// Returns the OS-level id of the current thread. We derive it from Miri's thread id, adding 1
+    /// since 0 is never a valid thread id. Under isolation, this cannot match `getpid` for the
+    /// main thread like it would on Linux.
+    fn gettid(&mut self) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let thread_id = this.get_active_thread();
+        let tid = u64::from(thread_id).checked_add(1).unwrap();
+        Ok(Scalar::from_i32(tid.try_into().unwrap()))
+    }
+
+    /// Stores the OS-level id of `thread` (or of the current thread, if `thread` is null) in
+    /// `thread_id`. The id is computed like in `gettid`. Since the main thread's `pthread_t` is
+    /// null, a null `thread` is only supported on the main thread.
+    fn pthread_threadid_np(
+        &mut self,
+        thread: &OpTy<'tcx, Provenance>,
+        thread_id_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let thread = this.read_target_usize(thread)?;
+        let thread_id_ptr = this.read_pointer(thread_id_op)?;
+
+        if this.ptr_is_null(thread_id_ptr)? {
+            return Ok(this.eval_libc("EINVAL"));
+        }
+        // A null `pthread_t` refers to the current thread, but it is also the `pthread_t` of
+        // Miri's main thread. Both meanings agree in the main thread; in any other thread, we
+        // cannot tell which one the program means.
+        let thread = if thread == 0 {
+            if this.get_active_thread() != ThreadId::MAIN_THREAD {
+                throw_unsup_format!(
+                    "`pthread_threadid_np` with a null thread is only supported on the main thread; \
+                    use `pthread_self()` instead"
+                );
+            }
+            ThreadId::MAIN_THREAD
+        } else if thread < u64::try_from(this.get_total_thread_count()).unwrap() {
+            ThreadId::try_from(thread).unwrap()
+        } else {
+            return Ok(this.eval_libc("ESRCH"));
+        };
+
+        let tid = u64::from(thread).checked_add(1).unwrap();
+        let thread_id = this.deref_pointer_as(thread_id_op, this.machine.layouts.u64)?;
+        this.write_scalar(Scalar::from_u64(tid), &thread_id)?;
+
+        Ok(Scalar::from_i32(0))
+    }
+
     fn sched_yield(&mut self) -> InterpResult<'tcx, i32> {
         let this = self.eval_context_mut();
 
@@ -6,9 +6,10 @@ use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
 use super::{
-    bin_op_simd_float_all, conditional_dot_product, convert_float_to_int, horizontal_bin_op,
-    mask_load, mask_store, round_all, test_bits_masked, test_high_bits_masked, unary_op_ps,
-    FloatBinOp, FloatUnaryOp,
+    addsub, bin_op_simd_float_all, blend_imm, blendv, conditional_dot_product,
+    convert_float_to_float, convert_float_to_int, horizontal_bin_op, mask_load, mask_store,
+    round_all, shuffle_float, split_simd_to_128bit_chunks, test_bits_masked, test_high_bits_masked,
+    unary_op_ps, FloatBinOp, FloatUnaryOp,
 };
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
@@ -25,7 +26,6 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
         dest: &MPlaceTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, EmulateForeignItemResult> {
         let this = self.eval_context_mut();
-        this.expect_target_feature_for_intrinsic(link_name, "avx")?;
         // Prefix should have already been checked.
         let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.avx.").unwrap();
 
@@ -112,6 +112,14 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 horizontal_bin_op(this, which, /*saturating*/ false, left, right, dest)?;
             }
+            // Used to implement the _mm256_addsub_ps and _mm256_addsub_pd functions.
+            // Like `sse3.addsub.ps` and `sse3.addsub.pd`, but on 256-bit vectors.
+            "addsub.ps.256" | "addsub.pd.256" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                addsub(this, left, right, dest)?;
+            }
             // Used to implement the _mm256_cmp_ps function.
             // Performs a comparison operation on each component of `left`
             // and `right`. For each component, returns 0 if false or u32::MAX
@@ -154,6 +162,29 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 convert_float_to_int(this, op, rnd, dest)?;
             }
+            // Used to implement the _mm256_cvtpd_ps function.
+            // Converts 4 packed f64 to 4 packed f32, rounding to nearest.
+            // (_mm256_cvtps_pd is lowered to a generic `fpext` instead of an intrinsic.)
+            "cvt.pd2.ps.256" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (op_len, _) = op.layout.ty.simd_size_and_type(*this.tcx);
+                let (dest_len, _) = dest.layout.ty.simd_size_and_type(*this.tcx);
+                // f64x4 to f32x4, so no element is dropped or zeroed.
+                assert_eq!(op_len, 4);
+                assert_eq!(dest_len, 4);
+
+                convert_float_to_float(this, op, dest)?;
+            }
+            // Used to implement the _mm256_shuffle_ps and _mm256_shuffle_pd functions
+            // (by older versions of LLVM, newer versions use a shuffle).
+            // Like `shuf.ps` and `shuf.pd`, applied to each 128-bit chunk.
+            "shuf.ps.256" | "shuf.pd.256" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                shuffle_float(this, left, right, imm, dest)?;
+            }
             // Used to implement the _mm_permutevar_ps and _mm256_permutevar_ps functions.
             // Shuffles 32-bit floats from `data` using `control` as control. Each 128-bit
             // chunk is shuffled independently: this means that we view the vector as a
@@ -302,6 +333,45 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                 // Unaligned copy, which is what we want.
                 this.mem_copy(src_ptr, dest.ptr(), dest.layout.size, /*nonoverlapping*/ true)?;
             }
+            // Used to implement the _mm_broadcast_ss, _mm256_broadcast_ss and
+            // _mm256_broadcast_sd functions.
+            // Reads a single element from an unaligned pointer and writes it to
+            // every element of `dest`.
+            "vbroadcast.ss" | "vbroadcast.ss.256" | "vbroadcast.sd.256" => {
+                let [src_ptr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let src_ptr = this.read_pointer(src_ptr)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                for i in 0..dest_len {
+                    let dest = this.project_index(&dest, i)?;
+                    // Unaligned copy, which is what we want.
+                    this.mem_copy(
+                        src_ptr,
+                        dest.ptr(),
+                        dest.layout.size,
+                        /*nonoverlapping*/ true,
+                    )?;
+                }
+            }
+            // Used to implement the _mm256_broadcast_ps and _mm256_broadcast_pd functions.
+            // Reads a 128-bit vector from an unaligned pointer and writes it to
+            // both 128-bit halves of `dest`.
+            "vbroadcastf128.ps.256" | "vbroadcastf128.pd.256" => {
+                let [src_ptr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let src_ptr = this.read_pointer(src_ptr)?;
+                let (num_chunks, _, dest) = split_simd_to_128bit_chunks(this, dest)?;
+
+                for i in 0..num_chunks {
+                    let dest = this.project_index(&dest, i)?;
+                    // Unaligned copy, which is what we want.
+                    this.mem_copy(
+                        src_ptr,
+                        dest.ptr(),
+                        dest.layout.size,
+                        /*nonoverlapping*/ true,
+                    )?;
+                }
+            }
             // Used to implement the _mm256_testz_si256, _mm256_testc_si256 and
             // _mm256_testnzc_si256 functions.
             // Tests `op & mask == 0`, `op & mask == mask` or
@@ -343,6 +413,37 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 this.write_scalar(Scalar::from_i32(res.into()), dest)?;
             }
+            // Used to implement the _mm256_blendv_ps and _mm256_blendv_pd functions.
+            // Selects each element from `right` if the high bit of the corresponding
+            // element of `mask` is set, and from `left` otherwise.
+            "blendv.ps.256" | "blendv.pd.256" => {
+                let [left, right, mask] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = match unprefixed_name {
+                    "blendv.ps.256" => this.tcx.types.u32,
+                    "blendv.pd.256" => this.tcx.types.u64,
+                    _ => unreachable!(),
+                };
+
+                blendv(this, elem_ty, left, right, mask, dest)?;
+            }
+            // Used to implement the _mm256_blend_ps and _mm256_blend_pd functions
+            // (by older versions of LLVM, newer versions use a shuffle).
+            // Selects each element from `right` if the corresponding bit of `imm` is set,
+            // and from `left` otherwise.
+            "blend.ps.256" | "blend.pd.256" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = match unprefixed_name {
+                    "blend.ps.256" => this.tcx.types.u32,
+                    "blend.pd.256" => this.tcx.types.u64,
+                    _ => unreachable!(),
+                };
+
+                blend_imm(this, elem_ty, left, right, imm, dest)?;
+            }
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
         Ok(EmulateForeignItemResult::NeedsJumping)
@@ -5,8 +5,10 @@ use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
 use super::{
-    horizontal_bin_op, int_abs, mask_load, mask_store, mpsadbw, packssdw, packsswb, packusdw,
-    packuswb, pmulhrsw, psign, shift_simd_by_scalar, shift_simd_by_simd, ShiftOp,
+    blend_imm, blendv, broadcast_first, horizontal_bin_op, int_abs, int_min_max, mask_load,
+    mask_store, mpsadbw, packssdw, packsswb, packusdw, packuswb, palignr, pcmpgtq, pmulhrsw,
+    psadbw, psign, shift_simd_by_bytes, shift_simd_by_scalar, shift_simd_by_simd, shuffle_epi32,
+    split_simd_to_128bit_chunks, ShiftOp,
 };
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
@@ -23,7 +25,6 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
         dest: &MPlaceTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, EmulateForeignItemResult> {
         let this = self.eval_context_mut();
-        this.expect_target_feature_for_intrinsic(link_name, "avx2")?;
         // Prefix should have already been checked.
         let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.avx2.").unwrap();
 
@@ -334,31 +335,7 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                 let [left, right] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
 
-                let (left, left_len) = this.operand_to_simd(left)?;
-                let (right, right_len) = this.operand_to_simd(right)?;
-                let (dest, dest_len) = this.mplace_to_simd(dest)?;
-
-                assert_eq!(left_len, right_len);
-                assert_eq!(left_len, dest_len.checked_mul(8).unwrap());
-
-                for i in 0..dest_len {
-                    let dest = this.project_index(&dest, i)?;
-
-                    let mut acc: u16 = 0;
-                    for j in 0..8 {
-                        let src_index = i.checked_mul(8).unwrap().checked_add(j).unwrap();
-
-                        let left = this.project_index(&left, src_index)?;
-                        let left = this.read_scalar(&left)?.to_u8()?;
-
-                        let right = this.project_index(&right, src_index)?;
-                        let right = this.read_scalar(&right)?.to_u8()?;
-
-                        acc = acc.checked_add(left.abs_diff(right).into()).unwrap();
-                    }
-
-                    this.write_scalar(Scalar::from_u64(acc.into()), &dest)?;
-                }
+                psadbw(this, left, right, dest)?;
             }
             // Used to implement the _mm256_shuffle_epi8 intrinsic.
             // Shuffles bytes from `left` using `right` as pattern.
@@ -367,29 +344,39 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                 let [left, right] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
 
-                let (left, left_len) = this.operand_to_simd(left)?;
-                let (right, right_len) = this.operand_to_simd(right)?;
-                let (dest, dest_len) = this.mplace_to_simd(dest)?;
-
-                assert_eq!(dest_len, left_len);
-                assert_eq!(dest_len, right_len);
+                let (num_chunks, items_per_chunk, left) = split_simd_to_128bit_chunks(this, left)?;
+                let (_, _, right) = split_simd_to_128bit_chunks(this, right)?;
+                let (_, _, dest) = split_simd_to_128bit_chunks(this, dest)?;
 
-                for i in 0..dest_len {
-                    let right = this.read_scalar(&this.project_index(&right, i)?)?.to_u8()?;
+                for i in 0..num_chunks {
+                    let left = this.project_index(&left, i)?;
+                    let right = this.project_index(&right, i)?;
                     let dest = this.project_index(&dest, i)?;
 
-                    let res = if right & 0x80 == 0 {
-                        // Shuffle each 128-bit (16-byte) block independently.
-                        let j = u64::from(right % 16).checked_add(i & !15).unwrap();
-                        this.read_scalar(&this.project_index(&left, j)?)?
-                    } else {
-                        // If the highest bit in `right` is 1, write zero.
-                        Scalar::from_u8(0)
-                    };
+                    for j in 0..items_per_chunk {
+                        let ctrl = this.read_scalar(&this.project_index(&right, j)?)?.to_u8()?;
+                        let dest = this.project_index(&dest, j)?;
+
+                        let res = if ctrl & 0x80 == 0 {
+                            let k = u64::from(ctrl % 16);
+                            this.read_scalar(&this.project_index(&left, k)?)?
+                        } else {
+                            // If the highest bit in the control byte is 1, write zero.
+                            Scalar::from_u8(0)
+                        };
 
-                    this.write_scalar(res, &dest)?;
+                        this.write_scalar(res, &dest)?;
+                    }
                 }
             }
+            // Used to implement the _mm256_shuffle_epi32 function (by older versions
+            // of LLVM, newer versions use a shuffle).
+            // Like `sse2.pshuf.d`, applied to each 128-bit chunk with the same `imm`.
+            "pshuf.d" => {
+                let [op, imm] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                shuffle_epi32(this, op, imm, dest)?;
+            }
             // Used to implement the _mm256_sign_epi{8,16,32} functions.
             // Negates elements from `left` when the corresponding element in
             // `right` is negative. If an element from `right` is zero, zero
@@ -423,6 +410,48 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 shift_simd_by_scalar(this, left, right, which, dest)?;
             }
+            // Used to implement the _mm256_cmpgt_epi64 function when it is not
+            // lowered to a generic SIMD comparison.
+            // Like `sse42.pcmpgtq`, but on 256-bit vectors.
+            "pcmpgt.q" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                pcmpgtq(this, left, right, dest)?;
+            }
+            // Used to implement the _mm256_alignr_epi8 function. Current `stdarch` builds
+            // it from a shuffle, so only direct declarations of the intrinsic get here.
+            // Like `ssse3.palign.r.128`, but on each 128-bit lane of `left` and `right`
+            // independently.
+            "palignr" => {
+                let [left, right, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = this.read_scalar(count)?.to_u8()?;
+
+                palignr(this, left, right, count, dest)?;
+            }
+            // LLVM upgrades these intrinsics to shuffles, which is also how `stdarch`
+            // implements _mm256_slli_si256, _mm256_bslli_epi128, _mm256_srli_si256 and
+            // _mm256_bsrli_epi128 nowadays; they are still accepted as declared.
+            // Shifts each 128-bit lane of `op` by the number of bytes in `count`,
+            // filling with zeros. The count is given in bits for `psll.dq` and `psrl.dq`,
+            // and in bytes for the `.bs` variants.
+            "psll.dq" | "psrl.dq" | "psll.dq.bs" | "psrl.dq.bs" => {
+                let [op, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = u64::from(this.read_scalar(count)?.to_u32()?);
+                let (count, left) = match unprefixed_name {
+                    "psll.dq" => (count / 8, true),
+                    "psrl.dq" => (count / 8, false),
+                    "psll.dq.bs" => (count, true),
+                    "psrl.dq.bs" => (count, false),
+                    _ => unreachable!(),
+                };
+
+                shift_simd_by_bytes(this, op, count, left, dest)?;
+            }
             // Used to implement the _mm{,256}_{sllv,srlv,srav}_epi{32,64} functions
             // (except _mm{,256}_srav_epi64, which are not available in AVX2).
             "psllv.d" | "psllv.d.256" | "psllv.q" | "psllv.q.256" | "psrlv.d" | "psrlv.d.256"
@@ -439,6 +468,79 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 shift_simd_by_simd(this, left, right, which, dest)?;
             }
+            // Used to implement the _mm256_blendv_epi8 function.
+            // Selects each byte from `right` if the high bit of the corresponding
+            // byte of `mask` is set, and from `left` otherwise.
+            "pblendvb" => {
+                let [left, right, mask] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = this.tcx.types.u8;
+                blendv(this, elem_ty, left, right, mask, dest)?;
+            }
+            // Used to implement the _mm256_blend_epi16 function (by older versions
+            // of LLVM, newer versions use a shuffle).
+            // Selects each element from `right` if the corresponding bit of `imm` is set,
+            // and from `left` otherwise. The same 8 bits are used for both 128-bit halves.
+            "pblendw" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = this.tcx.types.u16;
+                blend_imm(this, elem_ty, left, right, imm, dest)?;
+            }
+            // Used to implement the _mm_broadcastss_ps, _mm256_broadcastss_ps and
+            // _mm256_broadcastsd_pd functions.
+            // Writes the first element of `op` to every element of `dest`.
+            "vbroadcast.ss.ps" | "vbroadcast.ss.ps.256" | "vbroadcast.sd.pd.256" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = match unprefixed_name {
+                    "vbroadcast.ss.ps" | "vbroadcast.ss.ps.256" => this.tcx.types.f32,
+                    "vbroadcast.sd.pd.256" => this.tcx.types.f64,
+                    _ => unreachable!(),
+                };
+                broadcast_first(this, elem_ty, op, dest)?;
+            }
+            // Integer counterparts of `avx2.vbroadcast.*`, as emitted by older compilers for
+            // the _mm{,256}_broadcast{b,w,d,q}_epi{8,16,32,64} functions.
+            // Writes the first element of `op` to every element of `dest`. The element
+            // size is given by the name rather than by the vector types, since `__m128i`
+            // and `__m256i` are vectors of `i64`.
+            "pbroadcastb.128" | "pbroadcastb.256" | "pbroadcastw.128" | "pbroadcastw.256"
+            | "pbroadcastd.128" | "pbroadcastd.256" | "pbroadcastq.128" | "pbroadcastq.256" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = match unprefixed_name {
+                    "pbroadcastb.128" | "pbroadcastb.256" => this.tcx.types.u8,
+                    "pbroadcastw.128" | "pbroadcastw.256" => this.tcx.types.u16,
+                    "pbroadcastd.128" | "pbroadcastd.256" => this.tcx.types.u32,
+                    "pbroadcastq.128" | "pbroadcastq.256" => this.tcx.types.u64,
+                    _ => unreachable!(),
+                };
+                broadcast_first(this, elem_ty, op, dest)?;
+            }
+            // Used to implement the _mm256_{min,max}_{epi,epu}{8,16,32} functions (by
+            // older versions of LLVM, newer versions use generic integer min/max).
+            // Computes the minimum or maximum of each pair of elements.
+            "pmins.b" | "pmins.w" | "pmins.d" | "pmaxs.b" | "pmaxs.w" | "pmaxs.d" | "pminu.b"
+            | "pminu.w" | "pminu.d" | "pmaxu.b" | "pmaxu.w" | "pmaxu.d" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let max = unprefixed_name.starts_with("pmax");
+                let elem_ty = match unprefixed_name {
+                    "pmins.b" | "pmaxs.b" => this.tcx.types.i8,
+                    "pmins.w" | "pmaxs.w" => this.tcx.types.i16,
+                    "pmins.d" | "pmaxs.d" => this.tcx.types.i32,
+                    "pminu.b" | "pmaxu.b" => this.tcx.types.u8,
+                    "pminu.w" | "pmaxu.w" => this.tcx.types.u16,
+                    "pminu.d" | "pmaxu.d" => this.tcx.types.u32,
+                    _ => unreachable!(),
+                };
+
+                int_min_max(this, max, elem_ty, left, right, dest)?;
+            }
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
         Ok(EmulateForeignItemResult::NeedsJumping)
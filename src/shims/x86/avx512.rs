@@ -0,0 +1,360 @@
+use rustc_apfloat::ieee::{Double, Single};
+use rustc_middle::mir;
+use rustc_span::Symbol;
+use rustc_target::abi::Size;
+use rustc_target::spec::abi::Abi;
+
+use super::{
+    convert_float_to_int, convert_int_to_float_first, float_to_int_or_indefinite, rounding_from_imm,
+};
+use crate::*;
+use shims::foreign_items::EmulateForeignItemResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
+    crate::MiriInterpCxExt<'mir, 'tcx>
+{
+    fn emulate_x86_avx512_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateForeignItemResult> {
+        let this = self.eval_context_mut();
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.avx512.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement the _mm_cvt_roundu32_ss and _mm_cvt_roundu64_ss functions.
+            // Converts `right` from u32/u64 to f32 according to the rounding mode
+            // `rounding`. Returns a SIMD vector with the result in the first component
+            // and the remaining components are copied from `left`.
+            // https://www.felixcloutier.com/x86/vcvtusi2ss
+            "cvtusi2ss" | "cvtusi642ss" => {
+                let [left, right, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let rnd = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+
+                convert_int_to_float_first::<Single>(
+                    this, left, right, /* signed */ false, rnd, dest,
+                )?;
+            }
+            // Used to implement the _mm_cvt_roundu64_sd function.
+            // Converts `right` from u64 to f64 according to the rounding mode
+            // `rounding`. Returns a SIMD vector with the result in the first component
+            // and the remaining component is copied from `left`.
+            // https://www.felixcloutier.com/x86/vcvtusi2sd
+            "cvtusi642sd" => {
+                let [left, right, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let rnd = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+
+                convert_int_to_float_first::<Double>(
+                    this, left, right, /* signed */ false, rnd, dest,
+                )?;
+            }
+            // Used to implement the _mm_cvtss_u32, _mm_cvttss_u32, _mm_cvtss_u64,
+            // _mm_cvttss_u64, _mm_cvtsd_u32, _mm_cvttsd_u32, _mm_cvtsd_u64 and
+            // _mm_cvttsd_u64 functions (and their _mm_cvt{,t}_round* variants).
+            // Converts the first component of `op` from f32/f64 to u32/u64.
+            // When the value is NaN or out of range, returns the maximum value.
+            "vcvtss2usi32" | "vcvtss2usi64" | "vcvtsd2usi32" | "vcvtsd2usi64" | "cvttss2usi"
+            | "cvttss2usi64" | "cvttsd2usi" | "cvttsd2usi64" => {
+                let [op, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let (op, _) = this.operand_to_simd(op)?;
+
+                let op = this.read_immediate(&this.project_index(&op, 0)?)?;
+
+                let rnd = match unprefixed_name {
+                    // Rounding mode given by `rounding`.
+                    // https://www.felixcloutier.com/x86/vcvtss2usi
+                    // https://www.felixcloutier.com/x86/vcvtsd2usi
+                    "vcvtss2usi32" | "vcvtss2usi64" | "vcvtsd2usi32" | "vcvtsd2usi64" =>
+                        rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?,
+                    // Always truncate. `rounding` can only be used to suppress
+                    // exceptions, which we cannot observe.
+                    // https://www.felixcloutier.com/x86/vcvttss2usi
+                    // https://www.felixcloutier.com/x86/vcvttsd2usi
+                    "cvttss2usi" | "cvttss2usi64" | "cvttsd2usi" | "cvttsd2usi64" =>
+                        rustc_apfloat::Round::TowardZero,
+                    _ => unreachable!(),
+                };
+
+                // The result is always unsigned, regardless of how the return
+                // type was declared.
+                let int_layout = match dest.layout.size.bits() {
+                    32 => this.machine.layouts.u32,
+                    64 => this.machine.layouts.u64,
+                    _ => unreachable!(),
+                };
+                let res = float_to_int_or_indefinite(this, &op, int_layout, rnd)?;
+
+                this.write_scalar(res.to_scalar(), dest)?;
+            }
+            // Used to implement the _mm512_cvt{,t}ps_epi32 and _mm512_cvt{,t}ps_epu32
+            // functions, their _mask_ and _maskz_ variants, and the _mm512_cvt_round*
+            // variants.
+            // Converts each element of `op` from f32 to i32/u32 (as given by the element
+            // type of `dest`) if the corresponding bit of `mask` is set, and takes the
+            // element of `src` otherwise. When the value is NaN or out of range, returns
+            // the minimum value for signed and the maximum value for unsigned integers.
+            "mask.cvtps2dq.512"
+            | "mask.cvttps2dq.512"
+            | "mask.cvtps2udq.512"
+            | "mask.cvttps2udq.512" => {
+                let [op, src, mask, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let rnd = match unprefixed_name {
+                    // Rounding mode given by `rounding`.
+                    "mask.cvtps2dq.512" | "mask.cvtps2udq.512" =>
+                        rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?,
+                    // Always truncate. `rounding` can only be used to suppress
+                    // exceptions, which we cannot observe.
+                    "mask.cvttps2dq.512" | "mask.cvttps2udq.512" =>
+                        rustc_apfloat::Round::TowardZero,
+                    _ => unreachable!(),
+                };
+                let mask = this.read_scalar(mask)?.to_u16()?;
+
+                convert_float_to_int(this, op, rnd, dest)?;
+
+                let (src, src_len) = this.operand_to_simd(src)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+                assert_eq!(dest_len, src_len);
+
+                for i in 0..dest_len {
+                    if mask.checked_shr(u32::try_from(i).unwrap()).unwrap() & 1 == 0 {
+                        this.copy_op(
+                            &this.project_index(&src, i)?,
+                            &this.project_index(&dest, i)?,
+                        )?;
+                    }
+                }
+            }
+            // Used to implement the _mm512_{add,sub,mul,div}_round_ps functions (and their
+            // _mask_ and _maskz_ variants, which select the lanes of the result with
+            // `simd_select_bitmask`).
+            // Performs the operation on each component of `left` and `right`, rounding
+            // according to `rounding`.
+            "add.ps.512" | "sub.ps.512" | "mul.ps.512" | "div.ps.512" => {
+                let [left, right, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = arith_op_from_name(unprefixed_name);
+                let rnd = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+
+                bin_op_simd_float_round::<Single>(this, which, left, right, rnd, dest)?;
+            }
+            // Used to implement the _mm512_{add,sub,mul,div}_round_pd functions (and their
+            // _mask_ and _maskz_ variants).
+            // Performs the operation on each component of `left` and `right`, rounding
+            // according to `rounding`.
+            "add.pd.512" | "sub.pd.512" | "mul.pd.512" | "div.pd.512" => {
+                let [left, right, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = arith_op_from_name(unprefixed_name);
+                let rnd = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+
+                bin_op_simd_float_round::<Double>(this, which, left, right, rnd, dest)?;
+            }
+            // Used to implement the _mm_{add,sub,mul,div}_round_ss functions and their
+            // _mask_ and _maskz_ variants.
+            // Performs the operation on the first component of `left` and `right` if the
+            // lowest bit of `mask` is set, and takes the first component of `src`
+            // otherwise. The remaining components are copied from `left`.
+            "mask.add.ss.round" | "mask.sub.ss.round" | "mask.mul.ss.round"
+            | "mask.div.ss.round" => {
+                let [left, right, src, mask, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = arith_op_from_name(unprefixed_name);
+                let mask = this.read_scalar(mask)?.to_u8()?;
+                let rnd = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+
+                bin_op_float_first_masked::<Single>(
+                    this, which, left, right, src, mask, rnd, dest,
+                )?;
+            }
+            // Used to implement the _mm_{add,sub,mul,div}_round_sd functions and their
+            // _mask_ and _maskz_ variants.
+            // Same as the `ss` variants above, but on f64.
+            "mask.add.sd.round" | "mask.sub.sd.round" | "mask.mul.sd.round"
+            | "mask.div.sd.round" => {
+                let [left, right, src, mask, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = arith_op_from_name(unprefixed_name);
+                let mask = this.read_scalar(mask)?.to_u8()?;
+                let rnd = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+
+                bin_op_float_first_masked::<Double>(
+                    this, which, left, right, src, mask, rnd, dest,
+                )?;
+            }
+            // The _kand_mask16, _kandn_mask16, _kor_mask16, _kxor_mask16 and _kxnor_mask16
+            // functions are plain integer operations in `stdarch`, but LLVM used to expose
+            // them as intrinsics on `i16` masks.
+            // Performs a bitwise operation on two 16-bit mask registers.
+            "kand.w" | "kandn.w" | "kor.w" | "kxor.w" | "kxnor.w" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let left = this.read_scalar(left)?.to_u16()?;
+                let right = this.read_scalar(right)?.to_u16()?;
+
+                let res = match unprefixed_name {
+                    "kand.w" => left & right,
+                    // Note that the *first* operand is negated.
+                    "kandn.w" => !left & right,
+                    "kor.w" => left | right,
+                    "kxor.w" => left ^ right,
+                    "kxnor.w" => !(left ^ right),
+                    _ => unreachable!(),
+                };
+
+                this.write_scalar(Scalar::from_u16(res), dest)?;
+            }
+            // Used to implement the _knot_mask16 function.
+            // Negates a 16-bit mask register.
+            "knot.w" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let op = this.read_scalar(op)?.to_u16()?;
+
+                this.write_scalar(Scalar::from_u16(!op), dest)?;
+            }
+            // Used to implement the _mm512_kunpackb, _mm512_kunpackw and _mm512_kunpackd
+            // functions.
+            // Concatenates the lower halves of two mask registers, with the lower half
+            // of `left` ending up in the upper half of the result.
+            // Only `kunpck.bw` is part of AVX512F, the wider ones need AVX512BW.
+            "kunpck.bw" | "kunpck.wd" | "kunpck.dq" => {
+                if unprefixed_name != "kunpck.bw" {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512bw")?;
+                }
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let size = dest.layout.size;
+                let half = Size::from_bytes(size.bytes().checked_div(2).unwrap());
+                let left = half.truncate(this.read_scalar(left)?.to_uint(size)?);
+                let right = half.truncate(this.read_scalar(right)?.to_uint(size)?);
+
+                let res = left.checked_shl(half.bits().try_into().unwrap()).unwrap() | right;
+
+                this.write_scalar(Scalar::from_uint(res, size), dest)?;
+            }
+            _ => return Ok(EmulateForeignItemResult::NotSupported),
+        }
+        Ok(EmulateForeignItemResult::NeedsJumping)
+    }
+}
+
+/// Returns the arithmetic operation named by an intrinsic like `add.ps.512`
+/// or `mask.mul.sd.round`.
+fn arith_op_from_name(name: &str) -> mir::BinOp {
+    let name = name.strip_prefix("mask.").unwrap_or(name);
+    match name.split('.').next().unwrap() {
+        "add" => mir::BinOp::Add,
+        "sub" => mir::BinOp::Sub,
+        "mul" => mir::BinOp::Mul,
+        "div" => mir::BinOp::Div,
+        _ => unreachable!(),
+    }
+}
+
+/// Performs the arithmetic operation `which` on `left` and `right`, rounding
+/// according to `rnd`.
+fn float_arith_round<'tcx, F: rustc_apfloat::Float + rustc_apfloat::FloatConvert<F>>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    which: mir::BinOp,
+    left: &ImmTy<'tcx, Provenance>,
+    right: &ImmTy<'tcx, Provenance>,
+    rnd: rustc_apfloat::Round,
+) -> InterpResult<'tcx, Scalar<Provenance>> {
+    let left = left.to_scalar().to_float::<F>()?;
+    let right = right.to_scalar().to_float::<F>()?;
+    let res = match which {
+        mir::BinOp::Add => left.add_r(right, rnd),
+        mir::BinOp::Sub => left.sub_r(right, rnd),
+        mir::BinOp::Mul => left.mul_r(right, rnd),
+        mir::BinOp::Div => left.div_r(right, rnd),
+        _ => unreachable!(),
+    }
+    .value;
+    let res = this.adjust_nan(res, &[left, right]);
+    Ok(Scalar::from_uint(res.to_bits(), Size::from_bits(F::BITS)))
+}
+
+/// Performs the arithmetic operation `which` on each component of `left`
+/// and `right`, rounding according to `rnd`, and stores the result in `dest`.
+fn bin_op_simd_float_round<'tcx, F: rustc_apfloat::Float + rustc_apfloat::FloatConvert<F>>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: mir::BinOp,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    rnd: rustc_apfloat::Round,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let left = this.read_immediate(&this.project_index(&left, i)?)?;
+        let right = this.read_immediate(&this.project_index(&right, i)?)?;
+        let dest = this.project_index(&dest, i)?;
+
+        let res = float_arith_round::<F>(this, which, &left, &right, rnd)?;
+        this.write_scalar(res, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Performs the arithmetic operation `which` on the first component of `left`
+/// and `right`, rounding according to `rnd`, if the lowest bit of the k-register
+/// `mask` is set. Otherwise, the first component of `src` is used. The remaining
+/// components are copied from `left`. The result is stored in `dest`.
+#[allow(clippy::too_many_arguments)]
+fn bin_op_float_first_masked<'tcx, F: rustc_apfloat::Float + rustc_apfloat::FloatConvert<F>>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: mir::BinOp,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    src: &OpTy<'tcx, Provenance>,
+    mask: u8,
+    rnd: rustc_apfloat::Round,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (src, src_len) = this.operand_to_simd(src)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+    assert_eq!(dest_len, src_len);
+
+    let dest0 = this.project_index(&dest, 0)?;
+    if mask & 1 != 0 {
+        let left0 = this.read_immediate(&this.project_index(&left, 0)?)?;
+        let right0 = this.read_immediate(&this.project_index(&right, 0)?)?;
+        let res0 = float_arith_round::<F>(this, which, &left0, &right0, rnd)?;
+        this.write_scalar(res0, &dest0)?;
+    } else {
+        this.copy_op(&this.project_index(&src, 0)?, &dest0)?;
+    }
+
+    for i in 1..dest_len {
+        this.copy_op(&this.project_index(&left, i)?, &this.project_index(&dest, i)?)?;
+    }
+
+    Ok(())
+}
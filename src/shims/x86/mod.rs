@@ -1,7 +1,8 @@
-use rand::Rng as _;
+use rand::rngs::StdRng;
+use rand::{Rng as _, SeedableRng as _};
 
 use rustc_apfloat::{ieee::Single, Float};
-use rustc_middle::ty::layout::LayoutOf as _;
+use rustc_middle::ty::layout::{LayoutOf as _, TyAndLayout};
 use rustc_middle::ty::Ty;
 use rustc_middle::{mir, ty};
 use rustc_span::Symbol;
@@ -15,16 +16,58 @@ use shims::foreign_items::EmulateForeignItemResult;
 mod aesni;
 mod avx;
 mod avx2;
+mod avx512;
 mod sse;
 mod sse2;
 mod sse3;
 mod sse41;
+mod sse42;
 mod ssse3;
 
+/// The `emulate_x86_*_intrinsic` function of a submodule.
+type EmulateIntrinsicFn<'mir, 'tcx> = fn(
+    &mut MiriInterpCx<'mir, 'tcx>,
+    Symbol,
+    Abi,
+    &[OpTy<'tcx, Provenance>],
+    &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, EmulateForeignItemResult>;
+
+/// For each prefix (after `llvm.x86.`), the target feature that is required by all intrinsics
+/// with that prefix, and the function of the submodule for that target feature that emulates them.
+fn intrinsics_by_prefix<'mir, 'tcx: 'mir>(
+) -> [(&'static str, &'static str, EmulateIntrinsicFn<'mir, 'tcx>); 10] {
+    [
+        ("sse.", "sse", sse::EvalContextExt::emulate_x86_sse_intrinsic),
+        ("sse2.", "sse2", sse2::EvalContextExt::emulate_x86_sse2_intrinsic),
+        ("sse3.", "sse3", sse3::EvalContextExt::emulate_x86_sse3_intrinsic),
+        ("ssse3.", "ssse3", ssse3::EvalContextExt::emulate_x86_ssse3_intrinsic),
+        ("sse41.", "sse4.1", sse41::EvalContextExt::emulate_x86_sse41_intrinsic),
+        ("sse42.", "sse4.2", sse42::EvalContextExt::emulate_x86_sse42_intrinsic),
+        ("aesni.", "aes", aesni::EvalContextExt::emulate_x86_aesni_intrinsic),
+        ("avx.", "avx", avx::EvalContextExt::emulate_x86_avx_intrinsic),
+        ("avx2.", "avx2", avx2::EvalContextExt::emulate_x86_avx2_intrinsic),
+        ("avx512.", "avx512f", avx512::EvalContextExt::emulate_x86_avx512_intrinsic),
+    ]
+}
+
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
 pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
     crate::MiriInterpCxExt<'mir, 'tcx>
 {
+    /// Emulates an `llvm.x86.*` intrinsic.
+    ///
+    /// This is the single routing point for all x86 intrinsics: a handful of
+    /// feature-independent intrinsics are handled directly here, while everything
+    /// else is routed by its prefix (`sse.`, `avx2.`, ...) to the `emulate_x86_*_intrinsic`
+    /// function of the submodule for that target feature, after checking that the target
+    /// feature is enabled (see `intrinsics_by_prefix`). Each of those functions
+    /// has its own `match` on the unprefixed name and returns
+    /// `EmulateForeignItemResult::NotSupported` for names it does not know,
+    /// which is reported back to the caller unchanged (the caller records it for
+    /// `-Zmiri-report-unsupported-intrinsics`). New intrinsic families
+    /// should get their own submodule and `intrinsics_by_prefix` entry
+    /// instead of growing an existing `match`.
     fn emulate_x86_intrinsic(
         &mut self,
         link_name: Symbol,
@@ -102,48 +145,18 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                 }
             }
 
-            name if name.starts_with("sse.") => {
-                return sse::EvalContextExt::emulate_x86_sse_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
-            }
-            name if name.starts_with("sse2.") => {
-                return sse2::EvalContextExt::emulate_x86_sse2_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
-            }
-            name if name.starts_with("sse3.") => {
-                return sse3::EvalContextExt::emulate_x86_sse3_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
-            }
-            name if name.starts_with("ssse3.") => {
-                return ssse3::EvalContextExt::emulate_x86_ssse3_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
-            }
-            name if name.starts_with("sse41.") => {
-                return sse41::EvalContextExt::emulate_x86_sse41_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
-            }
-            name if name.starts_with("aesni.") => {
-                return aesni::EvalContextExt::emulate_x86_aesni_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
-            }
-            name if name.starts_with("avx.") => {
-                return avx::EvalContextExt::emulate_x86_avx_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
-            }
-            name if name.starts_with("avx2.") => {
-                return avx2::EvalContextExt::emulate_x86_avx2_intrinsic(
-                    this, link_name, abi, args, dest,
-                );
+            // Feature-specific intrinsics, routed by prefix once we checked that the target
+            // feature of that prefix is enabled.
+            name => {
+                let Some((_, target_feature, emulate)) = intrinsics_by_prefix()
+                    .into_iter()
+                    .find(|&(prefix, _, _)| name.starts_with(prefix))
+                else {
+                    return Ok(EmulateForeignItemResult::NotSupported);
+                };
+                this.expect_target_feature_for_intrinsic(link_name, target_feature)?;
+                return emulate(this, link_name, abi, args, dest);
             }
-
-            _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
         Ok(EmulateForeignItemResult::NeedsJumping)
     }
@@ -411,13 +424,25 @@ fn unary_op_f32<'tcx>(
 }
 
 /// Disturbes a floating-point result by a relative error on the order of (-2^scale, 2^scale).
+/// With `-Zmiri-deterministic-float-approximations`, the result is returned unchanged.
+/// With `-Zmiri-per-thread-float-rng`, the error is drawn from the RNG of the active thread.
 #[allow(clippy::arithmetic_side_effects)] // floating point arithmetic cannot panic
 fn apply_random_float_error<F: rustc_apfloat::Float>(
     this: &mut crate::MiriInterpCx<'_, '_>,
     val: F,
     err_scale: i32,
 ) -> F {
-    let rng = this.machine.rng.get_mut();
+    if this.machine.deterministic_float_approximations {
+        return val;
+    }
+    let thread = this.get_active_thread();
+    let seed = this.machine.seed;
+    let rng = match &mut this.machine.thread_float_rngs {
+        Some(rngs) =>
+            rngs.entry(thread)
+                .or_insert_with(|| StdRng::seed_from_u64(seed ^ u64::from(thread.to_u32()))),
+        None => this.machine.rng.get_mut(),
+    };
     // generates rand(0, 2^64) * 2^(scale - 64) = rand(0, 1) * 2^scale
     let err =
         F::from_u128(rng.gen::<u64>().into()).value.scalbn(err_scale.checked_sub(64).unwrap());
@@ -595,6 +620,154 @@ fn shift_simd_by_simd<'tcx>(
     Ok(())
 }
 
+/// Shifts each 128-bit chunk of `op` as a whole by `count` bytes, filling with
+/// zeros. `left` shifts towards the most significant byte. When `count` is larger
+/// than 15, zero is produced.
+///
+/// <https://www.felixcloutier.com/x86/pslldq>
+/// <https://www.felixcloutier.com/x86/psrldq>
+fn shift_simd_by_bytes<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    count: u64,
+    left: bool,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(dest.layout.size, op.layout.size);
+
+    // Transmute arguments to arrays of `u128`.
+    assert_eq!(dest.layout.size.bytes() % 16, 0);
+    let len = dest.layout.size.bytes() / 16;
+
+    let u128_array_layout =
+        this.layout_of(Ty::new_array(this.tcx.tcx, this.tcx.types.u128, len))?;
+
+    let op = op.transmute(u128_array_layout, this)?;
+    let dest = dest.transmute(u128_array_layout, this)?;
+
+    // It is ok to saturate the value to u32::MAX because any value
+    // above 127 will produce the same result.
+    let shift =
+        count.checked_mul(8).and_then(|shift| u32::try_from(shift).ok()).unwrap_or(u32::MAX);
+
+    for i in 0..len {
+        let op = this.read_scalar(&this.project_index(&op, i)?)?.to_u128()?;
+        let dest = this.project_index(&dest, i)?;
+
+        // x86 is little-endian, so the least significant byte is the first one.
+        let res = if left {
+            op.checked_shl(shift).unwrap_or(0)
+        } else {
+            op.checked_shr(shift).unwrap_or(0)
+        };
+
+        this.write_scalar(Scalar::from_u128(res), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Concatenates each 128-bit chunk of `left` (as the high half) with the
+/// corresponding chunk of `right` (as the low half), shifts the 256-bit result
+/// right by `count` bytes and stores the low 128 bits in the corresponding chunk
+/// of `dest`. Counts of 16 or more shift in zeros from the high side, and counts
+/// of 32 or more produce zero.
+///
+/// <https://www.felixcloutier.com/x86/palignr>
+fn palignr<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    count: u8,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(dest.layout.size, left.layout.size);
+    assert_eq!(dest.layout.size, right.layout.size);
+
+    // Transmute arguments to arrays of `u128`.
+    assert_eq!(dest.layout.size.bytes() % 16, 0);
+    let len = dest.layout.size.bytes() / 16;
+
+    let u128_array_layout =
+        this.layout_of(Ty::new_array(this.tcx.tcx, this.tcx.types.u128, len))?;
+
+    let left = left.transmute(u128_array_layout, this)?;
+    let right = right.transmute(u128_array_layout, this)?;
+    let dest = dest.transmute(u128_array_layout, this)?;
+
+    let shift = u32::from(count).checked_mul(8).unwrap();
+
+    for i in 0..len {
+        let left = this.read_scalar(&this.project_index(&left, i)?)?.to_u128()?;
+        let right = this.read_scalar(&this.project_index(&right, i)?)?.to_u128()?;
+        let dest = this.project_index(&dest, i)?;
+
+        let low = right.checked_shr(shift).unwrap_or(0);
+        let high = match 128u32.checked_sub(shift) {
+            // The upper bytes come from `left`.
+            Some(shift) => left.checked_shl(shift).unwrap_or(0),
+            // Only `left` remains.
+            None => left.checked_shr(shift.checked_sub(128).unwrap()).unwrap_or(0),
+        };
+
+        this.write_scalar(Scalar::from_u128(high | low), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Stores `value` to the memory pointed to by `ptr`, which must be aligned to
+/// the size of `value`. Used to implement non-temporal stores, whose cache
+/// hint has no observable effect in the abstract machine.
+fn nontemporal_store<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    ptr: &OpTy<'tcx, Provenance>,
+    value: &OpTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let ptr = this.read_pointer(ptr)?;
+    // The vector layout has the required alignment, so the write below
+    // checks both alignment and bounds.
+    let place = this.ptr_to_mplace(ptr, value.layout);
+    this.copy_op(value, &place)
+}
+
+/// Compares each signed 64-bit element of `left` with the corresponding
+/// element of `right`, setting the element of `dest` to all ones if `left`
+/// is greater and to all zeros otherwise.
+///
+/// <https://www.felixcloutier.com/x86/pcmpgtq>
+fn pcmpgtq<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(dest.layout.size, left.layout.size);
+    assert_eq!(dest.layout.size, right.layout.size);
+
+    // Transmute arguments to arrays of `i64`, since the vector types used by
+    // the callers do not necessarily have 64-bit elements.
+    assert_eq!(dest.layout.size.bytes() % 8, 0);
+    let len = dest.layout.size.bytes() / 8;
+
+    let i64_array_layout = this.layout_of(Ty::new_array(this.tcx.tcx, this.tcx.types.i64, len))?;
+
+    let left = left.transmute(i64_array_layout, this)?;
+    let right = right.transmute(i64_array_layout, this)?;
+    let dest = dest.transmute(i64_array_layout, this)?;
+
+    for i in 0..len {
+        let left = this.read_scalar(&this.project_index(&left, i)?)?.to_i64()?;
+        let right = this.read_scalar(&this.project_index(&right, i)?)?.to_i64()?;
+        let dest = this.project_index(&dest, i)?;
+
+        let res = if left > right { -1 } else { 0 };
+        this.write_scalar(Scalar::from_i64(res), &dest)?;
+    }
+
+    Ok(())
+}
+
 /// Takes a 128-bit vector, transmutes it to `[u64; 2]` and extracts
 /// the first value.
 fn extract_first_u64<'tcx>(
@@ -688,9 +861,34 @@ fn rounding_from_imm<'tcx>(rounding: i32) -> InterpResult<'tcx, rustc_apfloat::R
     }
 }
 
-/// Converts each element of `op` from floating point to signed integer.
+/// Converts `op` from floating point to the integer type of `dest_layout`,
+/// rounding according to `rnd`.
+///
+/// When the input value is NaN or out of range, fall back to the "integer
+/// indefinite" value, which is the minimum value for signed integers and the
+/// maximum value for unsigned integers.
+fn float_to_int_or_indefinite<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &ImmTy<'tcx, Provenance>,
+    dest_layout: TyAndLayout<'tcx>,
+    rnd: rustc_apfloat::Round,
+) -> InterpResult<'tcx, ImmTy<'tcx, Provenance>> {
+    let res = this.float_to_int_checked(op, dest_layout, rnd)?.unwrap_or_else(|| {
+        match dest_layout.ty.kind() {
+            // Fallback to minimum according to SSE/AVX semantics.
+            ty::Int(_) => ImmTy::from_int(dest_layout.size.signed_int_min(), dest_layout),
+            // Fallback to maximum according to AVX512 semantics.
+            ty::Uint(_) => ImmTy::from_uint(dest_layout.size.unsigned_int_max(), dest_layout),
+            _ => bug!("float_to_int_or_indefinite: invalid destination type"),
+        }
+    });
+    Ok(res)
+}
+
+/// Converts each element of `op` from floating point to integer.
 ///
-/// When the input value is NaN or out of range, fall back to minimum value.
+/// When the input value is NaN or out of range, fall back to minimum value
+/// for signed integers or to maximum value for unsigned integers.
 ///
 /// If `op` has more elements than `dest`, extra elements are ignored. If `op`
 /// has less elements than `dest`, the rest is filled with zeros.
@@ -703,17 +901,11 @@ fn convert_float_to_int<'tcx>(
     let (op, op_len) = this.operand_to_simd(op)?;
     let (dest, dest_len) = this.mplace_to_simd(dest)?;
 
-    // Output must be *signed* integers.
-    assert!(matches!(dest.layout.field(this, 0).ty.kind(), ty::Int(_)));
-
     for i in 0..op_len.min(dest_len) {
         let op = this.read_immediate(&this.project_index(&op, i)?)?;
         let dest = this.project_index(&dest, i)?;
 
-        let res = this.float_to_int_checked(&op, dest.layout, rnd)?.unwrap_or_else(|| {
-            // Fallback to minimum according to SSE/AVX semantics.
-            ImmTy::from_int(dest.layout.size.signed_int_min(), dest.layout)
-        });
+        let res = float_to_int_or_indefinite(this, &op, dest.layout, rnd)?;
         this.write_immediate(*res, &dest)?;
     }
     // Fill remainder with zeros
@@ -725,6 +917,76 @@ fn convert_float_to_int<'tcx>(
     Ok(())
 }
 
+/// Converts each element of `op` from floating point to another floating
+/// point type (f32 to f64 or f64 to f32), rounding to nearest.
+///
+/// If `op` has more elements than `dest`, extra elements are ignored. If `op`
+/// has less elements than `dest`, the rest is filled with zeros.
+fn convert_float_to_float<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    for i in 0..op_len.min(dest_len) {
+        let op = this.read_immediate(&this.project_index(&op, i)?)?;
+        let dest = this.project_index(&dest, i)?;
+
+        // `float_to_float_or_int` rounds to nearest when narrowing, which matches
+        // the default SSE/AVX rounding mode.
+        let res = this.float_to_float_or_int(&op, dest.layout)?;
+        this.write_immediate(*res, &dest)?;
+    }
+    // Fill remainder with zeros
+    for i in op_len..dest_len {
+        let dest = this.project_index(&dest, i)?;
+        this.write_scalar(Scalar::from_uint(0u32, dest.layout.size), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Converts the integer `right` to floating point, rounding according to `rnd`,
+/// and stores the result in the first element of `dest`. The remaining elements
+/// are copied from `left`.
+///
+/// `right` is interpreted as signed or unsigned according to `signed`, regardless
+/// of the type it is declared with. For unsigned conversions, this means that values
+/// above the signed range produce positive results.
+fn convert_int_to_float_first<'tcx, F: rustc_apfloat::Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    signed: bool,
+    rnd: rustc_apfloat::Round,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+
+    let right = this.read_scalar(right)?;
+    let size = right.size();
+    let res0: F = if signed {
+        F::from_i128_r(right.to_int(size)?, rnd).value
+    } else {
+        F::from_u128_r(right.to_uint(size)?, rnd).value
+    };
+    this.write_scalar(
+        Scalar::from_uint(res0.to_bits(), Size::from_bits(F::BITS)),
+        &this.project_index(&dest, 0)?,
+    )?;
+
+    for i in 1..dest_len {
+        this.copy_op(&this.project_index(&left, i)?, &this.project_index(&dest, i)?)?;
+    }
+
+    Ok(())
+}
+
 /// Calculates absolute value of integers in `op` and stores the result in `dest`.
 ///
 /// In case of overflow (when the operand is the minimum value), the operation
@@ -758,6 +1020,50 @@ fn int_abs<'tcx>(
     Ok(())
 }
 
+/// Computes the minimum (or maximum, if `max` is true) of each element of
+/// `left` and the corresponding element of `right`, and stores it in `dest`.
+///
+/// The elements are compared as `elem_ty`, which determines both the element
+/// size and the signedness, since the vector types used by the callers do not
+/// necessarily match the instruction (e.g., `__m128i` is a vector of `i64`).
+///
+/// <https://www.felixcloutier.com/x86/pminsb:pminsw>
+/// <https://www.felixcloutier.com/x86/pminub:pminuw>
+/// <https://www.felixcloutier.com/x86/pmaxsb:pmaxsw:pmaxsd:pmaxsq>
+/// <https://www.felixcloutier.com/x86/pmaxub:pmaxuw>
+fn int_min_max<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    max: bool,
+    elem_ty: Ty<'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(dest.layout.size, left.layout.size);
+    assert_eq!(dest.layout.size, right.layout.size);
+
+    let elem_size = this.layout_of(elem_ty)?.size;
+    let len = dest.layout.size.bytes().checked_div(elem_size.bytes()).unwrap();
+    let array_layout = this.layout_of(Ty::new_array(this.tcx.tcx, elem_ty, len))?;
+
+    let left = left.transmute(array_layout, this)?;
+    let right = right.transmute(array_layout, this)?;
+    let dest = dest.transmute(array_layout, this)?;
+
+    for i in 0..len {
+        let left = this.read_immediate(&this.project_index(&left, i)?)?;
+        let right = this.read_immediate(&this.project_index(&right, i)?)?;
+        let dest = this.project_index(&dest, i)?;
+
+        let lt = this.wrapping_binary_op(mir::BinOp::Lt, &left, &right)?.to_scalar().to_bool()?;
+        let res = if lt != max { left } else { right };
+
+        this.write_immediate(*res, &dest)?;
+    }
+
+    Ok(())
+}
+
 /// Splits `op` (which must be a SIMD vector) into 128-bit chuncks.
 ///
 /// Returns a tuple where:
@@ -843,6 +1149,127 @@ fn horizontal_bin_op<'tcx>(
     Ok(())
 }
 
+/// Subtracts the even-indexed elements of `right` from the corresponding
+/// elements of `left` and adds the odd-indexed ones, which is the building
+/// block of complex multiplication.
+///
+/// <https://www.felixcloutier.com/x86/addsubps>
+/// <https://www.felixcloutier.com/x86/addsubpd>
+fn addsub<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let left = this.read_immediate(&this.project_index(&left, i)?)?;
+        let right = this.read_immediate(&this.project_index(&right, i)?)?;
+        let dest = this.project_index(&dest, i)?;
+
+        let which = if i % 2 == 0 { mir::BinOp::Sub } else { mir::BinOp::Add };
+        let res = this.wrapping_binary_op(which, &left, &right)?;
+        this.write_immediate(*res, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Shuffles the floating-point elements of `left` and `right` according
+/// to `imm`, as done by `shufps` and `shufpd`. Each 128-bit chunk is
+/// shuffled independently: the lower half of each chunk of `dest` is
+/// selected from the corresponding chunk of `left` and the upper half
+/// from the corresponding chunk of `right`.
+///
+/// `shufps` uses 2 bits of `imm` per element, the same for every chunk.
+/// `shufpd` uses 1 bit per element, consuming the next bits for each chunk.
+///
+/// <https://www.felixcloutier.com/x86/shufps>
+/// <https://www.felixcloutier.com/x86/shufpd>
+fn shuffle_float<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    let imm = this.read_scalar(imm)?.to_uint(imm.layout.size)?;
+
+    // Number of elements per 128-bit chunk (4 for `f32`, 2 for `f64`) and
+    // number of bits of `imm` used to select each of them.
+    let chunk_len = 128u64.checked_div(dest.layout.field(this, 0).size.bits()).unwrap();
+    let sel_bits = chunk_len.ilog2();
+    let sel_mask = chunk_len.checked_sub(1).unwrap();
+
+    for i in 0..dest_len {
+        let chunk_base = i & !sel_mask;
+        let src = if i & sel_mask < chunk_len / 2 { &left } else { &right };
+        // For `shufps`, this wraps around after the 4 elements of a chunk.
+        let shift = i.checked_mul(sel_bits.into()).unwrap() % 8;
+        let sel = u64::try_from((imm >> shift) & u128::from(sel_mask)).unwrap();
+        let src_i = chunk_base.checked_add(sel).unwrap();
+
+        this.copy_op(&this.project_index(src, src_i)?, &this.project_index(&dest, i)?)?;
+    }
+
+    Ok(())
+}
+
+/// Shuffles the 32-bit elements of `op` according to `imm`, as done by
+/// `pshufd`. Each 128-bit chunk is shuffled independently, with 2 bits of
+/// `imm` selecting each element, the same for every chunk.
+///
+/// The element size is fixed rather than given by the vector types, since
+/// this is usually called with `__m128i` or `__m256i`, which are vectors of `i64`.
+///
+/// <https://www.felixcloutier.com/x86/pshufd>
+fn shuffle_epi32<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(op.layout.size, dest.layout.size);
+
+    // Transmute `op` and `dest` to `[[u32; 4]; num_chunks]`.
+    assert_eq!(dest.layout.size.bytes() % 16, 0);
+    let num_chunks = dest.layout.size.bytes() / 16;
+    let chunked_layout = this.layout_of(Ty::new_array(
+        this.tcx.tcx,
+        Ty::new_array(this.tcx.tcx, this.tcx.types.u32, 4),
+        num_chunks,
+    ))?;
+    let op = op.transmute(chunked_layout, this)?;
+    let dest = dest.transmute(chunked_layout, this)?;
+
+    let imm = this.read_scalar(imm)?.to_uint(imm.layout.size)?;
+
+    for i in 0..num_chunks {
+        let op = this.project_index(&op, i)?;
+        let dest = this.project_index(&dest, i)?;
+
+        for j in 0..4 {
+            let sel = u64::try_from((imm >> j.checked_mul(2).unwrap()) & 0b11).unwrap();
+            this.copy_op(&this.project_index(&op, sel)?, &this.project_index(&dest, j)?)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Conditionally multiplies the packed floating-point elements in
 /// `left` and `right` using the high 4 bits in `imm`, sums the calculated
 /// products (up to 4), and conditionally stores the sum in `dest` using
@@ -1033,6 +1460,223 @@ fn mask_store<'tcx>(
     Ok(())
 }
 
+/// Returns the layout of an array of `elem_ty` elements with the given total `size`, and its
+/// length. This is used to reinterpret vectors whose declared element type does not match the
+/// instruction (e.g., `__m128i` is a vector of `i64`).
+fn array_layout_of<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    elem_ty: Ty<'tcx>,
+    size: Size,
+) -> InterpResult<'tcx, (TyAndLayout<'tcx>, u64)> {
+    let elem_size = this.layout_of(elem_ty)?.size;
+    let len = size.bytes().checked_div(elem_size.bytes()).unwrap();
+    Ok((this.layout_of(Ty::new_array(this.tcx.tcx, elem_ty, len))?, len))
+}
+
+/// Selects each element of `dest` from `right` if the high bit of the
+/// corresponding element of `mask` is set, and from `left` otherwise.
+///
+/// All operands are treated as vectors of `elem_ty`, which must be an unsigned
+/// integer type of the element size of the instruction.
+fn blendv<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    elem_ty: Ty<'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    mask: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(dest.layout.size, left.layout.size);
+    assert_eq!(dest.layout.size, right.layout.size);
+    assert_eq!(dest.layout.size, mask.layout.size);
+
+    let (array_layout, len) = array_layout_of(this, elem_ty, dest.layout.size)?;
+    let left = left.transmute(array_layout, this)?;
+    let right = right.transmute(array_layout, this)?;
+    let mask = mask.transmute(array_layout, this)?;
+    let dest = dest.transmute(array_layout, this)?;
+
+    let elem_size = array_layout.field(this, 0).size;
+    let high_bit_offset = elem_size.bits().checked_sub(1).unwrap();
+
+    for i in 0..len {
+        let mask = this.project_index(&mask, i)?;
+        let src = if this.read_scalar(&mask)?.to_uint(elem_size)? >> high_bit_offset != 0 {
+            &right
+        } else {
+            &left
+        };
+        this.copy_op(&this.project_index(src, i)?, &this.project_index(&dest, i)?)?;
+    }
+
+    Ok(())
+}
+
+/// Selects each element of `dest` from `right` if the corresponding bit of
+/// `imm` is set, and from `left` otherwise. For vectors with more than 8
+/// elements, the 8 bits of `imm` are used again for every 8 elements.
+///
+/// All operands are treated as vectors of `elem_ty`, which must be an integer
+/// type of the element size of the instruction.
+fn blend_imm<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    elem_ty: Ty<'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(dest.layout.size, left.layout.size);
+    assert_eq!(dest.layout.size, right.layout.size);
+
+    let (array_layout, len) = array_layout_of(this, elem_ty, dest.layout.size)?;
+    let left = left.transmute(array_layout, this)?;
+    let right = right.transmute(array_layout, this)?;
+    let dest = dest.transmute(array_layout, this)?;
+
+    // Depending on the LLVM version, the immediate is declared as `i8` or `i32`,
+    // but only its lowest 8 bits are used.
+    let imm = this.read_scalar(imm)?.to_uint(imm.layout.size)?;
+
+    for i in 0..len {
+        let src = if imm & (1 << (i % 8)) != 0 { &right } else { &left };
+        this.copy_op(&this.project_index(src, i)?, &this.project_index(&dest, i)?)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the first element of `op` to every element of `dest`.
+///
+/// `op` and `dest` are treated as vectors of `elem_ty`, which must have the element size of the
+/// instruction.
+fn broadcast_first<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    elem_ty: Ty<'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (op_layout, _) = array_layout_of(this, elem_ty, op.layout.size)?;
+    let (dest_layout, dest_len) = array_layout_of(this, elem_ty, dest.layout.size)?;
+    let op = op.transmute(op_layout, this)?;
+    let dest = dest.transmute(dest_layout, this)?;
+
+    let first = this.read_immediate(&this.project_index(&op, 0)?)?;
+    for i in 0..dest_len {
+        this.write_immediate(*first, &this.project_index(&dest, i)?)?;
+    }
+
+    Ok(())
+}
+
+/// Copies `vec` to `dest` and replaces the element selected by `imm` with
+/// `value`, truncated to the element size. Like the hardware, this only uses
+/// the bits of `imm` that are needed to address an element, so out-of-range
+/// indices wrap around.
+///
+/// `vec` and `dest` are treated as vectors of `elem_ty`, which must be an
+/// unsigned integer type of the element size of the instruction.
+fn insert_element<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    elem_ty: Ty<'tcx>,
+    vec: &OpTy<'tcx, Provenance>,
+    value: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(dest.layout.size, vec.layout.size);
+
+    let (array_layout, len) = array_layout_of(this, elem_ty, dest.layout.size)?;
+    let vec = vec.transmute(array_layout, this)?;
+    let dest = dest.transmute(array_layout, this)?;
+
+    assert!(len.is_power_of_two());
+
+    let value = this.read_scalar(value)?.to_uint(value.layout.size)?;
+    let index = this.read_scalar(imm)?.to_uint(imm.layout.size)? % u128::from(len);
+    let index = u64::try_from(index).unwrap();
+
+    for i in 0..len {
+        let dest = this.project_index(&dest, i)?;
+        if i == index {
+            let value = value & dest.layout.size.unsigned_int_max();
+            this.write_scalar(Scalar::from_uint(value, dest.layout.size), &dest)?;
+        } else {
+            this.copy_op(&this.project_index(&vec, i)?, &dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the element of `vec` selected by `imm` and writes it to `dest`,
+/// zero-extended to the size of `dest`. Like the hardware, this only uses
+/// the bits of `imm` that are needed to address an element, so out-of-range
+/// indices wrap around.
+///
+/// `vec` is treated as a vector of `elem_ty`, which must be an unsigned
+/// integer type of the element size of the instruction.
+fn extract_element<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    elem_ty: Ty<'tcx>,
+    vec: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (array_layout, len) = array_layout_of(this, elem_ty, vec.layout.size)?;
+    let vec = vec.transmute(array_layout, this)?;
+
+    assert!(len.is_power_of_two());
+
+    let index = this.read_scalar(imm)?.to_uint(imm.layout.size)? % u128::from(len);
+    let element = this.project_index(&vec, u64::try_from(index).unwrap())?;
+    let value = this.read_scalar(&element)?.to_uint(element.layout.size)?;
+    this.write_scalar(Scalar::from_uint(value, dest.layout.size), dest)?;
+
+    Ok(())
+}
+
+/// Computes the absolute differences of packed unsigned 8-bit integers in
+/// `left` and `right`, then horizontally sums each consecutive 8 differences
+/// into a 16-bit result, which is zero-extended into the corresponding 64-bit
+/// element of `dest`.
+///
+/// <https://www.felixcloutier.com/x86/psadbw>
+fn psadbw<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    // left and right are u8xN, dest is u64x(N/8)
+    assert_eq!(left_len, right_len);
+    assert_eq!(left_len, dest_len.checked_mul(8).unwrap());
+
+    for i in 0..dest_len {
+        let dest = this.project_index(&dest, i)?;
+
+        // At most 8 * 255, so this fits into the low 16 bits.
+        let mut acc: u16 = 0;
+        for j in 0..8 {
+            let src_index = i.checked_mul(8).unwrap().checked_add(j).unwrap();
+
+            let left = this.read_scalar(&this.project_index(&left, src_index)?)?.to_u8()?;
+            let right = this.read_scalar(&this.project_index(&right, src_index)?)?.to_u8()?;
+
+            acc = acc.checked_add(left.abs_diff(right).into()).unwrap();
+        }
+
+        // The upper 48 bits of each element are zero.
+        this.write_scalar(Scalar::from_u64(acc.into()), &dest)?;
+    }
+
+    Ok(())
+}
+
 /// Compute the sum of absolute differences of quadruplets of unsigned
 /// 8-bit integers in `left` and `right`, and store the 16-bit results
 /// in `right`. Quadruplets are selected from `left` and `right` with
@@ -1043,7 +1687,8 @@ fn mask_store<'tcx>(
 ///
 /// Each 128-bit chunk is treated independently (i.e., the value for
 /// the is i-th 128-bit chunk of `dest` is calculated with the i-th
-/// 128-bit chunks of `left` and `right`).
+/// 128-bit chunks of `left` and `right` and the i-th group of 3 bits
+/// of `imm`).
 fn mpsadbw<'tcx>(
     this: &mut crate::MiriInterpCx<'_, 'tcx>,
     left: &OpTy<'tcx, Provenance>,
@@ -1061,14 +1706,18 @@ fn mpsadbw<'tcx>(
     assert_eq!(op_items_per_chunk, dest_items_per_chunk.checked_mul(2).unwrap());
 
     let imm = this.read_scalar(imm)?.to_uint(imm.layout.size)?;
-    // Bit 2 of `imm` specifies the offset for indices of `left`.
-    // The offset is 0 when the bit is 0 or 4 when the bit is 1.
-    let left_offset = u64::try_from((imm >> 2) & 1).unwrap().checked_mul(4).unwrap();
-    // Bits 0..=1 of `imm` specify the offset for indices of
-    // `right` in blocks of 4 elements.
-    let right_offset = u64::try_from(imm & 0b11).unwrap().checked_mul(4).unwrap();
 
     for i in 0..num_chunks {
+        // Each chunk uses its own 3 bits of `imm`: bits 0..=2 for the first
+        // chunk and bits 3..=5 for the second one.
+        let chunk_imm = imm.checked_shr(u32::try_from(i).unwrap().checked_mul(3).unwrap()).unwrap();
+        // Bit 2 of `chunk_imm` specifies the offset for indices of `left`.
+        // The offset is 0 when the bit is 0 or 4 when the bit is 1.
+        let left_offset = u64::try_from((chunk_imm >> 2) & 1).unwrap().checked_mul(4).unwrap();
+        // Bits 0..=1 of `chunk_imm` specify the offset for indices of
+        // `right` in blocks of 4 elements.
+        let right_offset = u64::try_from(chunk_imm & 0b11).unwrap().checked_mul(4).unwrap();
+
         let left = this.project_index(&left, i)?;
         let right = this.project_index(&right, i)?;
         let dest = this.project_index(&dest, i)?;
@@ -4,8 +4,9 @@ use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
 use super::{
-    bin_op_simd_float_all, bin_op_simd_float_first, unary_op_ps, unary_op_ss, FloatBinOp,
-    FloatUnaryOp,
+    bin_op_simd_float_all, bin_op_simd_float_first, convert_int_to_float_first,
+    float_to_int_or_indefinite, nontemporal_store, shuffle_float, unary_op_ps, unary_op_ss,
+    FloatBinOp, FloatUnaryOp,
 };
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
@@ -22,7 +23,6 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
         dest: &MPlaceTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, EmulateForeignItemResult> {
         let this = self.eval_context_mut();
-        this.expect_target_feature_for_intrinsic(link_name, "sse")?;
         // Prefix should have already been checked.
         let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.sse.").unwrap();
         // All these intrinsics operate on 128-bit (f32x4) SIMD vectors unless stated otherwise.
@@ -181,10 +181,7 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                     _ => unreachable!(),
                 };
 
-                let res = this.float_to_int_checked(&op, dest.layout, rnd)?.unwrap_or_else(|| {
-                    // Fallback to minimum according to SSE semantics.
-                    ImmTy::from_int(dest.layout.size.signed_int_min(), dest.layout)
-                });
+                let res = float_to_int_or_indefinite(this, &op, dest.layout, rnd)?;
 
                 this.write_immediate(*res, dest)?;
             }
@@ -197,19 +194,41 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                 let [left, right] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
 
-                let (left, left_len) = this.operand_to_simd(left)?;
-                let (dest, dest_len) = this.mplace_to_simd(dest)?;
-
-                assert_eq!(dest_len, left_len);
+                // "current SSE rounding mode", assume nearest
+                convert_int_to_float_first::<Single>(
+                    this,
+                    left,
+                    right,
+                    /* signed */ true,
+                    rustc_apfloat::Round::NearestTiesToEven,
+                    dest,
+                )?;
+            }
+            // Used to implement the _mm_sfence function.
+            // Orders all stores before the fence with respect to all stores after it,
+            // which makes it a release fence.
+            "sfence" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.atomic_fence(AtomicFenceOrd::Release)?;
+            }
+            // Used to implement the _mm_stream_ps function.
+            // Stores `value` to the 16-byte aligned `ptr`, hinting that the
+            // cache should be bypassed.
+            "movnt.ps" => {
+                let [ptr, value] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
 
-                let right = this.read_immediate(right)?;
-                let dest0 = this.project_index(&dest, 0)?;
-                let res0 = this.int_to_int_or_float(&right, dest0.layout)?;
-                this.write_immediate(*res0, &dest0)?;
+                nontemporal_store(this, ptr, value)?;
+            }
+            // Used to implement the _mm_shuffle_ps function (by older versions
+            // of LLVM, newer versions use a shuffle).
+            // Selects the two lower elements of `dest` from `left` and the two
+            // upper elements from `right`, each using 2 bits of `imm`.
+            "shuf.ps" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
 
-                for i in 1..dest_len {
-                    this.copy_op(&this.project_index(&left, i)?, &this.project_index(&dest, i)?)?;
-                }
+                shuffle_float(this, left, right, imm, dest)?;
             }
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
@@ -3,8 +3,10 @@ use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
 use super::{
-    bin_op_simd_float_all, bin_op_simd_float_first, convert_float_to_int, packssdw, packsswb,
-    packuswb, shift_simd_by_scalar, FloatBinOp, ShiftOp,
+    bin_op_simd_float_all, bin_op_simd_float_first, convert_float_to_float, convert_float_to_int,
+    extract_element, float_to_int_or_indefinite, insert_element, int_min_max, nontemporal_store,
+    packssdw, packsswb, packuswb, psadbw, shift_simd_by_bytes, shift_simd_by_scalar, shuffle_epi32,
+    shuffle_float, FloatBinOp, ShiftOp,
 };
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
@@ -21,7 +23,6 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
         dest: &MPlaceTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, EmulateForeignItemResult> {
         let this = self.eval_context_mut();
-        this.expect_target_feature_for_intrinsic(link_name, "sse2")?;
         // Prefix should have already been checked.
         let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.sse2.").unwrap();
 
@@ -84,31 +85,7 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                 let [left, right] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
 
-                let (left, left_len) = this.operand_to_simd(left)?;
-                let (right, right_len) = this.operand_to_simd(right)?;
-                let (dest, dest_len) = this.mplace_to_simd(dest)?;
-
-                // left and right are u8x16, dest is u64x2
-                assert_eq!(left_len, right_len);
-                assert_eq!(left_len, 16);
-                assert_eq!(dest_len, 2);
-
-                for i in 0..dest_len {
-                    let dest = this.project_index(&dest, i)?;
-
-                    let mut res: u16 = 0;
-                    let n = left_len.checked_div(dest_len).unwrap();
-                    for j in 0..n {
-                        let op_i = j.checked_add(i.checked_mul(n).unwrap()).unwrap();
-                        let left = this.read_scalar(&this.project_index(&left, op_i)?)?.to_u8()?;
-                        let right =
-                            this.read_scalar(&this.project_index(&right, op_i)?)?.to_u8()?;
-
-                        res = res.checked_add(left.abs_diff(right).into()).unwrap();
-                    }
-
-                    this.write_scalar(Scalar::from_u64(res.into()), &dest)?;
-                }
+                psadbw(this, left, right, dest)?;
             }
             // Used to implement the _mm_{sll,srl,sra}_epi{16,32,64} functions
             // (except _mm_sra_epi64, which is not available in SSE2).
@@ -132,6 +109,26 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 shift_simd_by_scalar(this, left, right, which, dest)?;
             }
+            // Whole-register byte shifts, as in _mm_slli_si128, _mm_bslli_si128,
+            // _mm_srli_si128 and _mm_bsrli_si128 (which `stdarch` now writes as shuffles).
+            // Shifts the whole 128-bit vector by the number of bytes in `count`, filling
+            // with zeros. For `psll.dq` and `psrl.dq`, the count is given in bits (and must
+            // be a multiple of 8), for the `.bs` variants it is given in bytes.
+            "psll.dq" | "psrl.dq" | "psll.dq.bs" | "psrl.dq.bs" => {
+                let [op, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = u64::from(this.read_scalar(count)?.to_u32()?);
+                let (count, left) = match unprefixed_name {
+                    "psll.dq" => (count / 8, true),
+                    "psrl.dq" => (count / 8, false),
+                    "psll.dq.bs" => (count, true),
+                    "psrl.dq.bs" => (count, false),
+                    _ => unreachable!(),
+                };
+
+                shift_simd_by_bytes(this, op, count, left, dest)?;
+            }
             // Used to implement the _mm_cvtps_epi32, _mm_cvttps_epi32, _mm_cvtpd_epi32
             // and _mm_cvttpd_epi32 functions.
             // Converts packed f32/f64 to packed i32.
@@ -169,6 +166,23 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 convert_float_to_int(this, op, rnd, dest)?;
             }
+            // Used to implement the _mm_cvtpd_ps function.
+            // Converts packed f64 to packed f32, with the upper two elements
+            // of the result set to zero. (_mm_cvtps_pd is lowered to a generic
+            // `fpext` instead of an intrinsic.)
+            // https://www.felixcloutier.com/x86/cvtpd2ps
+            "cvtpd2ps" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (op_len, _) = op.layout.ty.simd_size_and_type(*this.tcx);
+                let (dest_len, _) = dest.layout.ty.simd_size_and_type(*this.tcx);
+                // f64x2 to f32x4 conversion
+                // the last two values are filled with zeros
+                assert_eq!(op_len, 2);
+                assert_eq!(dest_len, 4);
+
+                convert_float_to_float(this, op, dest)?;
+            }
             // Used to implement the _mm_packs_epi16 function.
             // Converts two 16-bit integer vectors to a single 8-bit integer
             // vector with signed saturation.
@@ -354,10 +368,7 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                     _ => unreachable!(),
                 };
 
-                let res = this.float_to_int_checked(&op, dest.layout, rnd)?.unwrap_or_else(|| {
-                    // Fallback to minimum according to SSE semantics.
-                    ImmTy::from_int(dest.layout.size.signed_int_min(), dest.layout)
-                });
+                let res = float_to_int_or_indefinite(this, &op, dest.layout, rnd)?;
 
                 this.write_immediate(*res, dest)?;
             }
@@ -387,6 +398,80 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
                     this.copy_op(&this.project_index(&left, i)?, &this.project_index(&dest, i)?)?;
                 }
             }
+            // Used to implement the _mm_insert_epi16 function.
+            // Copies `vec` and replaces the element selected by `imm` with `value`.
+            "pinsrw" => {
+                let [vec, value, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = this.tcx.types.u16;
+                insert_element(this, elem_ty, vec, value, imm, dest)?;
+            }
+            // Used to implement the _mm_extract_epi16 function.
+            // Returns the element of `vec` selected by `imm`, zero-extended.
+            "pextrw" => {
+                let [vec, imm] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = this.tcx.types.u16;
+                extract_element(this, elem_ty, vec, imm, dest)?;
+            }
+            // Used to implement the _mm_mfence function.
+            // Orders all loads and stores before the fence with respect to all loads and
+            // stores after it, which makes it a sequentially consistent fence.
+            "mfence" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.atomic_fence(AtomicFenceOrd::SeqCst)?;
+            }
+            // Used to implement the _mm_lfence function.
+            // Orders all loads before the fence with respect to all loads after it,
+            // which makes it an acquire fence.
+            "lfence" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.atomic_fence(AtomicFenceOrd::Acquire)?;
+            }
+            // Used to implement the _mm_stream_si128 and _mm_stream_pd functions.
+            // Stores `value` to the 16-byte aligned `ptr`, hinting that the
+            // cache should be bypassed.
+            "movnt.dq" | "movnt.pd" => {
+                let [ptr, value] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                nontemporal_store(this, ptr, value)?;
+            }
+            // Used to implement the _mm_shuffle_pd function (by older versions
+            // of LLVM, newer versions use a shuffle).
+            // Selects the lower element of `dest` from `left` and the upper
+            // element from `right`, each using 1 bit of `imm`.
+            "shuf.pd" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                shuffle_float(this, left, right, imm, dest)?;
+            }
+            // Used to implement the _mm_shuffle_epi32 function (by older versions
+            // of LLVM, newer versions use a shuffle).
+            // Selects each 32-bit element of `dest` from `op` using 2 bits of `imm`.
+            "pshuf.d" => {
+                let [op, imm] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                shuffle_epi32(this, op, imm, dest)?;
+            }
+            // Used to implement the _mm_{min,max}_{epi16,epu8} functions (by older
+            // versions of LLVM, newer versions use generic integer min/max).
+            // Computes the minimum or maximum of each pair of elements.
+            "pmins.w" | "pmaxs.w" | "pminu.b" | "pmaxu.b" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let max = matches!(unprefixed_name, "pmaxs.w" | "pmaxu.b");
+                let elem_ty = match unprefixed_name {
+                    "pmins.w" | "pmaxs.w" => this.tcx.types.i16,
+                    "pminu.b" | "pmaxu.b" => this.tcx.types.u8,
+                    _ => unreachable!(),
+                };
+
+                int_min_max(this, max, elem_ty, left, right, dest)?;
+            }
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
         Ok(EmulateForeignItemResult::NeedsJumping)
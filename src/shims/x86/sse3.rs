@@ -2,7 +2,7 @@ use rustc_middle::mir;
 use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
-use super::horizontal_bin_op;
+use super::{addsub, horizontal_bin_op};
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
 
@@ -18,7 +18,6 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
         dest: &MPlaceTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, EmulateForeignItemResult> {
         let this = self.eval_context_mut();
-        this.expect_target_feature_for_intrinsic(link_name, "sse3")?;
         // Prefix should have already been checked.
         let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.sse3.").unwrap();
 
@@ -38,6 +37,15 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 horizontal_bin_op(this, which, /*saturating*/ false, left, right, dest)?;
             }
+            // Used to implement the _mm_addsub_ps and _mm_addsub_pd functions.
+            // Subtracts the even elements and adds the odd elements of `right`
+            // to/from `left`.
+            "addsub.ps" | "addsub.pd" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                addsub(this, left, right, dest)?;
+            }
             // Used to implement the _mm_lddqu_si128 function.
             // Reads a 128-bit vector from an unaligned pointer. This intrinsic
             // is expected to perform better than a regular unaligned read when
@@ -50,6 +58,17 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 this.mem_copy(src_ptr, dest.ptr(), dest.layout.size, /*nonoverlapping*/ true)?;
             }
+            // Used to implement the _mm_monitor and _mm_mwait functions.
+            // These instructions can only be executed in ring 0 on most systems
+            // (user-mode code gets a #UD fault), and waiting for a write to an
+            // address range cannot be modeled meaningfully, so we reject them
+            // with a dedicated message instead of the generic one.
+            "monitor" | "mwait" => {
+                throw_unsup_format!(
+                    "`{link_name}` is a privileged instruction that Miri cannot emulate; \
+                    consider removing it or using a spin loop with `_mm_pause` instead"
+                );
+            }
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
         Ok(EmulateForeignItemResult::NeedsJumping)
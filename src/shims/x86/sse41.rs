@@ -1,7 +1,10 @@
 use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
-use super::{conditional_dot_product, mpsadbw, packusdw, round_all, round_first, test_bits_masked};
+use super::{
+    blend_imm, blendv, conditional_dot_product, extract_element, int_min_max, mpsadbw, packusdw,
+    round_all, round_first, test_bits_masked,
+};
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
 
@@ -17,7 +20,6 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
         dest: &MPlaceTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, EmulateForeignItemResult> {
         let this = self.eval_context_mut();
-        this.expect_target_feature_for_intrinsic(link_name, "sse4.1")?;
         // Prefix should have already been checked.
         let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.sse41.").unwrap();
 
@@ -175,6 +177,81 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 this.write_scalar(Scalar::from_i32(res.into()), dest)?;
             }
+            // Used to implement the _mm_blendv_epi8, _mm_blendv_ps and _mm_blendv_pd functions.
+            // Selects each element from `right` if the high bit of the corresponding
+            // element of `mask` is set, and from `left` otherwise.
+            "pblendvb" | "blendvps" | "blendvpd" => {
+                let [left, right, mask] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = match unprefixed_name {
+                    "pblendvb" => this.tcx.types.u8,
+                    "blendvps" => this.tcx.types.u32,
+                    "blendvpd" => this.tcx.types.u64,
+                    _ => unreachable!(),
+                };
+
+                blendv(this, elem_ty, left, right, mask, dest)?;
+            }
+            // Used to implement the _mm_blend_epi16, _mm_blend_ps and _mm_blend_pd functions
+            // (by older versions of LLVM, newer versions use a shuffle).
+            // Selects each element from `right` if the corresponding bit of `imm` is set,
+            // and from `left` otherwise.
+            "pblendw" | "blendps" | "blendpd" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = match unprefixed_name {
+                    "pblendw" => this.tcx.types.u16,
+                    "blendps" => this.tcx.types.u32,
+                    "blendpd" => this.tcx.types.u64,
+                    _ => unreachable!(),
+                };
+
+                blend_imm(this, elem_ty, left, right, imm, dest)?;
+            }
+            // Used to implement the _mm_extract_epi{8,32,64} functions.
+            // Returns the element of `vec` selected by `imm`, zero-extended.
+            "pextrb" | "pextrd" | "pextrq" => {
+                let [vec, imm] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = match unprefixed_name {
+                    "pextrb" => this.tcx.types.u8,
+                    "pextrd" => this.tcx.types.u32,
+                    "pextrq" => this.tcx.types.u64,
+                    _ => unreachable!(),
+                };
+
+                extract_element(this, elem_ty, vec, imm, dest)?;
+            }
+            // Used to implement the _mm_extract_ps function.
+            // Returns the bit pattern of the float element of `vec` selected by
+            // `imm` as an integer.
+            "extractps" => {
+                let [vec, imm] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let elem_ty = this.tcx.types.u32;
+                extract_element(this, elem_ty, vec, imm, dest)?;
+            }
+            // Used to implement the _mm_{min,max}_{epi8,epi32,epu16,epu32} functions
+            // (by older versions of LLVM, newer versions use generic integer min/max).
+            // Computes the minimum or maximum of each pair of elements.
+            "pminsb" | "pmaxsb" | "pminsd" | "pmaxsd" | "pminuw" | "pmaxuw" | "pminud"
+            | "pmaxud" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let max = unprefixed_name.starts_with("pmax");
+                let elem_ty = match unprefixed_name {
+                    "pminsb" | "pmaxsb" => this.tcx.types.i8,
+                    "pminsd" | "pmaxsd" => this.tcx.types.i32,
+                    "pminuw" | "pmaxuw" => this.tcx.types.u16,
+                    "pminud" | "pmaxud" => this.tcx.types.u32,
+                    _ => unreachable!(),
+                };
+
+                int_min_max(this, max, elem_ty, left, right, dest)?;
+            }
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
         Ok(EmulateForeignItemResult::NeedsJumping)
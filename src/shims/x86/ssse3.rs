@@ -2,7 +2,7 @@ use rustc_middle::mir;
 use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
-use super::{horizontal_bin_op, int_abs, pmulhrsw, psign};
+use super::{horizontal_bin_op, int_abs, palignr, pmulhrsw, psign};
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
 
@@ -18,7 +18,6 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
         dest: &MPlaceTy<'tcx, Provenance>,
     ) -> InterpResult<'tcx, EmulateForeignItemResult> {
         let this = self.eval_context_mut();
-        this.expect_target_feature_for_intrinsic(link_name, "ssse3")?;
         // Prefix should have already been checked.
         let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.ssse3.").unwrap();
 
@@ -136,6 +135,18 @@ pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
 
                 psign(this, left, right, dest)?;
             }
+            // Used to implement the _mm_alignr_epi8 function before `stdarch` switched
+            // to `simd_shuffle` for it.
+            // Concatenates `left` and `right` (with `left` as the upper half), shifts
+            // the result right by `count` bytes and returns the lower 128 bits.
+            "palign.r.128" => {
+                let [left, right, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = this.read_scalar(count)?.to_u8()?;
+
+                palignr(this, left, right, count, dest)?;
+            }
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
         Ok(EmulateForeignItemResult::NeedsJumping)
@@ -0,0 +1,17 @@
+//@only-target-darwin: `pthread_threadid_np` only exists on macOS
+
+// In Miri, the main thread's `pthread_t` is null, so outside the main thread, a null thread
+// could mean both the current thread and the main thread.
+
+use std::thread;
+
+fn main() {
+    thread::spawn(|| {
+        let mut tid = 0;
+        unsafe {
+            libc::pthread_threadid_np(0, &mut tid); //~ ERROR: only supported on the main thread
+        }
+    })
+    .join()
+    .unwrap();
+}
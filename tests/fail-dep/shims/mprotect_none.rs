@@ -0,0 +1,16 @@
+//@ignore-target-windows: No libc on Windows
+
+fn main() {
+    unsafe {
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            4096,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        assert_eq!(libc::mprotect(ptr, 4096, libc::PROT_NONE), 0);
+        let _x = *(ptr as *mut u8); //~ ERROR: mapped without PROT_READ
+    }
+}
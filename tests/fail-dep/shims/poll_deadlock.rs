@@ -0,0 +1,11 @@
+//@ignore-target-windows: No libc poll on Windows
+
+fn main() {
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let [reader, _writer] = fds;
+
+    // Nobody else can write to the pipe, so this waits forever.
+    let mut pollfd = libc::pollfd { fd: reader, events: libc::POLLIN, revents: 0 };
+    unsafe { libc::poll(&mut pollfd, 1, -1) }; //~ ERROR: deadlock: the evaluated program deadlocked
+}
@@ -0,0 +1,14 @@
+//@ignore-target-windows: No libc on Windows
+
+use std::ptr::{null, null_mut};
+
+fn main() {
+    let path = b"/bin/true\0".as_ptr().cast::<libc::c_char>();
+    let argv = [path.cast_mut(), null_mut()];
+    let envp = [null_mut::<libc::c_char>()];
+    let mut pid = 0;
+    unsafe {
+        libc::posix_spawn(&mut pid, path, null(), null(), argv.as_ptr(), envp.as_ptr());
+        //~^ ERROR: process spawning is not supported
+    }
+}
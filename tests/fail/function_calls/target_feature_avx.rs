@@ -0,0 +1,12 @@
+//@only-target-x86_64: uses x86 target features
+//@compile-flags: -C target-feature=-avx
+
+use std::arch::x86_64::*;
+
+fn main() {
+    assert!(!is_x86_feature_detected!("avx"));
+    unsafe {
+        let zero: __m256 = std::mem::transmute([0f32; 8]);
+        _mm256_add_ps(zero, zero); //~ ERROR: calling a function that requires unavailable target features: avx
+    }
+}
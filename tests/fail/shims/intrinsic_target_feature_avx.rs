@@ -0,0 +1,36 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// We cannot use `cfg`-based tricks here since the output would be
+// different for non-x86 targets.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+// Explicitly disable AVX in case some target enables it by default
+//@compile-flags: -C target-feature=-avx
+
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+fn main() {
+    assert!(!is_x86_feature_detected!("avx"));
+
+    unsafe {
+        let zero: __m256 = std::mem::transmute([0f32; 8]);
+        // Fail, since AVX is not enabled
+        maxps256(zero, zero);
+        //~^ ERROR: Undefined Behavior: attempted to call intrinsic `llvm.x86.avx.max.ps.256` that requires missing target feature avx
+    }
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.avx.max.ps.256"]
+    fn maxps256(a: __m256, b: __m256) -> __m256;
+}
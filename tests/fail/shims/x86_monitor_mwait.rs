@@ -0,0 +1,27 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// We cannot use `cfg`-based tricks here since the output would be
+// different for non-x86 targets.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse3
+
+#![feature(link_llvm_intrinsics)]
+
+fn main() {
+    let x = 0u32;
+    unsafe {
+        monitor(std::ptr::addr_of!(x).cast(), 0, 0);
+        //~^ ERROR: `llvm.x86.sse3.monitor` is a privileged instruction that Miri cannot emulate
+    }
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse3.monitor"]
+    fn monitor(ptr: *const u8, extensions: u32, hints: u32);
+}
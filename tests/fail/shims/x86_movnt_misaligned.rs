@@ -0,0 +1,35 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// We cannot use `cfg`-based tricks here since the output would be
+// different for non-x86 targets.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse2
+
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+#[repr(align(16))]
+struct Aligned([u8; 32]);
+
+fn main() {
+    let mut buf = Aligned([0; 32]);
+    unsafe {
+        movntdq(buf.0.as_mut_ptr().add(4), _mm_setzero_si128());
+        //~^ ERROR: accessing memory based on pointer with alignment 4, but alignment 16 is required
+    }
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse2.movnt.dq"]
+    fn movntdq(ptr: *mut u8, a: __m128i);
+}
@@ -0,0 +1,20 @@
+//@ignore-target-windows: No libc on Windows
+// Without preemption, the spin loop below only terminates if `sched_yield` lets the
+// other thread run.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+fn main() {
+    static FLAG: AtomicBool = AtomicBool::new(false);
+
+    let waiter = thread::spawn(|| {
+        while !FLAG.load(Ordering::Acquire) {
+            assert_eq!(unsafe { libc::sched_yield() }, 0);
+        }
+    });
+    let progress = thread::spawn(|| FLAG.store(true, Ordering::Release));
+    waiter.join().unwrap();
+    progress.join().unwrap();
+}
@@ -0,0 +1,33 @@
+//@ignore-target-windows: No libc dup on Windows
+//@compile-flags: -Zmiri-mute-stdout-stderr
+
+fn main() {
+    test_dup_muted();
+    test_dup2_muted();
+}
+
+fn write(fd: i32, bytes: &[u8]) {
+    let res = unsafe { libc::write(fd, bytes.as_ptr().cast(), bytes.len()) };
+    // Muted output still reports that everything was written.
+    assert_eq!(res, bytes.len() as isize);
+}
+
+fn test_dup_muted() {
+    for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        let dup = unsafe { libc::dup(fd) };
+        assert!(dup > 2);
+        write(dup, b"written to a duplicate\n");
+        assert_eq!(unsafe { libc::close(dup) }, 0);
+    }
+}
+
+fn test_dup2_muted() {
+    // Closing the original does not unmute the duplicate.
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    assert!(saved > 2);
+    assert_eq!(unsafe { libc::close(libc::STDOUT_FILENO) }, 0);
+    assert_eq!(unsafe { libc::dup2(saved, libc::STDOUT_FILENO) }, libc::STDOUT_FILENO);
+    write(libc::STDOUT_FILENO, b"written to a restored stdout\n");
+    println!("printed to a restored stdout");
+    assert_eq!(unsafe { libc::close(saved) }, 0);
+}
@@ -0,0 +1,239 @@
+//@ignore-target-windows: No libc dup on Windows
+
+use std::io::Error;
+
+fn main() {
+    test_dup();
+    test_dup2_same_fd();
+    test_dup2_replaces_open_fd();
+    test_dup2_redirect_stdout();
+    test_dup2_bad_fd();
+    test_dup2_negative_fd();
+    #[cfg(target_os = "linux")]
+    test_dup3();
+    test_cloexec();
+    #[cfg(target_os = "linux")]
+    test_close_range();
+    // This closes stdin, stdout and stderr, so it has to come last.
+    #[cfg(target_os = "linux")]
+    test_close_range_stdio();
+}
+
+fn pipe() -> [i32; 2] {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::pipe(fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    fds
+}
+
+fn read(fd: i32, buf: &mut [u8]) -> isize {
+    unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) }
+}
+
+fn write(fd: i32, buf: &[u8]) -> isize {
+    unsafe { libc::write(fd, buf.as_ptr().cast(), buf.len()) }
+}
+
+fn test_dup() {
+    let [reader, writer] = pipe();
+
+    // The duplicate refers to the same pipe.
+    let writer2 = unsafe { libc::dup(writer) };
+    assert!(writer2 >= 0 && writer2 != writer);
+    assert_eq!(write(writer2, b"abc"), 3);
+    let mut buf = [0u8; 3];
+    assert_eq!(read(reader, &mut buf), 3);
+    assert_eq!(&buf, b"abc");
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    assert_eq!(unsafe { libc::close(writer2) }, 0);
+}
+
+fn test_dup2_same_fd() {
+    let [reader, writer] = pipe();
+
+    // Duplicating a file descriptor onto itself does nothing; in particular, it does not close it.
+    assert_eq!(unsafe { libc::dup2(writer, writer) }, writer);
+    assert_eq!(write(writer, b"abc"), 3);
+    let mut buf = [0u8; 3];
+    assert_eq!(read(reader, &mut buf), 3);
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_dup2_replaces_open_fd() {
+    let [reader1, writer1] = pipe();
+    let [reader2, writer2] = pipe();
+
+    // After this, `writer2` refers to the first pipe, and the second pipe has no write end left.
+    assert_eq!(unsafe { libc::dup2(writer1, writer2) }, writer2);
+    assert_eq!(write(writer2, b"abc"), 3);
+    let mut buf = [0u8; 3];
+    assert_eq!(read(reader1, &mut buf), 3);
+    assert_eq!(&buf, b"abc");
+    assert_eq!(read(reader2, &mut buf), 0);
+
+    assert_eq!(unsafe { libc::close(reader1) }, 0);
+    assert_eq!(unsafe { libc::close(writer1) }, 0);
+    assert_eq!(unsafe { libc::close(reader2) }, 0);
+    assert_eq!(unsafe { libc::close(writer2) }, 0);
+}
+
+fn test_dup2_redirect_stdout() {
+    let [reader, writer] = pipe();
+
+    // Redirect stdout into the pipe, and restore it afterwards.
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    assert!(saved > 2);
+    assert_eq!(unsafe { libc::dup2(writer, libc::STDOUT_FILENO) }, libc::STDOUT_FILENO);
+    assert_eq!(write(libc::STDOUT_FILENO, b"abc"), 3);
+    let mut buf = [0u8; 3];
+    assert_eq!(read(reader, &mut buf), 3);
+    assert_eq!(&buf, b"abc");
+    assert_eq!(unsafe { libc::dup2(saved, libc::STDOUT_FILENO) }, libc::STDOUT_FILENO);
+    assert_eq!(unsafe { libc::close(saved) }, 0);
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_dup2_bad_fd() {
+    let [reader, writer] = pipe();
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+
+    // Duplicating a closed file descriptor fails, even onto itself.
+    assert_eq!(unsafe { libc::dup2(writer, writer) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    assert_eq!(unsafe { libc::dup2(writer, reader) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    // The target file descriptor stays open in that case.
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+}
+
+fn test_dup2_negative_fd() {
+    let [reader, writer] = pipe();
+
+    // A negative target is not a valid file descriptor.
+    assert_eq!(unsafe { libc::dup2(writer, -1) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    #[cfg(target_os = "linux")]
+    {
+        assert_eq!(unsafe { libc::dup3(writer, -1, libc::O_CLOEXEC) }, -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    }
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn test_dup3() {
+    let [reader1, writer1] = pipe();
+    let [reader2, writer2] = pipe();
+
+    // Unlike `dup2`, `dup3` rejects equal file descriptors.
+    assert_eq!(unsafe { libc::dup3(writer1, writer1, 0) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::dup3(writer1, writer2, libc::O_CLOEXEC) }, writer2);
+    assert_eq!(get_fd_flags(writer2), libc::FD_CLOEXEC);
+    assert_eq!(write(writer2, b"abc"), 3);
+    let mut buf = [0u8; 3];
+    assert_eq!(read(reader1, &mut buf), 3);
+    assert_eq!(read(reader2, &mut buf), 0);
+
+    assert_eq!(unsafe { libc::close(reader1) }, 0);
+    assert_eq!(unsafe { libc::close(writer1) }, 0);
+    assert_eq!(unsafe { libc::close(reader2) }, 0);
+    assert_eq!(unsafe { libc::close(writer2) }, 0);
+}
+
+fn get_fd_flags(fd: i32) -> i32 {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    assert!(flags >= 0);
+    flags
+}
+
+fn test_cloexec() {
+    let [reader, writer] = pipe();
+    // `pipe` does not set `FD_CLOEXEC`.
+    assert_eq!(get_fd_flags(reader), 0);
+
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_SETFD, libc::FD_CLOEXEC) }, 0);
+    assert_eq!(get_fd_flags(reader), libc::FD_CLOEXEC);
+    // The flag is not shared with duplicates.
+    let reader2 = unsafe { libc::dup(reader) };
+    assert_eq!(get_fd_flags(reader2), 0);
+    let reader3 = unsafe { libc::fcntl(reader, libc::F_DUPFD_CLOEXEC, 0) };
+    assert_eq!(get_fd_flags(reader3), libc::FD_CLOEXEC);
+
+    // Clearing the flag again.
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_SETFD, 0) }, 0);
+    assert_eq!(get_fd_flags(reader), 0);
+    assert_eq!(get_fd_flags(reader3), libc::FD_CLOEXEC);
+
+    assert_eq!(unsafe { libc::fcntl(100, libc::F_SETFD, 0) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(reader2) }, 0);
+    assert_eq!(unsafe { libc::close(reader3) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn test_close_range() {
+    let is_open = |fd| unsafe { libc::fcntl(fd, libc::F_GETFD) } != -1;
+    // Get 6 consecutive file descriptors.
+    let [first, writer] = pipe();
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    let fds: Vec<i32> = (1..6).map(|_| unsafe { libc::dup(first) }).collect();
+    assert_eq!(fds, (first + 1..first + 6).collect::<Vec<_>>());
+    // Leave a gap in the range, which is skipped.
+    assert_eq!(unsafe { libc::close(first + 3) }, 0);
+
+    // Mark the middle ones as close-on-exec.
+    assert_eq!(
+        unsafe {
+            libc::close_range(first as u32 + 1, first as u32 + 4, libc::CLOSE_RANGE_CLOEXEC as i32)
+        },
+        0
+    );
+    assert_eq!(get_fd_flags(first), 0);
+    assert_eq!(get_fd_flags(first + 1), libc::FD_CLOEXEC);
+    assert_eq!(get_fd_flags(first + 4), libc::FD_CLOEXEC);
+    assert_eq!(get_fd_flags(first + 5), 0);
+
+    // Close them.
+    assert_eq!(unsafe { libc::close_range(first as u32 + 1, first as u32 + 4, 0) }, 0);
+    assert!(is_open(first));
+    assert!(!is_open(first + 1));
+    assert!(!is_open(first + 2));
+    assert!(!is_open(first + 4));
+    assert!(is_open(first + 5));
+
+    // Invalid ranges and flags are rejected.
+    assert_eq!(unsafe { libc::close_range(first as u32 + 1, first as u32, 0) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    assert_eq!(unsafe { libc::close_range(first as u32, first as u32, 0x1000) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // Close everything that is left, up to the largest possible file descriptor.
+    assert_eq!(unsafe { libc::close_range(first as u32, u32::MAX, 0) }, 0);
+    assert!(!is_open(first));
+    assert!(!is_open(first + 5));
+}
+
+#[cfg(target_os = "linux")]
+fn test_close_range_stdio() {
+    let is_open = |fd| unsafe { libc::fcntl(fd, libc::F_GETFD) } != -1;
+    let [reader, writer] = pipe();
+
+    // Closing everything includes the standard streams.
+    assert_eq!(unsafe { libc::close_range(0, !0u32, 0) }, 0);
+    for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO, reader, writer] {
+        assert!(!is_open(fd));
+    }
+}
@@ -0,0 +1,112 @@
+//@only-target-linux
+// We are making scheduler assumptions here.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::io::Error;
+use std::thread;
+
+fn main() {
+    test_epoll_ctl();
+    test_epoll_wait_immediate();
+    test_epoll_wait_wakeup();
+}
+
+fn pipe() -> [i32; 2] {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::pipe(fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    fds
+}
+
+fn epoll_ctl(epfd: i32, op: i32, fd: i32, events: i32, data: u64) -> i32 {
+    let mut event = libc::epoll_event { events: events as u32, u64: data };
+    unsafe { libc::epoll_ctl(epfd, op, fd, &mut event) }
+}
+
+fn test_epoll_ctl() {
+    let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+    assert!(epfd >= 0);
+    let [reader, writer] = pipe();
+
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_ADD, reader, libc::EPOLLIN, 0), 0);
+    // Adding the same file descriptor twice fails.
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_ADD, reader, libc::EPOLLIN, 0), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EEXIST));
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_MOD, reader, libc::EPOLLIN, 1), 0);
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_DEL, reader, 0, 0), 0);
+    // Modifying or removing a file descriptor that is not registered fails.
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_MOD, reader, libc::EPOLLIN, 0), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOENT));
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_DEL, writer, 0, 0), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOENT));
+    // So does registering a file descriptor that is not open.
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_ADD, 100, libc::EPOLLIN, 0), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_wait_immediate() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert!(epfd >= 0);
+    let [reader, writer] = pipe();
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_ADD, reader, libc::EPOLLIN, 1), 0);
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_ADD, writer, libc::EPOLLOUT, 2), 0);
+
+    // Only the write end is ready.
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 4];
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 4, 0) };
+    assert_eq!(res, 1);
+    assert_eq!({ events[0].events }, libc::EPOLLOUT as u32);
+    assert_eq!({ events[0].u64 }, 2);
+
+    // After a write, both ends are ready, and as long as the data is not read, this does not
+    // change.
+    assert_eq!(unsafe { libc::write(writer, b"abc".as_ptr().cast(), 3) }, 3);
+    for _ in 0..2 {
+        let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 4, 0) };
+        assert_eq!(res, 2);
+        assert_eq!({ events[0].events }, libc::EPOLLIN as u32);
+        assert_eq!({ events[0].u64 }, 1);
+    }
+    // At most `maxevents` events are returned.
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 1, 0) };
+    assert_eq!(res, 1);
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 0, 0) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // Nothing becomes ready anymore once the file descriptors are closed.
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 4, 10) };
+    assert_eq!(res, 0);
+
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_wait_wakeup() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert!(epfd >= 0);
+    let [reader, writer] = pipe();
+    assert_eq!(epoll_ctl(epfd, libc::EPOLL_CTL_ADD, reader, libc::EPOLLIN | libc::EPOLLET, 7), 0);
+
+    let thread = thread::spawn(move || {
+        thread::yield_now();
+        assert_eq!(unsafe { libc::write(writer, b"abc".as_ptr().cast(), 3) }, 3);
+    });
+
+    // Wait until the other thread writes to the pipe.
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 1];
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 1, -1) };
+    assert_eq!(res, 1);
+    assert_eq!({ events[0].events }, libc::EPOLLIN as u32);
+    assert_eq!({ events[0].u64 }, 7);
+
+    thread.join().unwrap();
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
@@ -0,0 +1,110 @@
+//@only-target-linux
+
+use std::io::Error;
+
+fn main() {
+    test_read_write();
+    test_semaphore();
+    test_nonblock();
+    test_dup();
+    test_invalid_size();
+}
+
+fn read_u64(fd: i32) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) };
+    if res < 0 {
+        return Err(Error::last_os_error());
+    }
+    assert_eq!(res, 8);
+    Ok(u64::from_ne_bytes(buf))
+}
+
+fn write_u64(fd: i32, val: u64) -> Result<(), Error> {
+    let buf = val.to_ne_bytes();
+    let res = unsafe { libc::write(fd, buf.as_ptr().cast(), 8) };
+    if res < 0 {
+        return Err(Error::last_os_error());
+    }
+    assert_eq!(res, 8);
+    Ok(())
+}
+
+fn test_read_write() {
+    let fd = unsafe { libc::eventfd(3, libc::EFD_CLOEXEC) };
+    assert!(fd >= 0);
+
+    // Reading returns the counter and resets it.
+    assert_eq!(read_u64(fd).unwrap(), 3);
+
+    // Writing adds to the counter.
+    write_u64(fd, 5).unwrap();
+    write_u64(fd, 7).unwrap();
+    assert_eq!(read_u64(fd).unwrap(), 12);
+
+    // Writing `u64::MAX` is not allowed.
+    assert_eq!(write_u64(fd, u64::MAX).unwrap_err().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_semaphore() {
+    let fd = unsafe { libc::eventfd(2, libc::EFD_SEMAPHORE | libc::EFD_NONBLOCK) };
+    assert!(fd >= 0);
+
+    // Reading in semaphore mode returns 1 and decrements the counter.
+    assert_eq!(read_u64(fd).unwrap(), 1);
+    assert_eq!(read_u64(fd).unwrap(), 1);
+    assert_eq!(read_u64(fd).unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+
+    write_u64(fd, 1).unwrap();
+    assert_eq!(read_u64(fd).unwrap(), 1);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_nonblock() {
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    assert!(fd >= 0);
+
+    // Reading a zero counter would block.
+    assert_eq!(read_u64(fd).unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+
+    // The counter can hold at most `u64::MAX - 1`; exceeding that would block.
+    write_u64(fd, u64::MAX - 1).unwrap();
+    assert_eq!(write_u64(fd, 1).unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+    assert_eq!(read_u64(fd).unwrap(), u64::MAX - 1);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_dup() {
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    assert!(fd >= 0);
+    let fd2 = unsafe { libc::fcntl(fd, libc::F_DUPFD, 0) };
+    assert!(fd2 >= 0);
+
+    // Both file descriptors refer to the same counter.
+    write_u64(fd, 4).unwrap();
+    assert_eq!(read_u64(fd2).unwrap(), 4);
+    assert_eq!(read_u64(fd).unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(fd2) }, 0);
+}
+
+fn test_invalid_size() {
+    let fd = unsafe { libc::eventfd(1, libc::EFD_NONBLOCK) };
+    assert!(fd >= 0);
+
+    // Buffers smaller than 8 bytes are rejected.
+    let mut buf = [0u8; 4];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 4) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    let res = unsafe { libc::write(fd, buf.as_ptr().cast(), 4) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
@@ -25,4 +25,56 @@ fn main() {
     assert_eq!(err.kind(), ErrorKind::PermissionDenied);
     // check that it is the right kind of `PermissionDenied`
     assert_eq!(err.raw_os_error(), Some(libc::EACCES));
+
+    // test `mkdtemp`
+    let template = CString::new("miri_test_mkdtempXXXXXX").unwrap().into_raw();
+    unsafe {
+        assert!(libc::mkdtemp(template).is_null());
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EACCES));
+        drop(CString::from_raw(template));
+    }
+
+    // test `fchmod`
+    unsafe {
+        assert_eq!(libc::fchmod(1, 0o644), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPERM));
+    }
+
+    // test `ftruncate`
+    unsafe {
+        assert_eq!(libc::ftruncate(1, 0), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPERM));
+    }
+
+    // test reading from `/dev/urandom`: should work even with isolation.
+    for path in ["/dev/urandom", "/dev/random"] {
+        let path = CString::new(path).unwrap();
+        let mut buf = [0u8; 32];
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC);
+            assert!(fd >= 0);
+            assert_eq!(libc::read(fd, buf.as_mut_ptr().cast(), buf.len()), 32);
+            assert_eq!(libc::close(fd), 0);
+        }
+        // The chance of getting 32 zero bytes is negligible.
+        assert!(buf.iter().any(|&b| b != 0));
+    }
+
+    // The random devices already exist, and their duplicates can be read as well.
+    unsafe {
+        let path = CString::new("/dev/urandom").unwrap();
+        let fd = libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CREAT | libc::O_EXCL, 0o666);
+        assert_eq!(fd, -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EEXIST));
+
+        let fd = libc::open(path.as_ptr(), libc::O_RDONLY);
+        assert!(fd >= 0);
+        assert_eq!(libc::fcntl(fd, libc::F_GETFL) & libc::O_ACCMODE, libc::O_RDONLY);
+        let fd2 = libc::dup(fd);
+        assert!(fd2 >= 0);
+        let mut buf = [0u8; 8];
+        assert_eq!(libc::read(fd2, buf.as_mut_ptr().cast(), buf.len()), 8);
+        assert_eq!(libc::close(fd), 0);
+        assert_eq!(libc::close(fd2), 0);
+    }
 }
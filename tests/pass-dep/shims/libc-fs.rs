@@ -6,7 +6,7 @@
 
 use std::ffi::CString;
 use std::fs::{canonicalize, remove_dir_all, remove_file, File};
-use std::io::{Error, ErrorKind, Write};
+use std::io::{Error, ErrorKind, Read, Write};
 use std::os::unix::ffi::OsStrExt;
 use std::path::PathBuf;
 
@@ -21,12 +21,35 @@ fn main() {
     #[cfg(target_os = "linux")]
     test_ftruncate::<libc::off64_t>(libc::ftruncate64);
     test_readlink();
+    test_readlinkat();
+    test_openat_fstatat();
+    #[cfg(target_os = "linux")]
+    test_getdents64();
     test_file_open_unix_allow_two_args();
     test_file_open_unix_needs_three_args();
     test_file_open_unix_extra_third_arg();
     #[cfg(target_os = "linux")]
     test_o_tmpfile_flag();
     test_posix_mkstemp();
+    test_mkstemps_mkdtemp();
+    test_pread_pwrite();
+    test_mmap_file();
+    test_readv_writev();
+    #[cfg(target_os = "linux")]
+    test_preadv_pwritev();
+    test_fsync();
+    test_flock();
+    #[cfg(target_os = "linux")]
+    test_seek_data_hole();
+    #[cfg(target_os = "linux")]
+    test_sendfile();
+    test_o_append();
+    #[cfg(target_os = "linux")]
+    test_statx();
+    test_statvfs();
+    test_fchmod_fchown();
+    #[cfg(target_os = "linux")]
+    test_posix_fadvise_fallocate();
 }
 
 /// Prepare: compute filename and make sure the file does not exist.
@@ -54,6 +77,133 @@ fn prepare_with_content(filename: &str, content: &[u8]) -> PathBuf {
     path
 }
 
+fn test_openat_fstatat() {
+    use std::fs::{create_dir, write};
+
+    let dir_path = prepare_dir("miri_test_libc_openat");
+    create_dir(&dir_path).unwrap();
+    write(dir_path.join("file.txt"), b"hello openat").unwrap();
+
+    let dir_c = CString::new(dir_path.as_os_str().as_bytes()).unwrap();
+    let dirfd = unsafe { libc::open(dir_c.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+    assert!(dirfd >= 0);
+
+    // Open a file relative to the directory, and read it.
+    let name = CString::new("file.txt").unwrap();
+    let fd = unsafe { libc::openat(dirfd, name.as_ptr(), libc::O_RDONLY) };
+    assert!(fd >= 0);
+    let mut buf = [0u8; 12];
+    assert_eq!(unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) }, 12);
+    assert_eq!(&buf, b"hello openat");
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+
+    // Query the metadata of the file and of the directory itself.
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    assert_eq!(unsafe { libc::fstatat(dirfd, name.as_ptr(), stat.as_mut_ptr(), 0) }, 0);
+    let stat = unsafe { stat.assume_init() };
+    assert_eq!(stat.st_size, 12);
+    assert_eq!(stat.st_mode & libc::S_IFMT, libc::S_IFREG);
+    let dot = CString::new(".").unwrap();
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    assert_eq!(unsafe { libc::fstatat(dirfd, dot.as_ptr(), stat.as_mut_ptr(), 0) }, 0);
+    assert_eq!(unsafe { stat.assume_init() }.st_mode & libc::S_IFMT, libc::S_IFDIR);
+
+    // A missing file is reported as such.
+    let missing = CString::new("missing.txt").unwrap();
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    assert_eq!(unsafe { libc::fstatat(dirfd, missing.as_ptr(), stat.as_mut_ptr(), 0) }, -1);
+    assert_eq!(Error::last_os_error().kind(), ErrorKind::NotFound);
+
+    // Only directories can be used as `dirfd`, and only directories can be opened with
+    // `O_DIRECTORY`.
+    let file_c = CString::new(dir_path.join("file.txt").as_os_str().as_bytes()).unwrap();
+    let fd = unsafe { libc::open(file_c.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+    assert_eq!(fd, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOTDIR));
+    let fd = unsafe { libc::open(file_c.as_ptr(), libc::O_RDONLY) };
+    assert!(fd >= 0);
+    assert_eq!(unsafe { libc::openat(fd, name.as_ptr(), libc::O_RDONLY) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOTDIR));
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+
+    assert_eq!(unsafe { libc::close(dirfd) }, 0);
+    remove_dir_all(&dir_path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+fn test_getdents64() {
+    use std::fs::{create_dir, write};
+
+    /// Lists the directory with buffers of `buf_size` bytes and returns the names of all entries.
+    fn list(dirfd: i32, buf_size: usize) -> Vec<String> {
+        // The buffer must be aligned like `dirent64`.
+        let mut buf = vec![0u64; buf_size / 8];
+        let mut names = Vec::new();
+        loop {
+            let res =
+                unsafe { libc::syscall(libc::SYS_getdents64, dirfd, buf.as_mut_ptr(), buf_size) };
+            assert!(res >= 0);
+            if res == 0 {
+                break;
+            }
+            let bytes = unsafe {
+                std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), res.try_into().unwrap())
+            };
+            let mut offset = 0;
+            while offset < bytes.len() {
+                let reclen = u16::from_ne_bytes([bytes[offset + 16], bytes[offset + 17]]);
+                let reclen = usize::from(reclen);
+                assert_eq!(reclen % 8, 0);
+                assert_eq!(bytes[offset + 18], libc::DT_REG);
+                let name = &bytes[offset + 19..offset + reclen];
+                let len = name.iter().position(|&b| b == 0).unwrap();
+                names.push(String::from_utf8(name[..len].to_vec()).unwrap());
+                offset += reclen;
+            }
+            assert_eq!(offset, bytes.len());
+        }
+        names.sort();
+        names
+    }
+
+    let dir_path = prepare_dir("miri_test_libc_getdents64");
+    create_dir(&dir_path).unwrap();
+    let expected = ["a.txt", "b.txt", "long_file_name.txt"];
+    for name in expected {
+        write(dir_path.join(name), b"").unwrap();
+    }
+
+    let dir_c = CString::new(dir_path.as_os_str().as_bytes()).unwrap();
+    let dirfd = unsafe { libc::open(dir_c.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+    assert!(dirfd >= 0);
+    assert_eq!(list(dirfd, 1024), expected);
+    // Once the end has been reached, nothing is returned anymore.
+    assert_eq!(list(dirfd, 1024), Vec::<String>::new());
+    // Duplicates share the position in the directory.
+    let dup = unsafe { libc::dup(dirfd) };
+    assert!(dup >= 0);
+    assert_eq!(list(dup, 1024), Vec::<String>::new());
+    assert_eq!(unsafe { libc::close(dup) }, 0);
+    assert_eq!(unsafe { libc::close(dirfd) }, 0);
+
+    // A small buffer fits only one entry at a time.
+    let dirfd = unsafe { libc::open(dir_c.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+    assert!(dirfd >= 0);
+    assert_eq!(list(dirfd, 40), expected);
+    assert_eq!(unsafe { libc::close(dirfd) }, 0);
+
+    // A buffer that cannot hold a single entry is rejected.
+    let dirfd = unsafe { libc::open(dir_c.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+    assert!(dirfd >= 0);
+    let mut buf = [0u64; 2];
+    let res = unsafe { libc::syscall(libc::SYS_getdents64, dirfd, buf.as_mut_ptr(), 16) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    assert_eq!(unsafe { libc::close(dirfd) }, 0);
+
+    remove_dir_all(&dir_path).unwrap();
+}
+
 fn test_file_open_unix_allow_two_args() {
     let path = prepare_with_content("test_file_open_unix_allow_two_args.txt", &[]);
 
@@ -137,6 +287,53 @@ fn test_readlink() {
     assert_eq!(Error::last_os_error().kind(), ErrorKind::NotFound);
 }
 
+fn test_readlinkat() {
+    use std::fs::create_dir;
+
+    let dir_path = prepare_dir("miri_test_libc_readlinkat");
+    create_dir(&dir_path).unwrap();
+    let target = b"some/target.txt";
+    std::os::unix::fs::symlink(std::ffi::OsStr::from_bytes(target), dir_path.join("link")).unwrap();
+    std::fs::write(dir_path.join("file.txt"), b"not a link").unwrap();
+
+    let dir_c = CString::new(dir_path.as_os_str().as_bytes()).unwrap();
+    let dirfd = unsafe { libc::open(dir_c.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+    assert!(dirfd >= 0);
+
+    // The link is resolved relative to `dirfd`, and its target is written without a null
+    // terminator.
+    let link = CString::new("link").unwrap();
+    let mut buf = [0xFFu8; 32];
+    let res = unsafe { libc::readlinkat(dirfd, link.as_ptr(), buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, target.len() as isize);
+    assert_eq!(&buf[..target.len()], target);
+    assert_eq!(buf[target.len()], 0xFF);
+
+    // The target is truncated to the buffer size.
+    let mut small_buf = [0u8; 4];
+    let res = unsafe {
+        libc::readlinkat(dirfd, link.as_ptr(), small_buf.as_mut_ptr().cast(), small_buf.len())
+    };
+    assert_eq!(res, 4);
+    assert_eq!(&small_buf, b"some");
+
+    // Reading something that is not a symlink fails with `EINVAL`.
+    let file = CString::new("file.txt").unwrap();
+    let res = unsafe { libc::readlinkat(dirfd, file.as_ptr(), buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // `AT_FDCWD` resolves relative to the working directory, like `readlink`.
+    let link_c = CString::new(dir_path.join("link").as_os_str().as_bytes()).unwrap();
+    let res = unsafe {
+        libc::readlinkat(libc::AT_FDCWD, link_c.as_ptr(), buf.as_mut_ptr().cast(), buf.len())
+    };
+    assert_eq!(res, target.len() as isize);
+
+    assert_eq!(unsafe { libc::close(dirfd) }, 0);
+    remove_dir_all(&dir_path).unwrap();
+}
+
 fn test_rename() {
     let path1 = prepare("miri_test_libc_fs_source.txt");
     let path2 = prepare("miri_test_libc_fs_rename_destination.txt");
@@ -193,6 +390,29 @@ fn test_ftruncate<T: From<i32>>(
     assert_eq!(res, 0);
     assert_eq!(file.metadata().unwrap().len(), 2);
 
+    // Grow the file again, the new part reads as zeros
+    res = unsafe { ftruncate(fd, T::from(6)) };
+    assert_eq!(res, 0);
+    let mut buf = [0xffu8; 8];
+    let n = unsafe { libc::pread(fd, buf.as_mut_ptr().cast(), buf.len(), 0) };
+    assert_eq!(n, 6);
+    assert_eq!(&buf[..6], b"he\0\0\0\0");
+
+    // Negative lengths are rejected
+    res = unsafe { ftruncate(fd, T::from(-1)) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // Only regular files can be truncated
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    res = unsafe { ftruncate(fds[1], T::from(0)) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
     remove_file(&path).unwrap();
 }
 
@@ -256,3 +476,538 @@ fn test_posix_mkstemp() {
         assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
     }
 }
+
+fn test_mkstemps_mkdtemp() {
+    use std::ffi::OsStr;
+    use std::os::unix::io::FromRawFd;
+    use std::path::Path;
+
+    // `mkstemps` keeps the given number of bytes after the `XXXXXX`, and the file can be written.
+    let template = utils::tmp().join("miri_test_libc_mkstempsXXXXXX.txt");
+    let ptr = CString::new(template.as_os_str().as_bytes()).unwrap().into_raw();
+    let fd = unsafe { libc::mkstemps(ptr, 4) };
+    let path = unsafe { CString::from_raw(ptr) };
+    assert!(fd > 0);
+    let path: &Path = OsStr::from_bytes(path.to_bytes()).as_ref();
+    let name = path.file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("miri_test_libc_mkstemps"));
+    assert!(name.ends_with(".txt"));
+    assert!(!name.contains("XXXXXX"));
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(b"temporary").unwrap();
+    drop(file);
+    assert_eq!(std::fs::read(path).unwrap(), b"temporary");
+    remove_file(path).unwrap();
+
+    // The suffix must not overlap the `XXXXXX`.
+    let ptr = CString::new(template.as_os_str().as_bytes()).unwrap().into_raw();
+    let fd = unsafe { libc::mkstemps(ptr, 5) };
+    let _ = unsafe { CString::from_raw(ptr) };
+    assert_eq!(fd, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // `mkdtemp` creates a directory and returns the modified template.
+    let template = utils::tmp().join("miri_test_libc_mkdtempXXXXXX");
+    let ptr = CString::new(template.as_os_str().as_bytes()).unwrap().into_raw();
+    let res = unsafe { libc::mkdtemp(ptr) };
+    assert_eq!(res, ptr);
+    let path = unsafe { CString::from_raw(ptr) };
+    let path: &Path = OsStr::from_bytes(path.to_bytes()).as_ref();
+    assert!(!path.to_str().unwrap().ends_with("XXXXXX"));
+    assert!(path.is_dir());
+    remove_dir_all(path).unwrap();
+
+    let ptr = CString::new("miri_test_libc_mkdtempXXXX").unwrap().into_raw();
+    let res = unsafe { libc::mkdtemp(ptr) };
+    let _ = unsafe { CString::from_raw(ptr) };
+    assert!(res.is_null());
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+}
+
+fn test_mmap_file() {
+    use std::os::unix::io::AsRawFd;
+    use std::{ptr, slice};
+
+    let page_size = page_size::get();
+    let mut content = vec![b'a'; page_size];
+    content.extend_from_slice(b"known");
+    let path = prepare_with_content("miri_test_libc_mmap_file.txt", &content);
+    let file = File::open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    // Map the second page of the file. The part beyond the end of the file is zeroed.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            fd,
+            page_size as _,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    let slice = unsafe { slice::from_raw_parts(ptr as *const u8, page_size) };
+    assert_eq!(&slice[..5], b"known");
+    assert!(slice[5..].iter().all(|b| *b == 0));
+    // `MADV_DONTNEED` refills a file mapping from the file instead of zeroing it.
+    assert_eq!(unsafe { libc::madvise(ptr, page_size, libc::MADV_DONTNEED) }, 0);
+    assert_eq!(&slice[..5], b"known");
+    assert_eq!(unsafe { libc::munmap(ptr, page_size) }, 0);
+
+    // Writes to a private mapping are not carried through to the file.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE,
+            fd,
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    unsafe { *(ptr as *mut u8) = b'b' };
+    assert_eq!(unsafe { *(ptr as *const u8) }, b'b');
+    assert_eq!(unsafe { libc::munmap(ptr, page_size) }, 0);
+    assert_eq!(std::fs::read(&path).unwrap(), content);
+
+    // A shared writable mapping needs a file that is open for writing.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        )
+    };
+    assert_eq!(ptr, libc::MAP_FAILED);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EACCES));
+
+    // The offset must be a multiple of the page size.
+    let ptr = unsafe {
+        libc::mmap(ptr::null_mut(), page_size, libc::PROT_READ, libc::MAP_PRIVATE, fd, 1)
+    };
+    assert_eq!(ptr, libc::MAP_FAILED);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_pread_pwrite() {
+    use std::os::unix::io::AsRawFd;
+
+    let bytes = b"hello, world!\n";
+    let path = prepare_with_content("miri_test_libc_pread_pwrite.txt", bytes);
+    let mut file = File::options().read(true).write(true).open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    // Advance the file offset with a regular read.
+    let mut buf = [0u8; 5];
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    // `pread` reads at the given offset and does not move the file offset.
+    let res = unsafe { libc::pread(fd, buf.as_mut_ptr().cast(), 5, 7) };
+    assert_eq!(res, 5);
+    assert_eq!(&buf, b"world");
+
+    let mut buf = [0u8; 2];
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b", ");
+
+    // `pwrite` writes at the given offset and does not move the file offset either.
+    let res = unsafe { libc::pwrite(fd, b"HELLO".as_ptr().cast(), 5, 0) };
+    assert_eq!(res, 5);
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut buf = [0u8; 5];
+        let res = unsafe { libc::pread64(fd, buf.as_mut_ptr().cast(), 5, 0) };
+        assert_eq!(res, 5);
+        assert_eq!(&buf, b"HELLO");
+        let res = unsafe { libc::pwrite64(fd, b"WORLD".as_ptr().cast(), 5, 7) };
+        assert_eq!(res, 5);
+    }
+
+    // Reading past the end of the file returns 0.
+    let mut buf = [0u8; 5];
+    let res = unsafe { libc::pread(fd, buf.as_mut_ptr().cast(), 5, 100) };
+    assert_eq!(res, 0);
+
+    // A negative offset is rejected.
+    let res = unsafe { libc::pread(fd, buf.as_mut_ptr().cast(), 5, -1) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // The regular read continues where it left off.
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).unwrap();
+    #[cfg(target_os = "linux")]
+    assert_eq!(buf, b"WORLD!\n");
+    #[cfg(not(target_os = "linux"))]
+    assert_eq!(buf, b"world!\n");
+
+    remove_file(&path).unwrap();
+}
+
+fn test_readv_writev() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare("miri_test_libc_readv_writev.txt");
+    let file = File::options().read(true).write(true).create(true).open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    // Gather three buffers into a single write.
+    let bufs: [&[u8]; 3] = [b"abc", b"", b"defgh"];
+    let iov = bufs.map(|buf| libc::iovec { iov_base: buf.as_ptr() as *mut _, iov_len: buf.len() });
+    let res = unsafe { libc::writev(fd, iov.as_ptr(), 3) };
+    assert_eq!(res, 8);
+
+    // Scatter the content back into three buffers.
+    let res = unsafe { libc::lseek(fd, 0, libc::SEEK_SET) };
+    assert_eq!(res, 0);
+    let mut buf1 = [0u8; 2];
+    let mut buf2 = [0u8; 4];
+    let mut buf3 = [0u8; 4];
+    let iov = [
+        libc::iovec { iov_base: buf1.as_mut_ptr().cast(), iov_len: buf1.len() },
+        libc::iovec { iov_base: buf2.as_mut_ptr().cast(), iov_len: buf2.len() },
+        libc::iovec { iov_base: buf3.as_mut_ptr().cast(), iov_len: buf3.len() },
+    ];
+    let res = unsafe { libc::readv(fd, iov.as_ptr(), 3) };
+    assert_eq!(res, 8);
+    assert_eq!(&buf1, b"ab");
+    assert_eq!(&buf2, b"cdef");
+    assert_eq!(&buf3, b"gh\0\0");
+
+    // At the end of the file, nothing is read.
+    let res = unsafe { libc::readv(fd, iov.as_ptr(), 3) };
+    assert_eq!(res, 0);
+
+    // An invalid number of buffers is rejected.
+    let res = unsafe { libc::readv(fd, iov.as_ptr(), -1) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+fn test_preadv_pwritev() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare("miri_test_libc_preadv_pwritev.txt");
+    let file = File::options().read(true).write(true).create(true).open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    // Write two buffers at offset 100, which extends the file.
+    let bufs: [&[u8]; 2] = [b"hello ", b"world"];
+    let iov = bufs.map(|buf| libc::iovec { iov_base: buf.as_ptr() as *mut _, iov_len: buf.len() });
+    let res = unsafe { libc::pwritev(fd, iov.as_ptr(), 2, 100) };
+    assert_eq!(res, 11);
+    assert_eq!(file.metadata().unwrap().len(), 111);
+
+    // Read them back into two buffers.
+    let mut buf1 = [0u8; 4];
+    let mut buf2 = [0u8; 8];
+    let iov = [
+        libc::iovec { iov_base: buf1.as_mut_ptr().cast(), iov_len: buf1.len() },
+        libc::iovec { iov_base: buf2.as_mut_ptr().cast(), iov_len: buf2.len() },
+    ];
+    let res = unsafe { libc::preadv(fd, iov.as_ptr(), 2, 100) };
+    assert_eq!(res, 11);
+    assert_eq!(&buf1, b"hell");
+    assert_eq!(&buf2, b"o world\0");
+
+    // The file offset was not moved.
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 0);
+
+    // A negative offset is rejected.
+    let res = unsafe { libc::preadv(fd, iov.as_ptr(), 2, -1) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_fsync() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare("miri_test_libc_fsync.txt");
+    let mut file = File::create(&path).unwrap();
+    file.write_all(b"hello").unwrap();
+    let fd = file.as_raw_fd();
+
+    assert_eq!(unsafe { libc::fsync(fd) }, 0);
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    assert_eq!(unsafe { libc::fdatasync(fd) }, 0);
+
+    // Only regular files can be synchronized.
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    assert_eq!(unsafe { libc::fsync(fds[1]) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    {
+        assert_eq!(unsafe { libc::fdatasync(fds[0]) }, -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    }
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_flock() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare_with_content("miri_test_libc_flock.txt", b"hello");
+    let file1 = File::open(&path).unwrap();
+    let file2 = File::open(&path).unwrap();
+    let fd1 = file1.as_raw_fd();
+    let fd2 = file2.as_raw_fd();
+
+    // Shared locks do not conflict with each other.
+    assert_eq!(unsafe { libc::flock(fd1, libc::LOCK_SH) }, 0);
+    assert_eq!(unsafe { libc::flock(fd2, libc::LOCK_SH | libc::LOCK_NB) }, 0);
+    // But an exclusive lock conflicts with the shared lock of the other open file.
+    assert_eq!(unsafe { libc::flock(fd1, libc::LOCK_EX | libc::LOCK_NB) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EWOULDBLOCK));
+
+    // Once that is unlocked, the lock can be converted to an exclusive one.
+    assert_eq!(unsafe { libc::flock(fd2, libc::LOCK_UN) }, 0);
+    assert_eq!(unsafe { libc::flock(fd1, libc::LOCK_EX | libc::LOCK_NB) }, 0);
+    assert_eq!(unsafe { libc::flock(fd2, libc::LOCK_SH | libc::LOCK_NB) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EWOULDBLOCK));
+
+    // Duplicated file descriptors share the lock, so they do not conflict.
+    let fd3 = unsafe { libc::dup(fd1) };
+    assert!(fd3 >= 0);
+    assert_eq!(unsafe { libc::flock(fd3, libc::LOCK_EX | libc::LOCK_NB) }, 0);
+    // The lock is only released once all duplicates are closed.
+    drop(file1);
+    assert_eq!(unsafe { libc::flock(fd2, libc::LOCK_EX | libc::LOCK_NB) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EWOULDBLOCK));
+    assert_eq!(unsafe { libc::close(fd3) }, 0);
+    assert_eq!(unsafe { libc::flock(fd2, libc::LOCK_EX | libc::LOCK_NB) }, 0);
+
+    // Invalid operations are rejected.
+    assert_eq!(unsafe { libc::flock(fd2, 0) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    drop(file2);
+    remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+fn test_seek_data_hole() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare_with_content("miri_test_libc_fs_seek_data_hole.txt", b"hello world");
+    let file = File::open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    // The whole file is data, so the data starts right at the given offset, and the only hole
+    // is at the end of the file.
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_DATA) }, 0);
+    assert_eq!(unsafe { libc::lseek(fd, 6, libc::SEEK_DATA) }, 6);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 6);
+    assert_eq!(unsafe { libc::lseek(fd, 3, libc::SEEK_HOLE) }, 11);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 11);
+
+    // There is neither data nor a hole at or past the end of the file.
+    assert_eq!(unsafe { libc::lseek(fd, 2, libc::SEEK_SET) }, 2);
+    assert_eq!(unsafe { libc::lseek(fd, 11, libc::SEEK_DATA) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENXIO));
+    assert_eq!(unsafe { libc::lseek(fd, 20, libc::SEEK_HOLE) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENXIO));
+    // The file offset is not changed in that case.
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 2);
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+fn test_sendfile() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare_with_content("miri_test_libc_fs_sendfile.txt", b"hello world");
+    let file = File::open(&path).unwrap();
+    let fd = file.as_raw_fd();
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let [reader, writer] = fds;
+    let mut buf = [0u8; 16];
+
+    // Without an offset, the file offset is used and advanced.
+    assert_eq!(unsafe { libc::sendfile(writer, fd, std::ptr::null_mut(), 5) }, 5);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 5);
+    assert_eq!(unsafe { libc::read(reader, buf.as_mut_ptr().cast(), buf.len()) }, 5);
+    assert_eq!(&buf[..5], b"hello");
+
+    // With an offset, the offset is advanced instead of the file offset. Copying stops at the
+    // end of the file.
+    let mut offset: libc::off_t = 6;
+    assert_eq!(unsafe { libc::sendfile(writer, fd, &mut offset, 100) }, 5);
+    assert_eq!(offset, 11);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 5);
+    assert_eq!(unsafe { libc::read(reader, buf.as_mut_ptr().cast(), buf.len()) }, 5);
+    assert_eq!(&buf[..5], b"world");
+
+    // The source must be a regular file.
+    assert_eq!(unsafe { libc::sendfile(writer, reader, std::ptr::null_mut(), 5) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_o_append() {
+    let path = prepare_with_content("miri_test_libc_fs_o_append.txt", b"abc");
+    let mut name = path.clone().into_os_string();
+    name.push("\0");
+    let name_ptr = name.as_bytes().as_ptr().cast::<libc::c_char>();
+    let fd = unsafe { libc::open(name_ptr, libc::O_WRONLY | libc::O_APPEND) };
+    assert!(fd >= 0);
+    assert_eq!(unsafe { libc::fcntl(fd, libc::F_GETFL) } & libc::O_APPEND, libc::O_APPEND);
+
+    // Every write goes to the end of the file, no matter where the file offset is.
+    assert_eq!(unsafe { libc::write(fd, b"def".as_ptr().cast(), 3) }, 3);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_SET) }, 0);
+    assert_eq!(unsafe { libc::write(fd, b"ghi".as_ptr().cast(), 3) }, 3);
+    assert_eq!(std::fs::read(&path).unwrap(), b"abcdefghi");
+
+    // Once `O_APPEND` is cleared, writes go to the file offset again.
+    assert_eq!(unsafe { libc::fcntl(fd, libc::F_SETFL, libc::O_WRONLY) }, 0);
+    assert_eq!(unsafe { libc::fcntl(fd, libc::F_GETFL) } & libc::O_APPEND, 0);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_SET) }, 0);
+    assert_eq!(unsafe { libc::write(fd, b"ABC".as_ptr().cast(), 3) }, 3);
+    assert_eq!(std::fs::read(&path).unwrap(), b"ABCdefghi");
+
+    // The flag is shared with duplicates, so setting it on one affects the other.
+    let dup = unsafe { libc::dup(fd) };
+    assert!(dup >= 0);
+    assert_eq!(unsafe { libc::fcntl(dup, libc::F_SETFL, libc::O_WRONLY | libc::O_APPEND) }, 0);
+    assert_eq!(unsafe { libc::fcntl(fd, libc::F_GETFL) } & libc::O_APPEND, libc::O_APPEND);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_SET) }, 0);
+    assert_eq!(unsafe { libc::write(fd, b"jkl".as_ptr().cast(), 3) }, 3);
+    assert_eq!(std::fs::read(&path).unwrap(), b"ABCdefghijkl");
+    assert_eq!(unsafe { libc::close(dup) }, 0);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+fn test_statx() {
+    let path = prepare_with_content("miri_test_libc_fs_statx.txt", b"hello world");
+    let mut name = path.clone().into_os_string();
+    name.push("\0");
+    let name_ptr = name.as_bytes().as_ptr().cast::<libc::c_char>();
+
+    let mut statx = std::mem::MaybeUninit::<libc::statx>::zeroed();
+    let res =
+        unsafe { libc::statx(libc::AT_FDCWD, name_ptr, 0, libc::STATX_SIZE, statx.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    let statx = unsafe { statx.assume_init() };
+    assert_eq!(statx.stx_mask & libc::STATX_SIZE, libc::STATX_SIZE);
+    assert_eq!(statx.stx_size, 11);
+
+    remove_file(&path).unwrap();
+}
+
+fn test_statvfs() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare_with_content("miri_test_libc_fs_statvfs.txt", b"hello world");
+    let mut name = path.clone().into_os_string();
+    name.push("\0");
+    let name_ptr = name.as_bytes().as_ptr().cast::<libc::c_char>();
+
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::zeroed();
+    assert_eq!(unsafe { libc::statvfs(name_ptr, stat.as_mut_ptr()) }, 0);
+    let stat = unsafe { stat.assume_init() };
+    assert!(stat.f_bsize > 0);
+
+    let file = File::open(&path).unwrap();
+    let mut fstat = std::mem::MaybeUninit::<libc::statvfs>::zeroed();
+    assert_eq!(unsafe { libc::fstatvfs(file.as_raw_fd(), fstat.as_mut_ptr()) }, 0);
+    assert_eq!(unsafe { fstat.assume_init_ref() }.f_bsize, stat.f_bsize);
+
+    // Closed file descriptors are rejected.
+    assert_eq!(unsafe { libc::fstatvfs(-1, fstat.as_mut_ptr()) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+
+    remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+fn test_posix_fadvise_fallocate() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = prepare("miri_test_libc_fs_fallocate.txt");
+    let file = File::options().read(true).write(true).create(true).open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    assert_eq!(unsafe { libc::posix_fadvise(fd, 0, 0, libc::POSIX_FADV_SEQUENTIAL) }, 0);
+    assert_eq!(unsafe { libc::posix_fadvise(fd, 0, 0, 1000) }, libc::EINVAL);
+    assert_eq!(unsafe { libc::posix_fadvise(-1, 0, 0, libc::POSIX_FADV_NORMAL) }, libc::EBADF);
+
+    // Allocating grows the file with zeros.
+    assert_eq!(unsafe { libc::posix_fallocate(fd, 4, 12) }, 0);
+    assert_eq!(file.metadata().unwrap().len(), 16);
+    assert_eq!(std::fs::read(&path).unwrap(), [0; 16]);
+    // But it never shrinks it.
+    assert_eq!(unsafe { libc::posix_fallocate(fd, 0, 8) }, 0);
+    assert_eq!(file.metadata().unwrap().len(), 16);
+
+    assert_eq!(unsafe { libc::posix_fallocate(fd, 0, 0) }, libc::EINVAL);
+    assert_eq!(unsafe { libc::posix_fallocate(-1, 0, 8) }, libc::EBADF);
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_fchmod_fchown() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = prepare_with_content("miri_test_libc_fchmod.txt", b"chmod");
+    let path_c = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let fd = unsafe { libc::open(path_c.as_ptr(), libc::O_RDONLY) };
+    assert!(fd >= 0);
+
+    // The new mode can be read back via `fstat`.
+    assert_eq!(unsafe { libc::fchmod(fd, 0o640) }, 0);
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    assert_eq!(unsafe { libc::fstat(fd, stat.as_mut_ptr()) }, 0);
+    assert_eq!(unsafe { stat.assume_init() }.st_mode & 0o777, 0o640);
+
+    // `fchmodat` resolves the path and changes the same file.
+    assert_eq!(unsafe { libc::fchmodat(libc::AT_FDCWD, path_c.as_ptr(), 0o604, 0) }, 0);
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o604);
+
+    // File-type bits in the mode are ignored by both, so they end up with the same permissions.
+    assert_eq!(unsafe { libc::fchmod(fd, libc::S_IFREG | 0o640) }, 0);
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o7777, 0o640);
+    let mode = libc::S_IFREG | 0o604;
+    assert_eq!(unsafe { libc::fchmodat(libc::AT_FDCWD, path_c.as_ptr(), mode, 0) }, 0);
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o7777, 0o604);
+
+    // Passing -1 keeps both the owner and the group, which is always permitted.
+    assert_eq!(unsafe { libc::fchown(fd, u32::MAX, u32::MAX) }, 0);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::fchmod(fd, 0o600) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    remove_file(&path).unwrap();
+}
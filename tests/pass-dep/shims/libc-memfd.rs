@@ -0,0 +1,143 @@
+//@only-target-linux
+
+use std::io::Error;
+
+fn main() {
+    test_memfd_read_write();
+    test_memfd_ftruncate();
+    test_memfd_positional();
+    test_memfd_fstat();
+    test_memfd_size_limit();
+}
+
+fn memfd_create() -> i32 {
+    let fd = unsafe { libc::memfd_create(b"miri-test\0".as_ptr().cast(), libc::MFD_CLOEXEC) };
+    assert!(fd >= 0);
+    fd
+}
+
+fn read(fd: i32, buf: &mut [u8]) -> isize {
+    unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) }
+}
+
+fn write(fd: i32, buf: &[u8]) -> isize {
+    unsafe { libc::write(fd, buf.as_ptr().cast(), buf.len()) }
+}
+
+fn test_memfd_read_write() {
+    let fd = memfd_create();
+
+    // The file starts out empty.
+    let mut buf = [0u8; 16];
+    assert_eq!(read(fd, &mut buf), 0);
+
+    assert_eq!(write(fd, b"hello world"), 11);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_SET) }, 0);
+    assert_eq!(read(fd, &mut buf), 11);
+    assert_eq!(&buf[..11], b"hello world");
+
+    // Duplicates share the file offset.
+    let fd2 = unsafe { libc::dup(fd) };
+    assert_eq!(unsafe { libc::lseek(fd2, 6, libc::SEEK_SET) }, 6);
+    assert_eq!(read(fd, &mut buf), 5);
+    assert_eq!(&buf[..5], b"world");
+
+    // Writing past the end leaves a hole filled with zeros.
+    assert_eq!(unsafe { libc::lseek(fd, 2, libc::SEEK_END) }, 13);
+    assert_eq!(write(fd, b"!"), 1);
+    let res = unsafe { libc::pread(fd, buf.as_mut_ptr().cast(), buf.len(), 10) };
+    assert_eq!(res, 4);
+    assert_eq!(&buf[..4], b"d\0\0!");
+
+    // Seeking to a negative offset fails.
+    assert_eq!(unsafe { libc::lseek(fd, -100, libc::SEEK_CUR) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(fd2) }, 0);
+}
+
+fn test_memfd_ftruncate() {
+    let fd = memfd_create();
+
+    assert_eq!(write(fd, b"hello"), 5);
+    assert_eq!(unsafe { libc::ftruncate(fd, 2) }, 0);
+    assert_eq!(unsafe { libc::ftruncate(fd, 4) }, 0);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_SET) }, 0);
+    let mut buf = [0xffu8; 8];
+    assert_eq!(read(fd, &mut buf), 4);
+    assert_eq!(&buf[..4], b"he\0\0");
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_memfd_positional() {
+    let fd = memfd_create();
+    assert_eq!(write(fd, b"0123456789"), 10);
+
+    // Positional writes and reads do not move the file offset...
+    let res = unsafe { libc::pwrite(fd, b"ab".as_ptr().cast(), 2, 2) };
+    assert_eq!(res, 2);
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 10);
+    assert_eq!(unsafe { libc::lseek(fd, 4, libc::SEEK_SET) }, 4);
+    let mut buf = [0u8; 4];
+    let res = unsafe { libc::pread(fd, buf.as_mut_ptr().cast(), buf.len(), 0) };
+    assert_eq!(res, 4);
+    assert_eq!(&buf, b"01ab");
+
+    // ...so the next regular read continues where the previous one left off.
+    assert_eq!(read(fd, &mut buf), 4);
+    assert_eq!(&buf, b"4567");
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn fstat(fd: i32) -> libc::stat {
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    assert_eq!(unsafe { libc::fstat(fd, stat.as_mut_ptr()) }, 0);
+    unsafe { stat.assume_init() }
+}
+
+fn test_memfd_fstat() {
+    let fd = memfd_create();
+
+    // A memfd is a regular file whose size follows its content.
+    let stat = fstat(fd);
+    assert_eq!(stat.st_mode & libc::S_IFMT, libc::S_IFREG);
+    assert_eq!(stat.st_size, 0);
+    assert_eq!(write(fd, b"hello"), 5);
+    assert_eq!(fstat(fd).st_size, 5);
+    assert_eq!(unsafe { libc::ftruncate(fd, 2) }, 0);
+    assert_eq!(fstat(fd).st_size, 2);
+
+    // `std` queries it via `statx` instead.
+    let file = std::mem::ManuallyDrop::new(unsafe {
+        <std::fs::File as std::os::fd::FromRawFd>::from_raw_fd(fd)
+    });
+    let metadata = file.metadata().unwrap();
+    assert!(metadata.is_file());
+    assert_eq!(metadata.len(), 2);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_memfd_size_limit() {
+    let fd = memfd_create();
+    assert_eq!(write(fd, b"hello"), 5);
+
+    // Miri holds the file content in memory, so it rejects absurdly large files.
+    assert_eq!(unsafe { libc::ftruncate64(fd, 1 << 40) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EFBIG));
+    assert_eq!(unsafe { libc::lseek64(fd, 1 << 40, libc::SEEK_SET) }, 1 << 40);
+    assert_eq!(write(fd, b"!"), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EFBIG));
+    let res = unsafe { libc::pwrite64(fd, b"!".as_ptr().cast(), 1, 1 << 40) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EFBIG));
+    assert_eq!(unsafe { libc::posix_fallocate(fd, 0, libc::off_t::MAX) }, libc::EFBIG);
+
+    // The file is left unchanged.
+    assert_eq!(fstat(fd).st_size, 5);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
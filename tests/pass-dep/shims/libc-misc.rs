@@ -281,6 +281,35 @@ fn test_isatty() {
     }
 }
 
+#[cfg(target_os = "linux")]
+fn test_isatty_pipe() {
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+    // Pipes are not TTYs, so terminal requests fail.
+    assert_eq!(unsafe { libc::isatty(fds[0]) }, 0);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error().unwrap(), libc::ENOTTY);
+    let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+    assert_eq!(unsafe { libc::ioctl(fds[0], libc::TCGETS, termios.as_mut_ptr()) }, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error().unwrap(), libc::ENOTTY);
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
+
+fn test_winsize() {
+    let mut winsize = libc::winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+    let res = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut winsize) };
+    // Whether stdout is a TTY depends on how the test is run.
+    if unsafe { libc::isatty(libc::STDOUT_FILENO) } == 1 {
+        assert_eq!(res, 0);
+        assert!(winsize.ws_col > 0);
+    } else {
+        assert_eq!(res, -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error().unwrap(), libc::ENOTTY);
+    }
+}
+
 fn test_memcpy() {
     unsafe {
         let src = [1i8, 2, 3];
@@ -401,6 +430,39 @@ fn test_reallocarray() {
     }
 }
 
+fn test_posix_spawn_setup() {
+    use std::mem::MaybeUninit;
+
+    // Preparing the arguments of `posix_spawn` works, even though spawning itself does not.
+    unsafe {
+        let mut actions = MaybeUninit::<libc::posix_spawn_file_actions_t>::uninit();
+        assert_eq!(libc::posix_spawn_file_actions_init(actions.as_mut_ptr()), 0);
+        let path = b"/dev/null\0";
+        assert_eq!(
+            libc::posix_spawn_file_actions_addopen(
+                actions.as_mut_ptr(),
+                0,
+                path.as_ptr().cast(),
+                libc::O_RDONLY,
+                0,
+            ),
+            0
+        );
+        assert_eq!(libc::posix_spawn_file_actions_adddup2(actions.as_mut_ptr(), 1, 2), 0);
+        assert_eq!(libc::posix_spawn_file_actions_addclose(actions.as_mut_ptr(), 3), 0);
+        assert_eq!(libc::posix_spawn_file_actions_destroy(actions.as_mut_ptr()), 0);
+
+        let mut attr = MaybeUninit::<libc::posix_spawnattr_t>::uninit();
+        assert_eq!(libc::posix_spawnattr_init(attr.as_mut_ptr()), 0);
+        assert_eq!(
+            libc::posix_spawnattr_setflags(attr.as_mut_ptr(), libc::POSIX_SPAWN_SETPGROUP as _),
+            0
+        );
+        assert_eq!(libc::posix_spawnattr_setpgroup(attr.as_mut_ptr(), 0), 0);
+        assert_eq!(libc::posix_spawnattr_destroy(attr.as_mut_ptr()), 0);
+    }
+}
+
 fn main() {
     test_posix_gettimeofday();
 
@@ -412,6 +474,9 @@ fn main() {
     test_localtime_r();
 
     test_isatty();
+    #[cfg(target_os = "linux")]
+    test_isatty_pipe();
+    test_winsize();
 
     test_clocks();
 
@@ -420,6 +485,8 @@ fn main() {
     test_memcpy();
     test_strcpy();
 
+    test_posix_spawn_setup();
+
     #[cfg(not(target_os = "macos"))] // reallocarray does not exist on macOS
     test_reallocarray();
 
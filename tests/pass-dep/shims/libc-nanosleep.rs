@@ -0,0 +1,92 @@
+//@ignore-target-windows: No libc on Windows
+//@ignore-target-apple: `clock_nanosleep` does not exist on macOS
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    test_nanosleep_threads();
+    test_clock_nanosleep();
+    test_clock_nanosleep_abstime();
+    test_clock_nanosleep_errors();
+}
+
+fn timespec(duration: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: duration.as_secs().try_into().unwrap(),
+        tv_nsec: duration.subsec_nanos().try_into().unwrap(),
+    }
+}
+
+fn monotonic_now() -> Duration {
+    let mut tp = std::mem::MaybeUninit::<libc::timespec>::uninit();
+    assert_eq!(unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, tp.as_mut_ptr()) }, 0);
+    let tp = unsafe { tp.assume_init() };
+    Duration::new(tp.tv_sec.try_into().unwrap(), tp.tv_nsec.try_into().unwrap())
+}
+
+fn test_nanosleep_threads() {
+    static PROGRESS: AtomicBool = AtomicBool::new(false);
+
+    let start = monotonic_now();
+    let sleeper = thread::spawn(|| {
+        let req = timespec(Duration::from_secs(1));
+        assert_eq!(unsafe { libc::nanosleep(&req, std::ptr::null_mut()) }, 0);
+        // The other thread got to run while we were asleep.
+        assert!(PROGRESS.load(Ordering::Relaxed));
+    });
+    let worker = thread::spawn(|| PROGRESS.store(true, Ordering::Relaxed));
+    sleeper.join().unwrap();
+    worker.join().unwrap();
+    // The virtual clock advanced by at least the time we slept.
+    assert!(monotonic_now() - start >= Duration::from_secs(1));
+}
+
+fn test_clock_nanosleep() {
+    let start = monotonic_now();
+    let req = timespec(Duration::from_millis(100));
+    let res =
+        unsafe { libc::clock_nanosleep(libc::CLOCK_MONOTONIC, 0, &req, std::ptr::null_mut()) };
+    assert_eq!(res, 0);
+    assert!(monotonic_now() - start >= Duration::from_millis(100));
+}
+
+fn test_clock_nanosleep_abstime() {
+    let deadline = monotonic_now() + Duration::from_millis(100);
+    let req = timespec(deadline);
+    let res = unsafe {
+        libc::clock_nanosleep(
+            libc::CLOCK_MONOTONIC,
+            libc::TIMER_ABSTIME,
+            &req,
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(res, 0);
+    assert!(monotonic_now() >= deadline);
+
+    // A deadline in the past returns immediately.
+    let res = unsafe {
+        libc::clock_nanosleep(
+            libc::CLOCK_MONOTONIC,
+            libc::TIMER_ABSTIME,
+            &req,
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(res, 0);
+}
+
+fn test_clock_nanosleep_errors() {
+    // Errors are returned directly, not via `errno`.
+    let req = libc::timespec { tv_sec: 0, tv_nsec: 1_000_000_000 };
+    let res =
+        unsafe { libc::clock_nanosleep(libc::CLOCK_MONOTONIC, 0, &req, std::ptr::null_mut()) };
+    assert_eq!(res, libc::EINVAL);
+
+    let req = timespec(Duration::from_millis(1));
+    let res =
+        unsafe { libc::clock_nanosleep(libc::CLOCK_MONOTONIC, !0, &req, std::ptr::null_mut()) };
+    assert_eq!(res, libc::EINVAL);
+}
@@ -0,0 +1,238 @@
+//@ignore-target-windows: No libc pipe on Windows
+
+use std::io::Error;
+
+fn main() {
+    test_pipe();
+    test_pipe_eof();
+    test_pipe_epipe();
+    test_pipe_writev();
+    test_pipe_fcntl_flags();
+    test_pipe_fstat();
+    #[cfg(target_os = "linux")]
+    test_pipe2_nonblock();
+    #[cfg(target_os = "linux")]
+    test_pipe_size();
+}
+
+fn pipe() -> [i32; 2] {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::pipe(fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    fds
+}
+
+fn read(fd: i32, buf: &mut [u8]) -> isize {
+    unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) }
+}
+
+fn write(fd: i32, buf: &[u8]) -> isize {
+    unsafe { libc::write(fd, buf.as_ptr().cast(), buf.len()) }
+}
+
+fn test_pipe() {
+    let [reader, writer] = pipe();
+
+    // Partially drain the pipe, then fill it some more, and drain the rest.
+    assert_eq!(write(writer, b"hello"), 5);
+    let mut buf = [0u8; 3];
+    assert_eq!(read(reader, &mut buf), 3);
+    assert_eq!(&buf, b"hel");
+    assert_eq!(write(writer, b" world"), 6);
+    let mut buf = [0u8; 16];
+    assert_eq!(read(reader, &mut buf), 8);
+    assert_eq!(&buf[..8], b"lo world");
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_pipe_eof() {
+    let [reader, writer] = pipe();
+
+    assert_eq!(write(writer, b"abc"), 3);
+    // Also close a duplicate of the write end, to check that it refers to the same pipe.
+    let writer2 = unsafe { libc::fcntl(writer, libc::F_DUPFD, 0) };
+    assert!(writer2 >= 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    assert_eq!(unsafe { libc::close(writer2) }, 0);
+
+    // The data still in the pipe can be read, then reads return EOF.
+    let mut buf = [0u8; 5];
+    assert_eq!(read(reader, &mut buf), 3);
+    assert_eq!(&buf[..3], b"abc");
+    assert_eq!(read(reader, &mut buf), 0);
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+}
+
+fn test_pipe_epipe() {
+    let [reader, writer] = pipe();
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+
+    // Writing to a pipe without read ends fails with EPIPE.
+    // (The Rust runtime ignores SIGPIPE, so this does not kill the process on a real system.)
+    assert_eq!(write(writer, b"abc"), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPIPE));
+
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_pipe_writev() {
+    let [reader, writer] = pipe();
+
+    // Write three small buffers into the pipe at once, and read them back.
+    let bufs: [&[u8]; 3] = [b"ab", b"cde", b"f"];
+    let iov = bufs.map(|buf| libc::iovec { iov_base: buf.as_ptr() as *mut _, iov_len: buf.len() });
+    let res = unsafe { libc::writev(writer, iov.as_ptr(), 3) };
+    assert_eq!(res, 6);
+    let mut buf = [0u8; 6];
+    assert_eq!(read(reader, &mut buf), 6);
+    assert_eq!(&buf, b"abcdef");
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_pipe_fcntl_flags() {
+    let [reader, writer] = pipe();
+
+    // The access mode of both ends is reported, and they start out blocking.
+    let flags = unsafe { libc::fcntl(reader, libc::F_GETFL) };
+    assert_eq!(flags & libc::O_ACCMODE, libc::O_RDONLY);
+    assert_eq!(flags & libc::O_NONBLOCK, 0);
+    let flags = unsafe { libc::fcntl(writer, libc::F_GETFL) };
+    assert_eq!(flags & libc::O_ACCMODE, libc::O_WRONLY);
+    assert_eq!(flags & libc::O_NONBLOCK, 0);
+
+    // Make the read end nonblocking, and check that the flag sticks.
+    let flags = unsafe { libc::fcntl(reader, libc::F_GETFL) };
+    let res = unsafe { libc::fcntl(reader, libc::F_SETFL, flags | libc::O_NONBLOCK) };
+    assert_eq!(res, 0);
+    let flags = unsafe { libc::fcntl(reader, libc::F_GETFL) };
+    assert_eq!(flags & libc::O_NONBLOCK, libc::O_NONBLOCK);
+
+    // The access mode and file creation flags are ignored, so the flags that were used to
+    // open a file can be passed back with `O_NONBLOCK` added.
+    let flags = libc::O_RDONLY | libc::O_CREAT | libc::O_TRUNC | libc::O_CLOEXEC;
+    let res = unsafe { libc::fcntl(reader, libc::F_SETFL, flags | libc::O_NONBLOCK) };
+    assert_eq!(res, 0);
+    let flags = unsafe { libc::fcntl(reader, libc::F_GETFL) };
+    assert_eq!(flags & (libc::O_NONBLOCK | libc::O_CREAT), libc::O_NONBLOCK);
+    #[cfg(target_os = "linux")]
+    {
+        let res = unsafe { libc::fcntl(reader, libc::F_SETFL, flags | libc::O_LARGEFILE) };
+        assert_eq!(res, 0);
+    }
+
+    // Reading from the empty pipe now fails with EAGAIN.
+    let mut buf = [0u8; 5];
+    assert_eq!(read(reader, &mut buf), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+
+    // A duplicate refers to the same open file description, so it shares the flag, but the
+    // write end does not.
+    let dup = unsafe { libc::dup(reader) };
+    assert!(dup >= 0);
+    let flags = unsafe { libc::fcntl(dup, libc::F_GETFL) };
+    assert_eq!(flags & libc::O_NONBLOCK, libc::O_NONBLOCK);
+    let flags = unsafe { libc::fcntl(writer, libc::F_GETFL) };
+    assert_eq!(flags & libc::O_NONBLOCK, 0);
+
+    // The flag can also be cleared again, through either file descriptor.
+    let flags = unsafe { libc::fcntl(dup, libc::F_GETFL) };
+    let res = unsafe { libc::fcntl(dup, libc::F_SETFL, flags & !libc::O_NONBLOCK) };
+    assert_eq!(res, 0);
+    let flags = unsafe { libc::fcntl(reader, libc::F_GETFL) };
+    assert_eq!(flags & libc::O_NONBLOCK, 0);
+
+    assert_eq!(unsafe { libc::close(dup) }, 0);
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_pipe_fstat() {
+    let [reader, writer] = pipe();
+
+    // Both ends are reported as FIFOs, even with isolation enabled.
+    for fd in [reader, writer] {
+        let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+        assert_eq!(unsafe { libc::fstat(fd, stat.as_mut_ptr()) }, 0);
+        let stat = unsafe { stat.assume_init() };
+        assert_eq!(stat.st_mode & libc::S_IFMT, libc::S_IFIFO);
+    }
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn test_pipe2_nonblock() {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) };
+    assert_eq!(res, 0);
+    let [reader, writer] = fds;
+
+    // Reading from an empty pipe fails with EAGAIN.
+    let mut buf = [0u8; 5];
+    assert_eq!(read(reader, &mut buf), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+    let flags = unsafe { libc::fcntl(reader, libc::F_GETFL) };
+    assert_eq!(flags & libc::O_NONBLOCK, libc::O_NONBLOCK);
+
+    assert_eq!(write(writer, b"abc"), 3);
+    assert_eq!(read(reader, &mut buf), 3);
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn test_pipe_size() {
+    let [reader, writer] = pipe();
+
+    // New pipes have the default capacity of 16 pages.
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_GETPIPE_SZ) }, 65536);
+
+    // The requested size is rounded up to a power of two, and shared by both ends.
+    assert_eq!(unsafe { libc::fcntl(writer, libc::F_SETPIPE_SZ, 10000) }, 16384);
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_GETPIPE_SZ) }, 16384);
+    assert_eq!(unsafe { libc::fcntl(writer, libc::F_GETPIPE_SZ) }, 16384);
+
+    // Sizes below a page are rounded up to a page.
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_SETPIPE_SZ, 1) }, 4096);
+
+    // Sizes above `/proc/sys/fs/pipe-max-size` need privileges.
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_SETPIPE_SZ, 1 << 20) }, 1 << 20);
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_SETPIPE_SZ, (1 << 20) + 1) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPERM));
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_GETPIPE_SZ) }, 1 << 20);
+
+    // The pipe cannot shrink below the amount of buffered data.
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_SETPIPE_SZ, 8192) }, 8192);
+    assert_eq!(write(writer, &[0; 5000]), 5000);
+    assert_eq!(unsafe { libc::fcntl(reader, libc::F_SETPIPE_SZ, 4096) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBUSY));
+
+    // Non-blocking writes are limited by the capacity.
+    assert_eq!(unsafe { libc::fcntl(writer, libc::F_SETFL, libc::O_NONBLOCK) }, 0);
+    assert_eq!(write(writer, &[0; 5000]), 3192);
+    assert_eq!(write(writer, &[0; 1]), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+    // Writes of up to `PIPE_BUF` bytes are not split.
+    let mut buf = [0; 100];
+    assert_eq!(read(reader, &mut buf), 100);
+    assert_eq!(write(writer, &[0; 200]), -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+    assert_eq!(write(writer, &[0; 100]), 100);
+
+    // Other file descriptions are not pipes.
+    assert_eq!(unsafe { libc::fcntl(libc::STDERR_FILENO, libc::F_GETPIPE_SZ) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    assert_eq!(unsafe { libc::fcntl(libc::STDERR_FILENO, libc::F_SETPIPE_SZ, 4096) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
@@ -0,0 +1,86 @@
+//@ignore-target-windows: No libc poll on Windows
+// We are making scheduler assumptions here.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::thread;
+
+fn main() {
+    test_poll_immediate();
+    test_poll_timeout();
+    test_poll_wakeup();
+}
+
+fn pipe() -> [i32; 2] {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::pipe(fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    fds
+}
+
+fn pollfd(fd: i32, events: i16) -> libc::pollfd {
+    libc::pollfd { fd, events, revents: 0 }
+}
+
+fn test_poll_immediate() {
+    let [reader, writer] = pipe();
+
+    let mut fds = [
+        pollfd(reader, libc::POLLIN),
+        pollfd(writer, libc::POLLOUT),
+        // Negative file descriptors are ignored.
+        pollfd(-1, libc::POLLIN),
+        // File descriptors that are not open are reported as invalid.
+        pollfd(100, libc::POLLIN),
+    ];
+    // The pipe is empty, so only the write end is ready.
+    let res = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, 0) };
+    assert_eq!(res, 2);
+    assert_eq!(fds[0].revents, 0);
+    assert_eq!(fds[1].revents, libc::POLLOUT);
+    assert_eq!(fds[2].revents, 0);
+    assert_eq!(fds[3].revents, libc::POLLNVAL);
+
+    // After a write, the read end becomes ready as well.
+    assert_eq!(unsafe { libc::write(writer, b"abc".as_ptr().cast(), 3) }, 3);
+    let res = unsafe { libc::poll(fds.as_mut_ptr(), 2, 0) };
+    assert_eq!(res, 2);
+    assert_eq!(fds[0].revents, libc::POLLIN);
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_poll_timeout() {
+    let [reader, writer] = pipe();
+
+    // Nothing becomes ready, so this times out.
+    let mut fds = [pollfd(reader, libc::POLLIN)];
+    let res = unsafe { libc::poll(fds.as_mut_ptr(), 1, 10) };
+    assert_eq!(res, 0);
+    assert_eq!(fds[0].revents, 0);
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_poll_wakeup() {
+    let [reader, writer] = pipe();
+
+    let thread = thread::spawn(move || {
+        thread::yield_now();
+        assert_eq!(unsafe { libc::write(writer, b"abc".as_ptr().cast(), 3) }, 3);
+    });
+
+    // Wait until the other thread writes to the pipe.
+    let mut fds = [pollfd(reader, libc::POLLIN)];
+    let res = unsafe { libc::poll(fds.as_mut_ptr(), 1, -1) };
+    assert_eq!(res, 1);
+    assert_eq!(fds[0].revents, libc::POLLIN);
+    let mut buf = [0u8; 3];
+    assert_eq!(unsafe { libc::read(reader, buf.as_mut_ptr().cast(), 3) }, 3);
+    assert_eq!(&buf, b"abc");
+
+    thread.join().unwrap();
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
@@ -0,0 +1,45 @@
+//@only-target-linux
+
+use std::ffi::CStr;
+use std::io::Error;
+use std::thread;
+
+fn get_name() -> Vec<u8> {
+    let mut buf = [0u8; 16];
+    assert_eq!(unsafe { libc::prctl(libc::PR_GET_NAME, buf.as_mut_ptr()) }, 0);
+    CStr::from_bytes_until_nul(&buf).unwrap().to_bytes().to_owned()
+}
+
+fn set_name(name: &CStr) {
+    assert_eq!(unsafe { libc::prctl(libc::PR_SET_NAME, name.as_ptr()) }, 0);
+}
+
+fn main() {
+    set_name(c"main-thread");
+
+    thread::spawn(|| {
+        set_name(c"worker");
+        assert_eq!(get_name(), b"worker");
+
+        // The name is visible through `pthread_getname_np` as well.
+        let mut buf = [0u8; 16];
+        let res = unsafe {
+            libc::pthread_getname_np(libc::pthread_self(), buf.as_mut_ptr().cast(), buf.len())
+        };
+        assert_eq!(res, 0);
+        assert_eq!(CStr::from_bytes_until_nul(&buf).unwrap().to_bytes(), b"worker");
+
+        // Long names are silently truncated to 15 bytes.
+        set_name(c"a-very-long-thread-name");
+        assert_eq!(get_name(), b"a-very-long-thr");
+    })
+    .join()
+    .unwrap();
+
+    // Names are per-thread.
+    assert_eq!(get_name(), b"main-thread");
+
+    // Other options are rejected.
+    assert_eq!(unsafe { libc::prctl(-1, 0) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+}
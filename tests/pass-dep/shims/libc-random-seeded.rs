@@ -0,0 +1,57 @@
+//@ignore-target-windows: no libc
+//@compile-flags: -Zmiri-seed=42
+
+// Under isolation, all randomness shims draw from the seeded machine RNG, so these buffers are
+// filled with the same bytes on every run.
+
+// on macOS this is not in the `libc` crate.
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn getentropy(bytes: *mut libc::c_void, count: libc::size_t) -> libc::c_int;
+}
+
+#[cfg(not(target_os = "macos"))]
+use libc::getentropy;
+
+// glibc only gained this in 2.36, so the `libc` crate does not declare it on Linux.
+extern "C" {
+    fn arc4random_buf(buf: *mut libc::c_void, nbytes: libc::size_t);
+}
+
+fn check_filled(fill: impl Fn(&mut [u8])) {
+    let mut buf1 = [0u8; 64];
+    let mut buf2 = [0u8; 64];
+    fill(&mut buf1);
+    fill(&mut buf2);
+    // The chance of 64 random bytes all being zero, or of two draws being equal, is negligible.
+    assert!(buf1.iter().any(|&b| b != 0));
+    assert_ne!(buf1, buf2);
+}
+
+fn main() {
+    check_filled(|buf| unsafe {
+        assert_eq!(getentropy(buf.as_mut_ptr() as *mut libc::c_void, buf.len()), 0);
+    });
+    check_filled(|buf| unsafe {
+        arc4random_buf(buf.as_mut_ptr() as *mut libc::c_void, buf.len());
+    });
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    check_filled(|buf| unsafe {
+        assert_eq!(libc::getrandom(buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0), 64);
+    });
+    #[cfg(target_os = "linux")]
+    check_filled(|buf| unsafe {
+        assert_eq!(
+            libc::syscall(
+                libc::SYS_getrandom,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                0 as libc::c_uint,
+            ),
+            64,
+        );
+    });
+
+    // A zero-length request never touches the buffer, so it may even be null.
+    unsafe { arc4random_buf(std::ptr::null_mut(), 0) };
+}
@@ -0,0 +1,171 @@
+//@ignore-target-windows: No libc socketpair on Windows
+
+use std::io::Error;
+
+fn main() {
+    test_socketpair();
+    test_socketpair_peer_closed();
+    test_socketpair_getsockname();
+    test_socketpair_shutdown();
+    #[cfg(target_os = "linux")]
+    test_socketpair_nonblock();
+}
+
+fn test_socketpair() {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+
+    // Write to one end and read from the other.
+    let data = "abcde".as_bytes().as_ptr();
+    let res = unsafe { libc::write(fds[0], data as *const libc::c_void, 5) };
+    assert_eq!(res, 5);
+    let mut buf: [u8; 5] = [0; 5];
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 5);
+    assert_eq!(&buf, b"abcde");
+
+    // It also works the other way around, and partial reads leave the rest in the buffer.
+    let data = "12345".as_bytes().as_ptr();
+    let res = unsafe { libc::write(fds[1], data as *const libc::c_void, 5) };
+    assert_eq!(res, 5);
+    let mut buf: [u8; 3] = [0; 3];
+    let res = unsafe { libc::read(fds[0], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 3);
+    assert_eq!(&buf, b"123");
+    let res = unsafe { libc::read(fds[0], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 2);
+    assert_eq!(&buf[..2], b"45");
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
+
+fn test_socketpair_peer_closed() {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+
+    let data = "abc".as_bytes().as_ptr();
+    let res = unsafe { libc::write(fds[0], data as *const libc::c_void, 3) };
+    assert_eq!(res, 3);
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+
+    // Data written before the peer was closed can still be read, then we get EOF.
+    let mut buf: [u8; 5] = [0; 5];
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 3);
+    assert_eq!(&buf[..3], b"abc");
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 0);
+
+    // Writing to a socket whose peer is closed fails with EPIPE.
+    // (The Rust runtime ignores SIGPIPE, so this does not kill the process on a real system.)
+    let res = unsafe { libc::write(fds[1], data as *const libc::c_void, 3) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPIPE));
+
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
+
+fn test_socketpair_getsockname() {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+
+    // Both the socket and its peer are unnamed, so the address is just the family.
+    let unnamed_len = std::mem::offset_of!(libc::sockaddr_un, sun_path) as libc::socklen_t;
+    for getname in [libc::getsockname, libc::getpeername] {
+        let mut addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+        let res =
+            unsafe { getname(fds[0], (&mut addr as *mut libc::sockaddr_un).cast(), &mut len) };
+        assert_eq!(res, 0);
+        assert_eq!(len, unnamed_len);
+        assert_eq!(addr.sun_family, libc::AF_UNIX as libc::sa_family_t);
+    }
+
+    // Other file descriptors are not sockets.
+    let mut addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+    let res = unsafe {
+        libc::getsockname(
+            libc::STDERR_FILENO,
+            (&mut addr as *mut libc::sockaddr_un).cast(),
+            &mut len,
+        )
+    };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOTSOCK));
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
+
+fn test_socketpair_shutdown() {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+
+    let data = "abc".as_bytes().as_ptr();
+    let res = unsafe { libc::write(fds[0], data as *const libc::c_void, 3) };
+    assert_eq!(res, 3);
+    assert_eq!(unsafe { libc::shutdown(fds[0], libc::SHUT_WR) }, 0);
+
+    // The peer can read what was written before, then gets EOF.
+    let mut buf: [u8; 5] = [0; 5];
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 3);
+    assert_eq!(&buf[..3], b"abc");
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 0);
+
+    // Writing in the shut down direction fails with EPIPE, but the other direction still works.
+    let res = unsafe { libc::write(fds[0], data as *const libc::c_void, 3) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPIPE));
+    let res = unsafe { libc::write(fds[1], data as *const libc::c_void, 3) };
+    assert_eq!(res, 3);
+    let res = unsafe { libc::read(fds[0], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 3);
+
+    // SHUT_RDWR closes the remaining direction, without closing the file descriptor.
+    assert_eq!(unsafe { libc::shutdown(fds[1], libc::SHUT_RDWR) }, 0);
+    let res = unsafe { libc::read(fds[0], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 0);
+    let res = unsafe { libc::write(fds[1], data as *const libc::c_void, 3) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPIPE));
+
+    // Invalid arguments are rejected.
+    assert_eq!(unsafe { libc::shutdown(fds[0], -1) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    assert_eq!(unsafe { libc::shutdown(libc::STDERR_FILENO, libc::SHUT_RDWR) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOTSOCK));
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn test_socketpair_nonblock() {
+    let mut fds = [-1, -1];
+    let res = unsafe {
+        libc::socketpair(
+            libc::AF_UNIX,
+            libc::SOCK_STREAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+            0,
+            fds.as_mut_ptr(),
+        )
+    };
+    assert_eq!(res, 0);
+
+    // Reading from an empty socket fails with EAGAIN.
+    let mut buf: [u8; 5] = [0; 5];
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
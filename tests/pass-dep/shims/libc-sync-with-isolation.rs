@@ -0,0 +1,27 @@
+//@only-target-linux: sync_file_range only on Linux
+//@compile-flags: -Zmiri-isolation-error=warn-nobacktrace
+
+use std::io::Error;
+
+fn main() {
+    // `fsync`, `fdatasync` and `sync_file_range` all fail the same way under isolation...
+    unsafe {
+        assert_eq!(libc::fsync(1), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPERM));
+        assert_eq!(libc::fdatasync(1), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPERM));
+        assert_eq!(libc::sync_file_range(1, 0, 0, 0), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPERM));
+    }
+
+    // ...unless the file only lives in memory, as there is no host state to synchronize then.
+    unsafe {
+        let fd = libc::memfd_create(b"miri-test\0".as_ptr().cast(), libc::MFD_CLOEXEC);
+        assert!(fd >= 0);
+        assert_eq!(libc::write(fd, b"hello".as_ptr().cast(), 5), 5);
+        assert_eq!(libc::fsync(fd), 0);
+        assert_eq!(libc::fdatasync(fd), 0);
+        assert_eq!(libc::sync_file_range(fd, 0, 0, 0), 0);
+        assert_eq!(libc::close(fd), 0);
+    }
+}
@@ -0,0 +1,51 @@
+//@ignore-target-windows: No libc on Windows
+//@ignore-target-freebsd: Neither `gettid` nor `pthread_threadid_np` exist on FreeBSD
+
+use std::thread;
+
+#[cfg(target_os = "linux")]
+fn thread_id() -> u64 {
+    let tid = unsafe { libc::gettid() };
+    // `gettid` can also be invoked as a raw syscall.
+    assert_eq!(unsafe { libc::syscall(libc::SYS_gettid) }, tid.into());
+    tid.try_into().unwrap()
+}
+
+#[cfg(target_os = "macos")]
+fn thread_id() -> u64 {
+    let mut tid = 0;
+    assert_eq!(unsafe { libc::pthread_threadid_np(libc::pthread_self(), &mut tid) }, 0);
+    tid
+}
+
+fn main() {
+    let main_id = thread_id();
+    assert_ne!(main_id, 0);
+    // On the main thread, a null thread refers to the main thread itself.
+    #[cfg(target_os = "macos")]
+    {
+        let mut tid = 0;
+        assert_eq!(unsafe { libc::pthread_threadid_np(0, &mut tid) }, 0);
+        assert_eq!(tid, main_id);
+    }
+    // The id is stable across calls.
+    assert_eq!(thread_id(), main_id);
+
+    let (id1, id2) = thread::scope(|s| {
+        let t1 = s.spawn(|| {
+            let id = thread_id();
+            assert_eq!(thread_id(), id);
+            id
+        });
+        let t2 = s.spawn(|| {
+            let id = thread_id();
+            assert_eq!(thread_id(), id);
+            id
+        });
+        (t1.join().unwrap(), t2.join().unwrap())
+    });
+    assert_ne!(id1, id2);
+    assert_ne!(id1, main_id);
+    assert_ne!(id2, main_id);
+    assert_eq!(thread_id(), main_id);
+}
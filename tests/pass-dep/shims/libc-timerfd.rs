@@ -0,0 +1,101 @@
+//@only-target-linux
+
+use std::io::Error;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    test_timerfd_one_shot();
+    test_timerfd_poll();
+    test_timerfd_disarm();
+}
+
+fn timerfd_create() -> i32 {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert!(fd >= 0);
+    fd
+}
+
+fn itimerspec(value: Duration, interval: Duration) -> libc::itimerspec {
+    let timespec = |duration: Duration| {
+        libc::timespec {
+            tv_sec: duration.as_secs().try_into().unwrap(),
+            tv_nsec: duration.subsec_nanos().try_into().unwrap(),
+        }
+    };
+    libc::itimerspec { it_value: timespec(value), it_interval: timespec(interval) }
+}
+
+fn settime(fd: i32, value: Duration, interval: Duration) {
+    let new_value = itimerspec(value, interval);
+    let res = unsafe { libc::timerfd_settime(fd, 0, &new_value, std::ptr::null_mut()) };
+    assert_eq!(res, 0);
+}
+
+/// Reads the number of expirations, or returns the error.
+fn read(fd: i32) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    if res < 0 {
+        return Err(Error::last_os_error());
+    }
+    assert_eq!(res, 8);
+    Ok(u64::from_ne_bytes(buf))
+}
+
+fn test_timerfd_one_shot() {
+    let fd = timerfd_create();
+    settime(fd, Duration::from_millis(10), Duration::ZERO);
+
+    // The timer did not expire yet.
+    assert_eq!(read(fd).unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+    let mut curr_value = itimerspec(Duration::ZERO, Duration::ZERO);
+    assert_eq!(unsafe { libc::timerfd_gettime(fd, &mut curr_value) }, 0);
+    assert!(curr_value.it_value.tv_sec > 0 || curr_value.it_value.tv_nsec > 0);
+
+    // After the timeout, it expired exactly once.
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(read(fd).unwrap(), 1);
+    assert_eq!(read(fd).unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+    assert_eq!(unsafe { libc::timerfd_gettime(fd, &mut curr_value) }, 0);
+    assert_eq!((curr_value.it_value.tv_sec, curr_value.it_value.tv_nsec), (0, 0));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_timerfd_poll() {
+    let fd = timerfd_create();
+    settime(fd, Duration::from_millis(10), Duration::from_millis(10));
+
+    // Waiting for the timer lets time pass until it expires.
+    let mut fds = [libc::pollfd { fd, events: libc::POLLIN, revents: 0 }];
+    let res = unsafe { libc::poll(fds.as_mut_ptr(), 1, -1) };
+    assert_eq!(res, 1);
+    assert_eq!(fds[0].revents, libc::POLLIN);
+    assert!(read(fd).unwrap() >= 1);
+
+    // A periodic timer keeps expiring.
+    thread::sleep(Duration::from_millis(50));
+    assert!(read(fd).unwrap() >= 1);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_timerfd_disarm() {
+    let fd = timerfd_create();
+    settime(fd, Duration::from_millis(10), Duration::ZERO);
+    // A zero value disarms the timer, and the previous setting is reported.
+    let new_value = itimerspec(Duration::ZERO, Duration::ZERO);
+    let mut old_value = itimerspec(Duration::ZERO, Duration::ZERO);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &new_value, &mut old_value) }, 0);
+    assert!(old_value.it_value.tv_sec > 0 || old_value.it_value.tv_nsec > 0);
+
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(read(fd).unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+
+    // Only timer file descriptors can be armed.
+    assert_eq!(unsafe { libc::timerfd_settime(0, 0, &new_value, std::ptr::null_mut()) }, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
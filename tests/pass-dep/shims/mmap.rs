@@ -42,6 +42,26 @@ fn test_mmap<Offset: Default>(
     let res = unsafe { libc::munmap(ptr, page_size) };
     assert_eq!(res, 0i32);
 
+    // Mappings are page-aligned, and their length is rounded up to whole pages,
+    // both when mapping and when unmapping.
+    let ptr = unsafe {
+        mmap(
+            ptr::null_mut(),
+            page_size + 1,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            Default::default(),
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    assert_eq!(ptr.addr() % page_size, 0);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, page_size * 2) };
+    assert!(slice.iter().all(|b| *b == 0));
+    slice[page_size * 2 - 1] = 1;
+    let res = unsafe { libc::munmap(ptr, page_size + 1) };
+    assert_eq!(res, 0i32);
+
     // Test all of our error conditions
     let ptr = unsafe {
         mmap(
@@ -83,8 +103,8 @@ fn test_mmap<Offset: Default>(
     assert_eq!(ptr, libc::MAP_FAILED);
     assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::ENOTSUP);
 
-    // We don't support protections other than read+write
-    for prot in [libc::PROT_NONE, libc::PROT_EXEC, libc::PROT_READ, libc::PROT_WRITE] {
+    // We don't support executable mappings
+    for prot in [libc::PROT_EXEC, libc::PROT_READ | libc::PROT_EXEC] {
         let ptr = unsafe {
             mmap(
                 ptr::null_mut(),
@@ -172,10 +192,149 @@ fn test_mremap() {
     assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
 }
 
+fn test_madvise() {
+    let page_size = page_size::get();
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size * 2,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, page_size * 2) };
+    for b in slice.iter_mut() {
+        *b = 1;
+    }
+
+    // Advice without an observable effect leaves the contents alone.
+    let res = unsafe { libc::madvise(ptr, page_size * 2, libc::MADV_WILLNEED) };
+    assert_eq!(res, 0);
+    assert!(slice.iter().all(|b| *b == 1));
+
+    // On Linux, MADV_DONTNEED resets the second page (and only that) to zeros.
+    let res = unsafe { libc::madvise(ptr.byte_add(page_size), page_size, libc::MADV_DONTNEED) };
+    assert_eq!(res, 0);
+    #[cfg(target_os = "linux")]
+    {
+        assert!(&slice[..page_size].iter().all(|b| *b == 1));
+        assert!(&slice[page_size..].iter().all(|b| *b == 0));
+    }
+
+    // The zeroing is done by the kernel, so it also works on pages the program cannot write.
+    let res = unsafe { libc::mprotect(ptr, page_size, libc::PROT_READ) };
+    assert_eq!(res, 0);
+    let res = unsafe { libc::madvise(ptr, page_size, libc::MADV_DONTNEED) };
+    assert_eq!(res, 0);
+    #[cfg(target_os = "linux")]
+    assert!(&slice[..page_size].iter().all(|b| *b == 0));
+    let res = unsafe { libc::mprotect(ptr, page_size, libc::PROT_NONE) };
+    assert_eq!(res, 0);
+    let res = unsafe { libc::madvise(ptr, page_size * 2, libc::MADV_DONTNEED) };
+    assert_eq!(res, 0);
+    let res = unsafe { libc::mprotect(ptr, page_size, libc::PROT_READ | libc::PROT_WRITE) };
+    assert_eq!(res, 0);
+
+    // Test all of our error conditions
+    // Not aligned
+    let res = unsafe { libc::madvise(ptr.byte_add(1), page_size, libc::MADV_DONTNEED) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
+
+    // Unknown advice
+    let res = unsafe { libc::madvise(ptr, page_size, -1) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
+
+    // Range extends beyond the mapping
+    let res = unsafe { libc::madvise(ptr, page_size * 3, libc::MADV_DONTNEED) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::ENOMEM);
+
+    let res = unsafe { libc::munmap(ptr, page_size * 2) };
+    assert_eq!(res, 0i32);
+}
+
+fn test_mprotect() {
+    let page_size = page_size::get();
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size * 2,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, page_size * 2) };
+    slice.fill(1);
+
+    // A read-only page can still be read, and the other page is unaffected.
+    let res = unsafe { libc::mprotect(ptr, page_size, libc::PROT_READ) };
+    assert_eq!(res, 0);
+    assert_eq!(unsafe { *(ptr as *const u8) }, 1);
+    slice[page_size] = 2;
+
+    // Inaccessible pages can be made accessible again.
+    let res = unsafe { libc::mprotect(ptr, page_size * 2, libc::PROT_NONE) };
+    assert_eq!(res, 0);
+    let res = unsafe { libc::mprotect(ptr, page_size * 2, libc::PROT_READ | libc::PROT_WRITE) };
+    assert_eq!(res, 0);
+    slice[0] = 3;
+    assert_eq!(slice[0], 3);
+    assert_eq!(slice[page_size], 2);
+
+    // Test all of our error conditions
+    // Not aligned
+    let res = unsafe { libc::mprotect(ptr.byte_add(1), page_size, libc::PROT_READ) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
+
+    // Unknown protection bits
+    let res = unsafe { libc::mprotect(ptr, page_size, !0) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
+
+    // Range extends beyond the mapping
+    let res = unsafe { libc::mprotect(ptr, page_size * 3, libc::PROT_READ) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::ENOMEM);
+
+    let res = unsafe { libc::munmap(ptr, page_size * 2) };
+    assert_eq!(res, 0i32);
+
+    // Memory can be mapped without any access and made accessible later.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    let res = unsafe { libc::mprotect(ptr, page_size, libc::PROT_READ | libc::PROT_WRITE) };
+    assert_eq!(res, 0);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, page_size) };
+    assert!(slice.iter().all(|b| *b == 0));
+    slice.fill(1);
+    assert!(slice.iter().all(|b| *b == 1));
+
+    let res = unsafe { libc::munmap(ptr, page_size) };
+    assert_eq!(res, 0i32);
+}
+
 fn main() {
     test_mmap(libc::mmap);
     #[cfg(target_os = "linux")]
     test_mmap(libc::mmap64);
     #[cfg(target_os = "linux")]
     test_mremap();
+    test_madvise();
+    test_mprotect();
 }
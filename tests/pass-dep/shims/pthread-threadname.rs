@@ -22,20 +22,26 @@ fn main() {
         return unsafe { libc::pthread_setname_np(name.as_ptr().cast()) };
     }
 
+    fn get_thread_name(name: &mut [u8]) -> i32 {
+        #[cfg(not(target_os = "freebsd"))]
+        return unsafe {
+            libc::pthread_getname_np(libc::pthread_self(), name.as_mut_ptr().cast(), name.len())
+        };
+        #[cfg(target_os = "freebsd")]
+        unsafe {
+            // pthread_get_name_np does not return anything
+            libc::pthread_get_name_np(libc::pthread_self(), name.as_mut_ptr().cast(), name.len());
+            return 0;
+        };
+    }
+
     let result = thread::Builder::new().name(long_name.clone()).spawn(move || {
         // Rust remembers the full thread name itself.
         assert_eq!(thread::current().name(), Some(long_name.as_str()));
 
         // But the system is limited -- make sure we successfully set a truncation.
         let mut buf = vec![0u8; long_name.len() + 1];
-        #[cfg(not(target_os = "freebsd"))]
-        unsafe {
-            libc::pthread_getname_np(libc::pthread_self(), buf.as_mut_ptr().cast(), buf.len())
-        };
-        #[cfg(target_os = "freebsd")]
-        unsafe {
-            libc::pthread_get_name_np(libc::pthread_self(), buf.as_mut_ptr().cast(), buf.len())
-        };
+        assert_eq!(get_thread_name(&mut buf), 0);
         let cstr = CStr::from_bytes_until_nul(&buf).unwrap();
         assert!(cstr.to_bytes().len() >= 15, "name is too short: len={}", cstr.to_bytes().len()); // POSIX seems to promise at least 15 chars
         assert!(long_name.as_bytes().starts_with(cstr.to_bytes()));
@@ -48,4 +54,34 @@ fn main() {
         assert_ne!(set_thread_name(&CString::new(long_name).unwrap()), 0);
     });
     result.unwrap().join().unwrap();
+
+    // A short name round-trips unchanged.
+    thread::spawn(|| {
+        assert_eq!(set_thread_name(c"short name"), 0);
+        let mut buf = [0u8; 32];
+        assert_eq!(get_thread_name(&mut buf), 0);
+        assert_eq!(CStr::from_bytes_until_nul(&buf).unwrap(), c"short name");
+    })
+    .join()
+    .unwrap();
+
+    // On Linux, other threads can be named as well.
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::thread::JoinHandleExt;
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let handle = thread::spawn(move || {
+            rx.recv().unwrap();
+            let mut buf = [0u8; 16];
+            assert_eq!(get_thread_name(&mut buf), 0);
+            assert_eq!(CStr::from_bytes_until_nul(&buf).unwrap(), c"named by main");
+        });
+        let res =
+            unsafe { libc::pthread_setname_np(handle.as_pthread_t(), c"named by main".as_ptr()) };
+        assert_eq!(res, 0);
+        tx.send(()).unwrap();
+        handle.join().unwrap();
+    }
 }
@@ -750,4 +750,29 @@ pub fn libm() {
     let (val, sign) = (-0.5f64).ln_gamma();
     assert_approx_eq!(val, (2.0 * f64::consts::PI.sqrt()).ln());
     assert_eq!(sign, -1);
+
+    // Call the C functions directly, as C code linked into a Rust program would.
+    #[cfg(unix)]
+    {
+        extern "C" {
+            fn tgamma(x: f64) -> f64;
+            fn tgammaf(x: f32) -> f32;
+            fn lgamma_r(x: f64, sign: *mut i32) -> f64;
+            fn lgammaf_r(x: f32, sign: *mut i32) -> f32;
+        }
+
+        unsafe {
+            assert_approx_eq!(tgamma(5.0), 24.0);
+            assert_approx_eq!(tgammaf(5.0), 24.0);
+            // The poles of the gamma function produce NaN.
+            assert!(tgamma(-1.0).is_nan());
+            assert!(tgammaf(f32::NAN).is_nan());
+
+            let mut sign = 0;
+            assert_approx_eq!(lgamma_r(5.0, &mut sign), 24.0f64.ln());
+            assert_eq!(sign, 1);
+            assert_approx_eq!(lgammaf_r(-0.5, &mut sign), (2.0 * f32::consts::PI.sqrt()).ln());
+            assert_eq!(sign, -1);
+        }
+    }
 }
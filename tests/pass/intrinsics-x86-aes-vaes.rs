@@ -89,6 +89,48 @@ unsafe fn test_aes() {
         assert_eq_m128i(r, e);
     }
     test_mm_aesimc_si128();
+
+    #[target_feature(enable = "aes")]
+    unsafe fn test_aes128_fips197() {
+        // The AES-128 example from FIPS-197, appendix C.1. The key schedule is written out
+        // instead of being expanded with `_mm_aeskeygenassist_si128`.
+        fn block(bytes: u128) -> __m128i {
+            unsafe { transmute(bytes.to_be_bytes()) }
+        }
+        let round_keys = [
+            0x000102030405060708090a0b0c0d0e0f,
+            0xd6aa74fdd2af72fadaa678f1d6ab76fe,
+            0xb692cf0b643dbdf1be9bc5006830b3fe,
+            0xb6ff744ed2c2c9bf6c590cbf0469bf41,
+            0x47f7f7bc95353e03f96c32bcfd058dfd,
+            0x3caaa3e8a99f9deb50f3af57adf622aa,
+            0x5e390f7df7a69296a7553dc10aa31f6b,
+            0x14f9701ae35fe28c440adf4d4ea9c026,
+            0x47438735a41c65b9e016baf4aebf7ad2,
+            0x549932d1f08557681093ed9cbe2c974e,
+            0x13111d7fe3944a17f307a78b4d2b30c5,
+        ]
+        .map(block);
+        let plaintext = block(0x00112233445566778899aabbccddeeff);
+        let ciphertext = block(0x69c4e0d86a7b0430d8cdb78070b4c55a);
+
+        // Nine full rounds, and a last round without MixColumns.
+        let mut state = _mm_xor_si128(plaintext, round_keys[0]);
+        for &key in &round_keys[1..10] {
+            state = _mm_aesenc_si128(state, key);
+        }
+        state = _mm_aesenclast_si128(state, round_keys[10]);
+        assert_eq_m128i(state, ciphertext);
+
+        // The equivalent inverse cipher needs InvMixColumns applied to the inner round keys.
+        let mut state = _mm_xor_si128(ciphertext, round_keys[10]);
+        for &key in round_keys[1..10].iter().rev() {
+            state = _mm_aesdec_si128(state, _mm_aesimc_si128(key));
+        }
+        state = _mm_aesdeclast_si128(state, round_keys[0]);
+        assert_eq_m128i(state, plaintext);
+    }
+    test_aes128_fips197();
 }
 
 // The constants in the tests below are just bit patterns. They should not
@@ -9,6 +9,8 @@
 //@ignore-target-wasm32
 //@compile-flags: -C target-feature=+avx
 
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
 #[cfg(target_arch = "x86")]
 use std::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
@@ -480,6 +482,16 @@ unsafe fn test_avx() {
         let r = _mm256_dp_ps::<0xFF>(a, b);
         let e = _mm256_setr_ps(200., 200., 200., 200., 2387., 2387., 2387., 2387.);
         assert_eq_m256(r, e);
+
+        // The selector applies to each 128-bit lane independently.
+        let r = _mm256_dp_ps::<0b0101_0010>(a, b);
+        let e = _mm256_setr_ps(0., 48., 0., 0., 0., 1056., 0., 0.);
+        assert_eq_m256(r, e);
+
+        // The sum is broadcast to several elements of each lane.
+        let r = _mm256_dp_ps::<0b0011_1100>(a, b);
+        let e = _mm256_setr_ps(0., 0., 43., 43., 0., 0., 113., 113.);
+        assert_eq_m256(r, e);
     }
     test_mm256_dp_ps();
 
@@ -794,6 +806,47 @@ unsafe fn test_avx() {
     }
     test_mm256_cvttpd_epi32();
 
+    #[target_feature(enable = "avx")]
+    unsafe fn test_mm256_cvtpd_ps() {
+        let r = _mm256_cvtpd_ps(_mm256_setr_pd(-1.0, 5.0, f64::MAX, f64::MIN));
+        assert_eq_m128(r, _mm_setr_ps(-1.0, 5.0, f32::INFINITY, f32::NEG_INFINITY));
+
+        // Narrowing rounds to nearest, ties to even.
+        let r = _mm256_cvtpd_ps(_mm256_setr_pd(1.0 + f64::EPSILON, 16777217.0, 16777219.0, 0.1));
+        assert_eq_m128(r, _mm_setr_ps(1.0, 16777216.0, 16777220.0, 0.1));
+
+        let r: [f32; 4] =
+            transmute(_mm256_cvtpd_ps(_mm256_setr_pd(1.0, f64::NAN, f64::INFINITY, -0.0)));
+        assert_eq!(r[0], 1.0);
+        assert!(r[1].is_nan());
+        assert_eq!(r[2], f32::INFINITY);
+        assert_eq!(r[3].to_bits(), (-0.0f32).to_bits());
+    }
+    test_mm256_cvtpd_ps();
+
+    #[target_feature(enable = "avx")]
+    unsafe fn test_mm256_movemask_ps() {
+        // Lane `i` ends up in bit `i`, so a single negative lane at index 5
+        // sets only bit 5.
+        let a = _mm256_setr_ps(1.0, 2.0, 3.0, 4.0, 5.0, -6.0, 7.0, 8.0);
+        assert_eq!(_mm256_movemask_ps(a), 1 << 5);
+
+        // Only the sign bit matters, including for zeros and NaNs.
+        let a = _mm256_setr_ps(-0.0, 0.0, -f32::NAN, f32::NAN, -1.0, 1.0, 1.0, -1.0);
+        assert_eq!(_mm256_movemask_ps(a), 0b1001_0101);
+    }
+    test_mm256_movemask_ps();
+
+    #[target_feature(enable = "avx")]
+    unsafe fn test_mm256_movemask_pd() {
+        let a = _mm256_setr_pd(1.0, 2.0, -3.0, 4.0);
+        assert_eq!(_mm256_movemask_pd(a), 1 << 2);
+
+        let a = _mm256_setr_pd(-0.0, 0.0, f64::NAN, -f64::NAN);
+        assert_eq!(_mm256_movemask_pd(a), 0b1001);
+    }
+    test_mm256_movemask_pd();
+
     #[target_feature(enable = "avx")]
     unsafe fn test_mm_permutevar_ps() {
         let a = _mm_setr_ps(4., 3., 2., 5.);
@@ -1140,6 +1193,36 @@ unsafe fn test_avx() {
     }
     test_mm256_maskstore_pd();
 
+    // Only the highest bit of each mask element selects the element.
+    #[target_feature(enable = "avx")]
+    unsafe fn test_mask_high_bit() {
+        let a = &[1.0f32, 2., 3., 4., 5., 6., 7., 8.];
+        let mask = _mm_setr_epi32(i32::MIN, i32::MAX, -1, 1);
+        let r = _mm_maskload_ps(a.as_ptr(), mask);
+        assert_eq_m128(r, _mm_setr_ps(1., 0., 3., 0.));
+        let mask = _mm256_setr_epi32(i32::MAX, i32::MIN, 1, -1, 0, -2, 0x4000_0000, i32::MIN);
+        let r = _mm256_maskload_ps(a.as_ptr(), mask);
+        assert_eq_m256(r, _mm256_setr_ps(0., 2., 0., 4., 0., 6., 0., 8.));
+
+        let a = &[1.0f64, 2., 3., 4.];
+        let mask = _mm_setr_epi64x(i64::MAX, i64::MIN);
+        let r = _mm_maskload_pd(a.as_ptr(), mask);
+        assert_eq_m128d(r, _mm_setr_pd(0., 2.));
+        let mask = _mm256_setr_epi64x(i64::MIN, 1, -1, i64::MAX);
+        let r = _mm256_maskload_pd(a.as_ptr(), mask);
+        assert_eq_m256d(r, _mm256_setr_pd(1., 0., 3., 0.));
+
+        let mut r = [0.0f32; 4];
+        let mask = _mm_setr_epi32(1, i32::MIN, i32::MAX, -1);
+        _mm_maskstore_ps(r.as_mut_ptr(), mask, _mm_setr_ps(1., 2., 3., 4.));
+        assert_eq!(r, [0., 2., 0., 4.]);
+        let mut r = [0.0f64; 4];
+        let mask = _mm256_setr_epi64x(-1, i64::MAX, i64::MIN, 0);
+        _mm256_maskstore_pd(r.as_mut_ptr(), mask, _mm256_setr_pd(1., 2., 3., 4.));
+        assert_eq!(r, [1., 0., 3., 0.]);
+    }
+    test_mask_high_bit();
+
     #[target_feature(enable = "avx")]
     unsafe fn test_mm256_lddqu_si256() {
         #[rustfmt::skip]
@@ -1199,6 +1282,30 @@ unsafe fn test_avx() {
     }
     test_mm256_testnzc_si256();
 
+    // Checks all combinations of the ZF and CF flags. The set bits are only in
+    // the last element, to make sure that the whole vector is taken into account.
+    #[target_feature(enable = "avx")]
+    unsafe fn test_mm256_test_flags_si256() {
+        #[target_feature(enable = "avx")]
+        unsafe fn check(a: i64, b: i64, zf: i32, cf: i32) {
+            let a = _mm256_setr_epi64x(0, 0, 0, a);
+            let b = _mm256_setr_epi64x(0, 0, 0, b);
+            assert_eq!(_mm256_testz_si256(a, b), zf);
+            assert_eq!(_mm256_testc_si256(a, b), cf);
+            assert_eq!(_mm256_testnzc_si256(a, b), i32::from(zf == 0 && cf == 0));
+        }
+
+        // `a & b` is zero and `!a & b` is zero.
+        check(0, 0, 1, 1);
+        // `a & b` is zero and `!a & b` is not zero.
+        check(0xF0, 0x0F, 1, 0);
+        // `a & b` is not zero and `!a & b` is zero.
+        check(-1, 1, 0, 1);
+        // `a & b` is not zero and `!a & b` is not zero.
+        check(1, 3, 0, 0);
+    }
+    test_mm256_test_flags_si256();
+
     #[target_feature(enable = "avx")]
     unsafe fn test_mm256_testz_pd() {
         let a = _mm256_setr_pd(1., 2., 3., 4.);
@@ -1342,6 +1449,118 @@ unsafe fn test_avx() {
         assert_eq!(r, 1);
     }
     test_mm_testnzc_ps();
+
+    // Only the sign bits are tested. `testz` checks the signs of `a & b` (direct)
+    // and `testc` checks the signs of `!a & b` (negated).
+    #[target_feature(enable = "avx")]
+    unsafe fn test_test_sign_bits() {
+        // The signs of `a` and `b` never overlap, but `b` has signs that `a` lacks.
+        let a = _mm_setr_ps(-1., 1., -0., f32::MAX);
+        let b = _mm_setr_ps(1., -1., 1., -3.);
+        assert_eq!(_mm_testz_ps(a, b), 1);
+        assert_eq!(_mm_testc_ps(a, b), 0);
+        assert_eq!(_mm_testnzc_ps(a, b), 0);
+        let a = _mm256_setr_ps(-1., 1., -0., f32::MAX, 1., 1., 1., 1.);
+        let b = _mm256_setr_ps(1., 1., 1., 1., 1., -1., 1., -3.);
+        assert_eq!(_mm256_testz_ps(a, b), 1);
+        assert_eq!(_mm256_testc_ps(a, b), 0);
+        assert_eq!(_mm256_testnzc_ps(a, b), 0);
+        let a = _mm_setr_pd(-1., f64::MAX);
+        let b = _mm_setr_pd(1., -3.);
+        assert_eq!(_mm_testz_pd(a, b), 1);
+        assert_eq!(_mm_testc_pd(a, b), 0);
+        assert_eq!(_mm_testnzc_pd(a, b), 0);
+        let a = _mm256_setr_pd(-1., 1., -0., f64::MAX);
+        let b = _mm256_setr_pd(1., 1., 1., -3.);
+        assert_eq!(_mm256_testz_pd(a, b), 1);
+        assert_eq!(_mm256_testc_pd(a, b), 0);
+        assert_eq!(_mm256_testnzc_pd(a, b), 0);
+
+        // All signs of `b` are also in `a`, and they overlap.
+        let a = _mm_setr_ps(-1., -1., 1., -0.);
+        let b = _mm_setr_ps(-2., 1., 1., 1.);
+        assert_eq!(_mm_testz_ps(a, b), 0);
+        assert_eq!(_mm_testc_ps(a, b), 1);
+        assert_eq!(_mm_testnzc_ps(a, b), 0);
+        let a = _mm256_setr_ps(1., 1., 1., 1., -1., -1., 1., -0.);
+        let b = _mm256_setr_ps(1., 1., 1., 1., 1., 1., 1., -f32::MAX);
+        assert_eq!(_mm256_testz_ps(a, b), 0);
+        assert_eq!(_mm256_testc_ps(a, b), 1);
+        assert_eq!(_mm256_testnzc_ps(a, b), 0);
+        let a = _mm_setr_pd(-1., -0.);
+        let b = _mm_setr_pd(1., -f64::MAX);
+        assert_eq!(_mm_testz_pd(a, b), 0);
+        assert_eq!(_mm_testc_pd(a, b), 1);
+        assert_eq!(_mm_testnzc_pd(a, b), 0);
+        let a = _mm256_setr_pd(1., 1., -1., -0.);
+        let b = _mm256_setr_pd(1., 1., -2., 1.);
+        assert_eq!(_mm256_testz_pd(a, b), 0);
+        assert_eq!(_mm256_testc_pd(a, b), 1);
+        assert_eq!(_mm256_testnzc_pd(a, b), 0);
+
+        // Both overlapping signs and signs of `b` that `a` lacks.
+        let a = _mm256_setr_pd(-1., 1., 1., 1.);
+        let b = _mm256_setr_pd(-1., 1., 1., -1.);
+        assert_eq!(_mm256_testz_pd(a, b), 0);
+        assert_eq!(_mm256_testc_pd(a, b), 0);
+        assert_eq!(_mm256_testnzc_pd(a, b), 1);
+    }
+    test_test_sign_bits();
+
+    #[target_feature(enable = "avx")]
+    unsafe fn test_broadcast() {
+        let x = 1.5f32;
+        assert_eq_m128(_mm_broadcast_ss(&x), _mm_set1_ps(1.5));
+        let x = -2.5f64;
+        assert_eq_m256d(_mm256_broadcast_sd(&x), _mm256_set1_pd(-2.5));
+
+        // The pointers do not need to be aligned.
+        let mut mem = [0u8; 17];
+        let ptr = mem.as_mut_ptr().add(1);
+        ptr.cast::<f32>().write_unaligned(1.5);
+        assert_eq_m128(vbroadcastss(ptr), _mm_set1_ps(1.5));
+        assert_eq_m256(vbroadcastss256(ptr), _mm256_set1_ps(1.5));
+        ptr.cast::<f64>().write_unaligned(-2.5);
+        assert_eq_m256d(vbroadcastsd256(ptr), _mm256_set1_pd(-2.5));
+
+        let a = _mm_setr_ps(1., 2., 3., 4.);
+        ptr.cast::<__m128>().write_unaligned(a);
+        assert_eq_m256(vbroadcastf128ps256(ptr), _mm256_setr_m128(a, a));
+    }
+    test_broadcast();
+
+    #[target_feature(enable = "avx")]
+    unsafe fn test_addsub() {
+        // Even lanes are subtracted, odd lanes are added.
+        let a = _mm256_setr_ps(10., 20., 30., 40., 50., 60., 70., 80.);
+        let b = _mm256_setr_ps(1., 2., 3., 4., 5., 6., 7., 8.);
+        let e = _mm256_setr_ps(9., 22., 27., 44., 45., 66., 63., 88.);
+        assert_eq_m256(addsubps256(a, b), e);
+        assert_eq_m256(_mm256_addsub_ps(a, b), e);
+
+        let a = _mm256_setr_pd(10., 20., 30., 40.);
+        let b = _mm256_setr_pd(1., 2., 3., 4.);
+        let e = _mm256_setr_pd(9., 22., 27., 44.);
+        assert_eq_m256d(addsubpd256(a, b), e);
+        assert_eq_m256d(_mm256_addsub_pd(a, b), e);
+    }
+    test_addsub();
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.avx.vbroadcast.ss"]
+    fn vbroadcastss(p: *const i8) -> __m128;
+    #[link_name = "llvm.x86.avx.vbroadcast.ss.256"]
+    fn vbroadcastss256(p: *const i8) -> __m256;
+    #[link_name = "llvm.x86.avx.vbroadcast.sd.256"]
+    fn vbroadcastsd256(p: *const i8) -> __m256d;
+    #[link_name = "llvm.x86.avx.vbroadcastf128.ps.256"]
+    fn vbroadcastf128ps256(p: *const i8) -> __m256;
+    #[link_name = "llvm.x86.avx.addsub.ps.256"]
+    fn addsubps256(a: __m256, b: __m256) -> __m256;
+    #[link_name = "llvm.x86.avx.addsub.pd.256"]
+    fn addsubpd256(a: __m256d, b: __m256d) -> __m256d;
 }
 
 #[target_feature(enable = "sse2")]
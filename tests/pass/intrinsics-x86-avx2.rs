@@ -9,6 +9,8 @@
 //@ignore-target-wasm32
 //@compile-flags: -C target-feature=+avx2
 
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
 #[cfg(target_arch = "x86")]
 use std::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
@@ -1075,28 +1077,59 @@ unsafe fn test_avx2() {
             18, 20, 22, 24, 26, 28, 30,
         );
 
-        let r = _mm256_mpsadbw_epu8::<0b000>(a, a);
+        let r = _mm256_mpsadbw_epu8::<0b000_000>(a, a);
         let e = _mm256_setr_epi16(0, 4, 8, 12, 16, 20, 24, 28, 0, 8, 16, 24, 32, 40, 48, 56);
         assert_eq_m256i(r, e);
 
-        let r = _mm256_mpsadbw_epu8::<0b001>(a, a);
+        let r = _mm256_mpsadbw_epu8::<0b001_001>(a, a);
         let e = _mm256_setr_epi16(16, 12, 8, 4, 0, 4, 8, 12, 32, 24, 16, 8, 0, 8, 16, 24);
         assert_eq_m256i(r, e);
 
-        let r = _mm256_mpsadbw_epu8::<0b100>(a, a);
+        let r = _mm256_mpsadbw_epu8::<0b100_100>(a, a);
         let e = _mm256_setr_epi16(16, 20, 24, 28, 32, 36, 40, 44, 32, 40, 48, 56, 64, 72, 80, 88);
         assert_eq_m256i(r, e);
 
-        let r = _mm256_mpsadbw_epu8::<0b101>(a, a);
+        let r = _mm256_mpsadbw_epu8::<0b101_101>(a, a);
         let e = _mm256_setr_epi16(0, 4, 8, 12, 16, 20, 24, 28, 0, 8, 16, 24, 32, 40, 48, 56);
         assert_eq_m256i(r, e);
 
-        let r = _mm256_mpsadbw_epu8::<0b111>(a, a);
+        let r = _mm256_mpsadbw_epu8::<0b111_111>(a, a);
         let e = _mm256_setr_epi16(32, 28, 24, 20, 16, 12, 8, 4, 64, 56, 48, 40, 32, 24, 16, 8);
         assert_eq_m256i(r, e);
     }
     test_mm256_mpsadbw_epu8();
 
+    // Compares against a scalar implementation, with different selectors
+    // for the two 128-bit lanes.
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_mpsadbw_epu8_reference() {
+        fn mpsadbw_ref(a: [u8; 32], b: [u8; 32], imm: u8) -> [u16; 16] {
+            let mut r = [0u16; 16];
+            for lane in 0..2 {
+                let sel = imm >> (3 * lane);
+                let a_off = lane * 16 + usize::from((sel >> 2) & 1) * 4;
+                let b_off = lane * 16 + usize::from(sel & 0b11) * 4;
+                for j in 0..8 {
+                    r[lane * 8 + j] =
+                        (0..4).map(|k| u16::from(a[a_off + j + k].abs_diff(b[b_off + k]))).sum();
+                }
+            }
+            r
+        }
+
+        let a: [u8; 32] = std::array::from_fn(|i| (i as u8).wrapping_mul(37).wrapping_add(11));
+        let b: [u8; 32] = std::array::from_fn(|i| (i as u8).wrapping_mul(91).wrapping_add(200));
+        let (va, vb) = (transmute::<_, __m256i>(a), transmute::<_, __m256i>(b));
+
+        let r = transmute::<_, [u16; 16]>(_mm256_mpsadbw_epu8::<0b010_101>(va, vb));
+        assert_eq!(r, mpsadbw_ref(a, b, 0b010_101));
+        let r = transmute::<_, [u16; 16]>(_mm256_mpsadbw_epu8::<0b111_000>(va, vb));
+        assert_eq!(r, mpsadbw_ref(a, b, 0b111_000));
+        let r = transmute::<_, [u16; 16]>(_mm256_mpsadbw_epu8::<0b001_110>(vb, va));
+        assert_eq!(r, mpsadbw_ref(b, a, 0b001_110));
+    }
+    test_mm256_mpsadbw_epu8_reference();
+
     #[target_feature(enable = "avx2")]
     unsafe fn test_mm256_mulhrs_epi16() {
         let a = _mm256_set1_epi16(2);
@@ -1200,6 +1233,37 @@ unsafe fn test_avx2() {
         let r = _mm256_sad_epu8(a, b);
         let e = _mm256_set1_epi64x(16);
         assert_eq_m256i(r, e);
+
+        // Equal inputs have no differences.
+        let r = _mm256_sad_epu8(a, a);
+        assert_eq_m256i(r, _mm256_setzero_si256());
+
+        // Each group of 8 bytes is summed into its own 64-bit element.
+        #[rustfmt::skip]
+        let a = _mm256_setr_epi8(
+            0, 1, 2, 3, 4, 5, 6, 7,
+            10, 10, 10, 10, 10, 10, 10, 10,
+            255u8 as i8, 0, 255u8 as i8, 0, 255u8 as i8, 0, 255u8 as i8, 0,
+            1, 1, 1, 1, 1, 1, 1, 1,
+        );
+        #[rustfmt::skip]
+        let b = _mm256_setr_epi8(
+            7, 6, 5, 4, 3, 2, 1, 0,
+            0, 20, 0, 20, 0, 20, 0, 20,
+            0, 255u8 as i8, 0, 255u8 as i8, 0, 255u8 as i8, 0, 255u8 as i8,
+            1, 1, 1, 1, 1, 1, 1, 2,
+        );
+        let r = _mm256_sad_epu8(a, b);
+        let e = _mm256_setr_epi64x(32, 80, 2040, 1);
+        assert_eq_m256i(r, e);
+
+        // The largest possible sum only needs 16 bits, the upper 48 bits of
+        // each element are zero.
+        let r = _mm256_sad_epu8(_mm256_set1_epi8(-1), _mm256_setzero_si256());
+        for x in transmute::<_, [u64; 4]>(r) {
+            assert_eq!(x, 2040);
+            assert_eq!(x >> 16, 0);
+        }
     }
     test_mm256_sad_epu8();
 
@@ -1229,6 +1293,43 @@ unsafe fn test_avx2() {
         let r = _mm256_shuffle_epi8(a, b);
         assert_eq_m256i(r, expected);
     }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_shuffle_epi8_per_lane() {
+        #[rustfmt::skip]
+        let a = _mm256_setr_epi8(
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+            16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+        );
+
+        // Indices are relative to each 128-bit lane, so this reverses each lane.
+        #[rustfmt::skip]
+        let reverse = _mm256_setr_epi8(
+            15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+            15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+        );
+        #[rustfmt::skip]
+        let expected = _mm256_setr_epi8(
+            15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+            31, 30, 29, 28, 27, 26, 25, 24, 23, 22, 21, 20, 19, 18, 17, 16,
+        );
+        assert_eq_m256i(_mm256_shuffle_epi8(a, reverse), expected);
+
+        // Only the low 4 bits select the byte, unless the high bit is set,
+        // which zeroes the output byte.
+        #[rustfmt::skip]
+        let ctrl = _mm256_setr_epi8(
+            0x10, 0x21, 0x80u8 as i8, 0x8Fu8 as i8, 0xFFu8 as i8, 0x7F, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+            0x80u8 as i8, 0x90u8 as i8, 0xF0u8 as i8, 0x81u8 as i8, 0x10, 0x21, 0x7F, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+        );
+        #[rustfmt::skip]
+        let expected = _mm256_setr_epi8(
+            0, 1, 0, 0, 0, 15, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+            0, 0, 0, 0, 16, 17, 31, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+        );
+        assert_eq_m256i(_mm256_shuffle_epi8(a, ctrl), expected);
+    }
+    test_mm256_shuffle_epi8_per_lane();
     test_mm256_shuffle_epi8();
 
     #[target_feature(enable = "avx2")]
@@ -1524,6 +1625,186 @@ unsafe fn test_avx2() {
         assert_eq_m256i(r, e);
     }
     test_mm256_srlv_epi64();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_blendv_epi8() {
+        let a = _mm256_set1_epi8(1);
+        let b = _mm256_set1_epi8(2);
+        // Only the high bit of each mask byte matters.
+        let mask = _mm256_set1_epi16(0x80_7f_u16 as i16);
+        let e = _mm256_set1_epi16(0x02_01);
+        assert_eq_m256i(_mm256_blendv_epi8(a, b, mask), e);
+
+        let mask = _mm256_set1_epi8(-1);
+        assert_eq_m256i(_mm256_blendv_epi8(a, b, mask), b);
+
+        // Call the intrinsic directly so that it gets integer vectors with
+        // 64-bit lanes; the blend must still happen per byte.
+        let mask = _mm256_set1_epi16(0x80_7f_u16 as i16);
+        assert_eq_m256i(pblendvb256(a, b, mask), e);
+    }
+    test_mm256_blendv_epi8();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_blend_epi16() {
+        let a = _mm256_set1_epi16(1);
+        let b = _mm256_set1_epi16(2);
+        // The immediate selects the same words in both 128-bit halves.
+        let e = _mm256_setr_epi16(2, 1, 1, 1, 1, 1, 1, 2, 2, 1, 1, 1, 1, 1, 1, 2);
+        assert_eq_m256i(_mm256_blend_epi16::<0b1000_0001>(a, b), e);
+        assert_eq_m256i(pblendw256(a, b, 0b1000_0001), e);
+    }
+    test_mm256_blend_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_broadcast() {
+        let a = _mm_setr_ps(1.5, 2., 3., 4.);
+        assert_eq_m128(_mm_broadcastss_ps(a), _mm_set1_ps(1.5));
+        assert_eq_m256(_mm256_broadcastss_ps(a), _mm256_set1_ps(1.5));
+
+        let a = _mm_setr_pd(-2.5, 3.);
+        assert_eq_m256d(_mm256_broadcastsd_pd(a), _mm256_set1_pd(-2.5));
+    }
+    test_mm256_broadcast();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_byte_shifts() {
+        #[rustfmt::skip]
+        let a = _mm_setr_epi8(
+            1, 2, 3, 4, 5, 6, 7, 8,
+            9, 10, 11, 12, 13, 14, 15, 16,
+        );
+
+        let e = _mm_setr_epi8(0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13);
+        assert_eq_m128i(psll_dq_bs(a, 3), e);
+        assert_eq_m128i(psll_dq(a, 3 * 8), e);
+        assert_eq_m128i(_mm_slli_si128::<3>(a), e);
+        let e = _mm_setr_epi8(4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0);
+        assert_eq_m128i(psrl_dq_bs(a, 3), e);
+        assert_eq_m128i(psrl_dq(a, 3 * 8), e);
+        assert_eq_m128i(_mm_srli_si128::<3>(a), e);
+
+        // Shifting by 16 bytes or more produces zero.
+        assert_eq_m128i(psll_dq_bs(a, 20), _mm_setzero_si128());
+        assert_eq_m128i(psrl_dq_bs(a, 20), _mm_setzero_si128());
+        assert_eq_m128i(psll_dq(a, 20 * 8), _mm_setzero_si128());
+        assert_eq_m128i(_mm_slli_si128::<20>(a), _mm_setzero_si128());
+
+        // The 256-bit variants shift each 128-bit lane independently.
+        let a = _mm256_setr_m128i(a, a);
+        let e = _mm_setr_epi8(0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13);
+        assert_eq_m256i(psll_dq_bs256(a, 3), _mm256_setr_m128i(e, e));
+        assert_eq_m256i(psll_dq256(a, 3 * 8), _mm256_setr_m128i(e, e));
+        assert_eq_m256i(_mm256_slli_si256::<3>(a), _mm256_setr_m128i(e, e));
+        let e = _mm_setr_epi8(4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0);
+        assert_eq_m256i(psrl_dq_bs256(a, 3), _mm256_setr_m128i(e, e));
+        assert_eq_m256i(psrl_dq256(a, 3 * 8), _mm256_setr_m128i(e, e));
+        assert_eq_m256i(_mm256_srli_si256::<3>(a), _mm256_setr_m128i(e, e));
+        assert_eq_m256i(psll_dq_bs256(a, 20), _mm256_setzero_si256());
+        assert_eq_m256i(psrl_dq_bs256(a, 20), _mm256_setzero_si256());
+    }
+    test_byte_shifts();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_alignr_epi8() {
+        #[rustfmt::skip]
+        let a = _mm256_setr_epi8(
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+            17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32,
+        );
+        #[rustfmt::skip]
+        let b = _mm256_setr_epi8(
+            -1, -2, -3, -4, -5, -6, -7, -8, -9, -10, -11, -12, -13, -14, -15, -16,
+            -17, -18, -19, -20, -21, -22, -23, -24, -25, -26, -27, -28, -29, -30, -31, -32,
+        );
+
+        assert_eq_m256i(palignr256(a, b, 0), b);
+        assert_eq_m256i(_mm256_alignr_epi8::<0>(a, b), b);
+        assert_eq_m256i(palignr256(a, b, 16), a);
+        assert_eq_m256i(_mm256_alignr_epi8::<16>(a, b), a);
+
+        // Each 128-bit lane is shifted independently.
+        #[rustfmt::skip]
+        let e = _mm256_setr_epi8(
+            -5, -6, -7, -8, -9, -10, -11, -12, -13, -14, -15, -16, 1, 2, 3, 4,
+            -21, -22, -23, -24, -25, -26, -27, -28, -29, -30, -31, -32, 17, 18, 19, 20,
+        );
+        assert_eq_m256i(palignr256(a, b, 4), e);
+        assert_eq_m256i(_mm256_alignr_epi8::<4>(a, b), e);
+
+        assert_eq_m256i(palignr256(a, b, 32), _mm256_setzero_si256());
+        assert_eq_m256i(_mm256_alignr_epi8::<32>(a, b), _mm256_setzero_si256());
+    }
+    test_mm256_alignr_epi8();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_broadcastd_epi32() {
+        let a = _mm_setr_epi32(-7, 1, 2, 3);
+        assert_eq!(transmute::<_, [i32; 8]>(pbroadcastd256(a)), [-7; 8]);
+        assert_eq!(transmute::<_, [i32; 8]>(_mm256_broadcastd_epi32(a)), [-7; 8]);
+        assert_eq!(transmute::<_, [i32; 4]>(pbroadcastd128(a)), [-7; 4]);
+        assert_eq!(transmute::<_, [i32; 4]>(_mm_broadcastd_epi32(a)), [-7; 4]);
+
+        // The other element sizes.
+        let a = _mm_setr_epi8(-3, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        assert_eq!(transmute::<_, [i8; 32]>(pbroadcastb256(a)), [-3; 32]);
+        let a = _mm_setr_epi16(-5, 1, 2, 3, 4, 5, 6, 7);
+        assert_eq!(transmute::<_, [i16; 16]>(pbroadcastw256(a)), [-5; 16]);
+        let a = _mm_setr_epi64x(-9, 1);
+        assert_eq!(transmute::<_, [i64; 4]>(pbroadcastq256(a)), [-9; 4]);
+    }
+    test_mm256_broadcastd_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_cmpgt_epi64() {
+        let a = _mm256_setr_epi64x(i64::MAX, i64::MIN, -1, 5);
+        let b = _mm256_setr_epi64x(i64::MIN, i64::MAX, 0, 5);
+        let e = _mm256_setr_epi64x(-1, 0, 0, 0);
+        assert_eq_m256i(pcmpgtq256(a, b), e);
+        assert_eq_m256i(_mm256_cmpgt_epi64(a, b), e);
+        let e = _mm256_setr_epi64x(0, -1, -1, 0);
+        assert_eq_m256i(pcmpgtq256(b, a), e);
+        assert_eq_m256i(_mm256_cmpgt_epi64(b, a), e);
+    }
+    test_mm256_cmpgt_epi64();
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse2.psll.dq"]
+    fn psll_dq(a: __m128i, bits: i32) -> __m128i;
+    #[link_name = "llvm.x86.sse2.psrl.dq"]
+    fn psrl_dq(a: __m128i, bits: i32) -> __m128i;
+    #[link_name = "llvm.x86.sse2.psll.dq.bs"]
+    fn psll_dq_bs(a: __m128i, bytes: i32) -> __m128i;
+    #[link_name = "llvm.x86.sse2.psrl.dq.bs"]
+    fn psrl_dq_bs(a: __m128i, bytes: i32) -> __m128i;
+    #[link_name = "llvm.x86.avx2.psll.dq"]
+    fn psll_dq256(a: __m256i, bits: i32) -> __m256i;
+    #[link_name = "llvm.x86.avx2.psrl.dq"]
+    fn psrl_dq256(a: __m256i, bits: i32) -> __m256i;
+    #[link_name = "llvm.x86.avx2.psll.dq.bs"]
+    fn psll_dq_bs256(a: __m256i, bytes: i32) -> __m256i;
+    #[link_name = "llvm.x86.avx2.psrl.dq.bs"]
+    fn psrl_dq_bs256(a: __m256i, bytes: i32) -> __m256i;
+    #[link_name = "llvm.x86.avx2.palignr"]
+    fn palignr256(a: __m256i, b: __m256i, count: u8) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pbroadcastb.256"]
+    fn pbroadcastb256(a: __m128i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pbroadcastw.256"]
+    fn pbroadcastw256(a: __m128i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pbroadcastd.128"]
+    fn pbroadcastd128(a: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.avx2.pbroadcastd.256"]
+    fn pbroadcastd256(a: __m128i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pbroadcastq.256"]
+    fn pbroadcastq256(a: __m128i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pcmpgt.q"]
+    fn pcmpgtq256(a: __m256i, b: __m256i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pblendvb"]
+    fn pblendvb256(a: __m256i, b: __m256i, mask: __m256i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pblendw"]
+    fn pblendw256(a: __m256i, b: __m256i, imm8: u8) -> __m256i;
 }
 
 #[target_feature(enable = "sse2")]
@@ -7,10 +7,11 @@
 //@ignore-target-s390x
 //@ignore-target-thumbv7em
 //@ignore-target-wasm32
-//@compile-flags: -C target-feature=+avx512f,+avx512vl,+avx512bitalg,+avx512vpopcntdq
+//@compile-flags: -C target-feature=+avx512f,+avx512vl,+avx512bw,+avx512bitalg,+avx512vpopcntdq
 
 #![feature(avx512_target_feature)]
 #![feature(stdarch_x86_avx512)]
+#![feature(link_llvm_intrinsics)]
 
 #[cfg(target_arch = "x86")]
 use std::arch::x86::*;
@@ -21,15 +22,391 @@ use std::mem::transmute;
 fn main() {
     assert!(is_x86_feature_detected!("avx512f"));
     assert!(is_x86_feature_detected!("avx512vl"));
+    assert!(is_x86_feature_detected!("avx512bw"));
     assert!(is_x86_feature_detected!("avx512bitalg"));
     assert!(is_x86_feature_detected!("avx512vpopcntdq"));
 
     unsafe {
+        test_avx512f();
+        test_avx512bw();
         test_avx512bitalg();
         test_avx512vpopcntdq();
     }
 }
 
+#[target_feature(enable = "avx512f")]
+unsafe fn test_avx512f() {
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_cvt_roundu32_ss() {
+        let a = _mm_set_ps(1.0, 2.0, 3.0, 4.0);
+
+        // Values above `i32::MAX` must not be interpreted as negative.
+        let r = _mm_cvt_roundu32_ss::<_MM_FROUND_CUR_DIRECTION>(a, u32::MAX);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 4294967296.0));
+        let r = _mm_cvt_roundu32_ss::<_MM_FROUND_CUR_DIRECTION>(a, 0x8000_0000);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 2147483648.0));
+
+        let r = _mm_cvt_roundu32_ss::<_MM_FROUND_CUR_DIRECTION>(a, 123);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 123.0));
+
+        // Explicit rounding modes
+        let r = _mm_cvt_roundu32_ss::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(a, u32::MAX);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 4294967040.0));
+        let r = _mm_cvt_roundu32_ss::<{ _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC }>(
+            a,
+            u32::MAX,
+        );
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 4294967296.0));
+    }
+    test_mm_cvt_roundu32_ss();
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_cvt_roundu64_ss() {
+        let a = _mm_set_ps(1.0, 2.0, 3.0, 4.0);
+
+        // Values above `i64::MAX` must not be interpreted as negative.
+        let r = _mm_cvt_roundu64_ss::<_MM_FROUND_CUR_DIRECTION>(a, u64::MAX);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 18446744073709551616.0));
+        let r = _mm_cvt_roundu64_ss::<_MM_FROUND_CUR_DIRECTION>(a, 1 << 63);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 9223372036854775808.0));
+
+        let r = _mm_cvt_roundu64_ss::<_MM_FROUND_CUR_DIRECTION>(a, 123);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 123.0));
+
+        let r = _mm_cvt_roundu64_ss::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(a, u64::MAX);
+        assert_eq_m128(r, _mm_set_ps(1.0, 2.0, 3.0, 18446742974197923840.0));
+    }
+    #[cfg(target_arch = "x86_64")]
+    test_mm_cvt_roundu64_ss();
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_cvt_roundu64_sd() {
+        let a = _mm_set_pd(1.0, 2.0);
+
+        // Values above `i64::MAX` must not be interpreted as negative.
+        let r = _mm_cvt_roundu64_sd::<_MM_FROUND_CUR_DIRECTION>(a, u64::MAX);
+        assert_eq_m128d(r, _mm_set_pd(1.0, 18446744073709551616.0));
+        let r = _mm_cvt_roundu64_sd::<_MM_FROUND_CUR_DIRECTION>(a, 1 << 63);
+        assert_eq_m128d(r, _mm_set_pd(1.0, 9223372036854775808.0));
+
+        let r = _mm_cvt_roundu64_sd::<_MM_FROUND_CUR_DIRECTION>(a, 123);
+        assert_eq_m128d(r, _mm_set_pd(1.0, 123.0));
+
+        let r = _mm_cvt_roundu64_sd::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(a, u64::MAX);
+        assert_eq_m128d(r, _mm_set_pd(1.0, 18446744073709549568.0));
+    }
+    #[cfg(target_arch = "x86_64")]
+    test_mm_cvt_roundu64_sd();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_cvtss_u32() {
+        let test = |x: f32, expected_round: u32, expected_trunc: u32| {
+            let a = _mm_setr_ps(x, -1.0, f32::NAN, 0.0);
+            assert_eq!(_mm_cvtss_u32(a), expected_round, "input: {x}");
+            assert_eq!(_mm_cvttss_u32(a), expected_trunc, "input: {x}");
+        };
+
+        test(1.5, 2, 1);
+        test(2.5, 2, 2);
+        test(-0.2, 0, 0);
+        test(4294967040.0, 4294967040, 4294967040);
+        // Invalid conversions return `u32::MAX`.
+        test(-1.0, u32::MAX, u32::MAX);
+        test(4294967296.0, u32::MAX, u32::MAX);
+        test(f32::INFINITY, u32::MAX, u32::MAX);
+        test(f32::NEG_INFINITY, u32::MAX, u32::MAX);
+        test(f32::NAN, u32::MAX, u32::MAX);
+
+        let a = _mm_setr_ps(1.5, 0.0, 0.0, 0.0);
+        assert_eq!(_mm_cvt_roundss_u32::<{ _MM_FROUND_TO_POS_INF | _MM_FROUND_NO_EXC }>(a), 2);
+        assert_eq!(_mm_cvt_roundss_u32::<{ _MM_FROUND_TO_NEG_INF | _MM_FROUND_NO_EXC }>(a), 1);
+    }
+    test_mm_cvtss_u32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_cvtsd_u32() {
+        let test = |x: f64, expected_round: u32, expected_trunc: u32| {
+            let a = _mm_setr_pd(x, f64::NAN);
+            assert_eq!(_mm_cvtsd_u32(a), expected_round, "input: {x}");
+            assert_eq!(_mm_cvttsd_u32(a), expected_trunc, "input: {x}");
+        };
+
+        test(1.5, 2, 1);
+        test(2.5, 2, 2);
+        test(-0.2, 0, 0);
+        test(4294967295.0, u32::MAX, u32::MAX);
+        test(4294967294.7, u32::MAX, 4294967294);
+        // Invalid conversions return `u32::MAX`.
+        test(-1.0, u32::MAX, u32::MAX);
+        test(4294967296.0, u32::MAX, u32::MAX);
+        test(f64::INFINITY, u32::MAX, u32::MAX);
+        test(f64::NEG_INFINITY, u32::MAX, u32::MAX);
+        test(f64::NAN, u32::MAX, u32::MAX);
+    }
+    test_mm_cvtsd_u32();
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_cvtss_u64() {
+        let test = |x: f32, expected_round: u64, expected_trunc: u64| {
+            let a = _mm_setr_ps(x, -1.0, f32::NAN, 0.0);
+            assert_eq!(_mm_cvtss_u64(a), expected_round, "input: {x}");
+            assert_eq!(_mm_cvttss_u64(a), expected_trunc, "input: {x}");
+        };
+
+        test(1.5, 2, 1);
+        test(-0.2, 0, 0);
+        test(9223372036854775808.0, 1 << 63, 1 << 63);
+        test(18446742974197923840.0, 18446742974197923840, 18446742974197923840);
+        // Invalid conversions return `u64::MAX`.
+        test(-1.0, u64::MAX, u64::MAX);
+        test(18446744073709551616.0, u64::MAX, u64::MAX);
+        test(f32::INFINITY, u64::MAX, u64::MAX);
+        test(f32::NAN, u64::MAX, u64::MAX);
+    }
+    #[cfg(target_arch = "x86_64")]
+    test_mm_cvtss_u64();
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_cvtsd_u64() {
+        let test = |x: f64, expected_round: u64, expected_trunc: u64| {
+            let a = _mm_setr_pd(x, f64::NAN);
+            assert_eq!(_mm_cvtsd_u64(a), expected_round, "input: {x}");
+            assert_eq!(_mm_cvttsd_u64(a), expected_trunc, "input: {x}");
+        };
+
+        test(1.5, 2, 1);
+        test(-0.2, 0, 0);
+        test(9223372036854775808.0, 1 << 63, 1 << 63);
+        test(18446744073709549568.0, 18446744073709549568, 18446744073709549568);
+        // Invalid conversions return `u64::MAX`.
+        test(-1.0, u64::MAX, u64::MAX);
+        test(18446744073709551616.0, u64::MAX, u64::MAX);
+        test(f64::INFINITY, u64::MAX, u64::MAX);
+        test(f64::NAN, u64::MAX, u64::MAX);
+    }
+    #[cfg(target_arch = "x86_64")]
+    test_mm_cvtsd_u64();
+
+    const NEAREST: i32 = _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC;
+    const POS_INF: i32 = _MM_FROUND_TO_POS_INF | _MM_FROUND_NO_EXC;
+    const TO_ZERO: i32 = _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC;
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_add_round_ps() {
+        #[rustfmt::skip]
+        let a = _mm512_setr_ps(
+            0., 1., 2., 3., 4., 5., 6., 7.,
+            8., 9., 10., 11., 12., 13., 14., 15.,
+        );
+        let b = _mm512_set1_ps(0.5);
+        let src = _mm512_set1_ps(-1.);
+
+        let r = _mm512_add_round_ps::<NEAREST>(a, b);
+        #[rustfmt::skip]
+        let e = _mm512_setr_ps(
+            0.5, 1.5, 2.5, 3.5, 4.5, 5.5, 6.5, 7.5,
+            8.5, 9.5, 10.5, 11.5, 12.5, 13.5, 14.5, 15.5,
+        );
+        assert_eq_m512(r, e);
+
+        // The lower half of the lanes is computed, the upper half is merged from `src`.
+        let r = _mm512_mask_add_round_ps::<NEAREST>(src, 0x00FF, a, b);
+        #[rustfmt::skip]
+        let e = _mm512_setr_ps(
+            0.5, 1.5, 2.5, 3.5, 4.5, 5.5, 6.5, 7.5,
+            -1., -1., -1., -1., -1., -1., -1., -1.,
+        );
+        assert_eq_m512(r, e);
+        let r = _mm512_mask_add_ps(src, 0x00FF, a, b);
+        assert_eq_m512(r, e);
+
+        // With `maskz`, the upper half is zeroed instead.
+        let r = _mm512_maskz_add_round_ps::<NEAREST>(0x00FF, a, b);
+        #[rustfmt::skip]
+        let e = _mm512_setr_ps(
+            0.5, 1.5, 2.5, 3.5, 4.5, 5.5, 6.5, 7.5,
+            0., 0., 0., 0., 0., 0., 0., 0.,
+        );
+        assert_eq_m512(r, e);
+
+        // The rounding mode is honored.
+        let a = _mm512_set1_ps(1.);
+        let b = _mm512_set1_ps(2f32.powi(-30));
+        let r = _mm512_add_round_ps::<NEAREST>(a, b);
+        assert_eq_m512(r, _mm512_set1_ps(1.));
+        let r = _mm512_add_round_ps::<POS_INF>(a, b);
+        assert_eq_m512(r, _mm512_set1_ps(1. + f32::EPSILON));
+    }
+    test_mm512_add_round_ps();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_mul_round_pd() {
+        let a = _mm512_setr_pd(1., 2., 3., 4., 5., 6., 7., 8.);
+        let b = _mm512_set1_pd(2.);
+        let src = _mm512_set1_pd(-1.);
+
+        let r = _mm512_mul_round_pd::<NEAREST>(a, b);
+        assert_eq_m512d(r, _mm512_setr_pd(2., 4., 6., 8., 10., 12., 14., 16.));
+
+        let r = _mm512_mask_mul_round_pd::<NEAREST>(src, 0b0101_0101, a, b);
+        assert_eq_m512d(r, _mm512_setr_pd(2., -1., 6., -1., 10., -1., 14., -1.));
+        let r = _mm512_mask_mul_pd(src, 0b0101_0101, a, b);
+        assert_eq_m512d(r, _mm512_setr_pd(2., -1., 6., -1., 10., -1., 14., -1.));
+
+        let r = _mm512_maskz_mul_round_pd::<NEAREST>(0b0101_0101, a, b);
+        assert_eq_m512d(r, _mm512_setr_pd(2., 0., 6., 0., 10., 0., 14., 0.));
+
+        // The rounding mode is honored.
+        let a = _mm512_set1_pd(1. + f64::EPSILON);
+        let b = _mm512_set1_pd(1. + f64::EPSILON);
+        let r = _mm512_mul_round_pd::<TO_ZERO>(a, b);
+        assert_eq_m512d(r, _mm512_set1_pd(1. + 2. * f64::EPSILON));
+        let r = _mm512_mul_round_pd::<POS_INF>(a, b);
+        assert_eq_m512d(r, _mm512_set1_pd(1. + 3. * f64::EPSILON));
+    }
+    test_mm512_mul_round_pd();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm_mask_add_round_ss() {
+        let a = _mm_setr_ps(1., 2., 3., 4.);
+        let b = _mm_setr_ps(10., 20., 30., 40.);
+        let src = _mm_setr_ps(-1., -2., -3., -4.);
+
+        let r = _mm_add_round_ss::<NEAREST>(a, b);
+        assert_eq_m128(r, _mm_setr_ps(11., 2., 3., 4.));
+
+        // Only the lowest bit of the mask is used.
+        let r = _mm_mask_add_round_ss::<NEAREST>(src, 0b1111_1110, a, b);
+        assert_eq_m128(r, _mm_setr_ps(-1., 2., 3., 4.));
+        let r = _mm_mask_add_round_ss::<NEAREST>(src, 0b0000_0001, a, b);
+        assert_eq_m128(r, _mm_setr_ps(11., 2., 3., 4.));
+        let r = _mm_maskz_add_round_ss::<NEAREST>(0, a, b);
+        assert_eq_m128(r, _mm_setr_ps(0., 2., 3., 4.));
+
+        let a = _mm_setr_pd(3., 4.);
+        let b = _mm_setr_pd(5., 6.);
+        let src = _mm_setr_pd(-1., -2.);
+        let r = _mm_mask_mul_round_sd::<NEAREST>(src, 1, a, b);
+        assert_eq_m128d(r, _mm_setr_pd(15., 4.));
+        let r = _mm_mask_mul_round_sd::<NEAREST>(src, 0, a, b);
+        assert_eq_m128d(r, _mm_setr_pd(-1., 4.));
+    }
+    test_mm_mask_add_round_ss();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mask_registers() {
+        #[allow(improper_ctypes)]
+        extern "C" {
+            #[link_name = "llvm.x86.avx512.kand.w"]
+            fn kandw(a: u16, b: u16) -> u16;
+            #[link_name = "llvm.x86.avx512.kandn.w"]
+            fn kandnw(a: u16, b: u16) -> u16;
+            #[link_name = "llvm.x86.avx512.kor.w"]
+            fn korw(a: u16, b: u16) -> u16;
+            #[link_name = "llvm.x86.avx512.kxor.w"]
+            fn kxorw(a: u16, b: u16) -> u16;
+            #[link_name = "llvm.x86.avx512.kxnor.w"]
+            fn kxnorw(a: u16, b: u16) -> u16;
+            #[link_name = "llvm.x86.avx512.knot.w"]
+            fn knotw(a: u16) -> u16;
+            #[link_name = "llvm.x86.avx512.kunpck.bw"]
+            fn kunpckbw(a: u16, b: u16) -> u16;
+        }
+
+        let a: u16 = 0b1100_1010_0101_0011;
+        let b: u16 = 0b1010_0110_1111_0000;
+
+        assert_eq!(kandw(a, b), 0b1000_0010_0101_0000);
+        assert_eq!(_kand_mask16(a, b), 0b1000_0010_0101_0000);
+        // The first operand is negated.
+        assert_eq!(kandnw(a, b), 0b0010_0100_1010_0000);
+        assert_eq!(_kandn_mask16(a, b), 0b0010_0100_1010_0000);
+        assert_eq!(korw(a, b), 0b1110_1110_1111_0011);
+        assert_eq!(_kor_mask16(a, b), 0b1110_1110_1111_0011);
+        assert_eq!(kxorw(a, b), 0b0110_1100_1010_0011);
+        assert_eq!(_kxor_mask16(a, b), 0b0110_1100_1010_0011);
+        assert_eq!(kxnorw(a, b), 0b1001_0011_0101_1100);
+        assert_eq!(_kxnor_mask16(a, b), 0b1001_0011_0101_1100);
+        assert_eq!(knotw(a), 0b0011_0101_1010_1100);
+        assert_eq!(_knot_mask16(a), 0b0011_0101_1010_1100);
+
+        // The lower half of the first operand ends up in the upper half of the result,
+        // the upper halves of the operands are ignored.
+        assert_eq!(kunpckbw(a, b), 0b0101_0011_1111_0000);
+        assert_eq!(_mm512_kunpackb(a, b), 0b0101_0011_1111_0000);
+    }
+    test_mask_registers();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_cvtps_epu32() {
+        #[rustfmt::skip]
+        let a = _mm512_setr_ps(
+            0., 1.5, 2.5, 3.7, 4294967040., 4294967296., 5e9, -1.,
+            -0.5, f32::INFINITY, f32::NEG_INFINITY, f32::NAN, 7., 8., 9., 10.,
+        );
+
+        // Values that do not fit into `u32` (including negative values that do
+        // not truncate to zero) saturate to `u32::MAX`.
+        let r: [u32; 16] = transmute(_mm512_cvttps_epu32(a));
+        #[rustfmt::skip]
+        let e = [
+            0, 1, 2, 3, 4294967040, u32::MAX, u32::MAX, u32::MAX,
+            0, u32::MAX, u32::MAX, u32::MAX, 7, 8, 9, 10,
+        ];
+        assert_eq!(r, e);
+        let r: [u32; 16] = transmute(_mm512_cvtps_epu32(a));
+        #[rustfmt::skip]
+        let e = [
+            0, 2, 2, 4, 4294967040, u32::MAX, u32::MAX, u32::MAX,
+            0, u32::MAX, u32::MAX, u32::MAX, 7, 8, 9, 10,
+        ];
+        assert_eq!(r, e);
+
+        // Elements whose mask bit is not set come from `src`.
+        let src = _mm512_set1_epi32(42);
+        let r: [u32; 16] = transmute(_mm512_mask_cvttps_epu32(src, 0b0000_0000_0110_0011, a));
+        #[rustfmt::skip]
+        let e = [
+            0, 1, 42, 42, 42, u32::MAX, u32::MAX, 42,
+            42, 42, 42, 42, 42, 42, 42, 42,
+        ];
+        assert_eq!(r, e);
+
+        // The signed conversions still fall back to `i32::MIN`.
+        let r: [i32; 16] = transmute(_mm512_cvttps_epi32(a));
+        #[rustfmt::skip]
+        let e = [
+            0, 1, 2, 3, i32::MIN, i32::MIN, i32::MIN, -1,
+            0, i32::MIN, i32::MIN, i32::MIN, 7, 8, 9, 10,
+        ];
+        assert_eq!(r, e);
+    }
+    test_mm512_cvtps_epu32();
+}
+
+#[target_feature(enable = "avx512bw,avx512f")]
+unsafe fn test_avx512bw() {
+    #[target_feature(enable = "avx512bw,avx512f")]
+    unsafe fn test_mask_registers() {
+        #[allow(improper_ctypes)]
+        extern "C" {
+            #[link_name = "llvm.x86.avx512.kunpck.wd"]
+            fn kunpckwd(a: u32, b: u32) -> u32;
+            #[link_name = "llvm.x86.avx512.kunpck.dq"]
+            fn kunpckdq(a: u64, b: u64) -> u64;
+        }
+
+        // Like `kunpck.bw`, but for 32-bit and 64-bit mask registers.
+        assert_eq!(kunpckwd(0xAAAA_1234, 0xBBBB_5678), 0x1234_5678);
+        assert_eq!(kunpckdq(0xAAAA_AAAA_1234_5678, 0xBBBB_BBBB_9ABC_DEF0), 0x1234_5678_9ABC_DEF0);
+    }
+    test_mask_registers();
+}
+
 // Some of the constants in the tests below are just bit patterns. They should not
 // be interpreted as integers; signedness does not make sense for them, but
 // __mXXXi happens to be defined in terms of signed integers.
@@ -201,6 +578,16 @@ unsafe fn test_avx512vpopcntdq() {
     test_mm_popcnt_epi64();
 }
 
+#[track_caller]
+unsafe fn assert_eq_m512(a: __m512, b: __m512) {
+    assert_eq!(transmute::<_, [u32; 16]>(a), transmute::<_, [u32; 16]>(b))
+}
+
+#[track_caller]
+unsafe fn assert_eq_m512d(a: __m512d, b: __m512d) {
+    assert_eq!(transmute::<_, [u64; 8]>(a), transmute::<_, [u64; 8]>(b))
+}
+
 #[track_caller]
 unsafe fn assert_eq_m512i(a: __m512i, b: __m512i) {
     assert_eq!(transmute::<_, [i32; 16]>(a), transmute::<_, [i32; 16]>(b))
@@ -211,6 +598,16 @@ unsafe fn assert_eq_m256i(a: __m256i, b: __m256i) {
     assert_eq!(transmute::<_, [u64; 4]>(a), transmute::<_, [u64; 4]>(b))
 }
 
+#[track_caller]
+unsafe fn assert_eq_m128(a: __m128, b: __m128) {
+    assert_eq!(transmute::<_, [u32; 4]>(a), transmute::<_, [u32; 4]>(b))
+}
+
+#[track_caller]
+unsafe fn assert_eq_m128d(a: __m128d, b: __m128d) {
+    assert_eq!(transmute::<_, [u64; 2]>(a), transmute::<_, [u64; 2]>(b))
+}
+
 #[track_caller]
 unsafe fn assert_eq_m128i(a: __m128i, b: __m128i) {
     assert_eq!(transmute::<_, [u64; 2]>(a), transmute::<_, [u64; 2]>(b))
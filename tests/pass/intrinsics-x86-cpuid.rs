@@ -0,0 +1,19 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse4.1
+
+fn main() {
+    // Runtime feature detection agrees with the enabled target features.
+    assert!(is_x86_feature_detected!("sse4.1"));
+    // ssse3 is implied by sse4.1.
+    assert!(is_x86_feature_detected!("ssse3"));
+    assert!(!is_x86_feature_detected!("sse4.2"));
+    assert!(!is_x86_feature_detected!("avx2"));
+}
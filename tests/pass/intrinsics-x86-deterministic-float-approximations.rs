@@ -0,0 +1,41 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+avx -Zmiri-deterministic-float-approximations
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("avx"));
+
+    unsafe {
+        test_rcp_rsqrt();
+    }
+}
+
+#[target_feature(enable = "avx")]
+unsafe fn test_rcp_rsqrt() {
+    // Without the random error, the results are exact and thus the same every time.
+    for _ in 0..8 {
+        let r: [u32; 4] = transmute(_mm_rcp_ps(_mm_set1_ps(4.0)));
+        assert_eq!(r, [0.25f32.to_bits(); 4]);
+        let r: [u32; 4] = transmute(_mm_rcp_ss(_mm_set1_ps(4.0)));
+        assert_eq!(r[0], 0.25f32.to_bits());
+        let r: [u32; 4] = transmute(_mm_rsqrt_ps(_mm_set1_ps(4.0)));
+        assert_eq!(r, [0.5f32.to_bits(); 4]);
+        let r: [u32; 8] = transmute(_mm256_rcp_ps(_mm256_set1_ps(8.0)));
+        assert_eq!(r, [0.125f32.to_bits(); 8]);
+        let r: [u32; 8] = transmute(_mm256_rsqrt_ps(_mm256_set1_ps(16.0)));
+        assert_eq!(r, [0.25f32.to_bits(); 8]);
+    }
+}
@@ -0,0 +1,86 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse3,+ssse3,+sse4.1,+aes,+avx,+avx2,+avx512f
+
+// Calls one representative intrinsic of each x86 shim module, to make sure
+// they are all still routed to the right place. The intrinsics themselves are
+// tested more thoroughly in the per-feature `intrinsics-x86-*` tests.
+
+#![feature(avx512_target_feature)]
+#![feature(stdarch_x86_avx512)]
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse3"));
+    assert!(is_x86_feature_detected!("ssse3"));
+    assert!(is_x86_feature_detected!("sse4.1"));
+    assert!(is_x86_feature_detected!("aes"));
+    assert!(is_x86_feature_detected!("avx"));
+    assert!(is_x86_feature_detected!("avx2"));
+    assert!(is_x86_feature_detected!("avx512f"));
+
+    unsafe {
+        test_dispatch();
+    }
+}
+
+#[target_feature(enable = "sse3,ssse3,sse4.1,aes,avx,avx2,avx512f")]
+unsafe fn test_dispatch() {
+    // Handled directly by `emulate_x86_intrinsic`.
+    let mut sum = 0;
+    assert_eq!(_addcarry_u32(1, u32::MAX, 0, &mut sum), 1);
+    assert_eq!(sum, 0);
+    _mm_pause();
+
+    // sse
+    let r = _mm_min_ss(_mm_setr_ps(2.0, 1.0, 1.0, 1.0), _mm_setr_ps(1.0, 2.0, 2.0, 2.0));
+    assert_eq!(transmute::<_, [f32; 4]>(r), [1.0, 1.0, 1.0, 1.0]);
+
+    // sse2
+    let r = _mm_packs_epi16(_mm_set1_epi16(300), _mm_set1_epi16(-300));
+    assert_eq!(
+        transmute::<_, [i8; 16]>(r),
+        [127, 127, 127, 127, 127, 127, 127, 127, -128, -128, -128, -128, -128, -128, -128, -128]
+    );
+
+    // sse3
+    let r = _mm_hadd_ps(_mm_setr_ps(1.0, 2.0, 3.0, 4.0), _mm_setr_ps(5.0, 6.0, 7.0, 8.0));
+    assert_eq!(transmute::<_, [f32; 4]>(r), [3.0, 7.0, 11.0, 15.0]);
+
+    // ssse3
+    let r = _mm_shuffle_epi8(
+        _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
+        _mm_set1_epi8(3),
+    );
+    assert_eq!(transmute::<_, [i8; 16]>(r), [3; 16]);
+
+    // sse41
+    let r = _mm_minpos_epu16(_mm_setr_epi16(8, 7, 6, 5, 4, 3, 2, 9));
+    assert_eq!(transmute::<_, [u16; 8]>(r), [2, 6, 0, 0, 0, 0, 0, 0]);
+
+    // aesni
+    let r = _mm_aesenc_si128(_mm_setzero_si128(), _mm_setzero_si128());
+    assert_eq!(transmute::<_, [u8; 16]>(r), [0x63; 16]);
+
+    // avx
+    assert_eq!(_mm256_testz_si256(_mm256_set1_epi8(1), _mm256_set1_epi8(2)), 1);
+
+    // avx2
+    let r = _mm256_packs_epi16(_mm256_set1_epi16(300), _mm256_set1_epi16(-300));
+    assert_eq!(transmute::<_, [i8; 32]>(r)[..4], [127, 127, 127, 127]);
+
+    // avx512
+    assert_eq!(_mm_cvttss_u32(_mm_setr_ps(3.7, 0.0, 0.0, 0.0)), 3);
+}
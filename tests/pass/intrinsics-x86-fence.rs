@@ -0,0 +1,62 @@
+// We are making scheduler assumptions here.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+fn main() {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        assert!(is_x86_feature_detected!("sse2"));
+
+        tests::test_mfence();
+        tests::test_sfence_lfence();
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod tests {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    #[derive(Copy, Clone)]
+    struct SendPtr(*mut u32);
+    unsafe impl Send for SendPtr {}
+
+    /// Writes to `data` in another thread and publishes that with a relaxed store to a flag,
+    /// using `release_fence` in between. Then waits for the flag and reads `data`, using
+    /// `acquire_fence` in between. Without the fences, this would be a data race.
+    fn message_passing(release_fence: unsafe fn(), acquire_fence: unsafe fn()) {
+        static READY: AtomicBool = AtomicBool::new(false);
+        READY.store(false, Ordering::Relaxed);
+        let mut data = 0u32;
+        let ptr = SendPtr(&mut data);
+
+        let writer = thread::spawn(move || {
+            let ptr = ptr;
+            unsafe {
+                *ptr.0 = 42;
+                release_fence();
+            }
+            READY.store(true, Ordering::Relaxed);
+        });
+
+        while !READY.load(Ordering::Relaxed) {
+            thread::yield_now();
+        }
+        unsafe {
+            acquire_fence();
+            assert_eq!(*ptr.0, 42);
+        }
+        writer.join().unwrap();
+    }
+
+    pub(super) fn test_mfence() {
+        message_passing(_mm_mfence, _mm_mfence);
+    }
+
+    pub(super) fn test_sfence_lfence() {
+        message_passing(_mm_sfence, _mm_lfence);
+    }
+}
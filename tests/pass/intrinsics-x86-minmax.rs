@@ -0,0 +1,171 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+avx2
+
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse4.1"));
+    assert!(is_x86_feature_detected!("avx2"));
+
+    unsafe {
+        test_sse2();
+        test_sse41();
+        test_avx2();
+    }
+}
+
+// The functions from `std::arch` are lowered to generic integer min/max, so we call
+// the LLVM intrinsics directly and compare against them.
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse2.pmins.w"]
+    fn pminsw(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse2.pmaxs.w"]
+    fn pmaxsw(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse2.pminu.b"]
+    fn pminub(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse2.pmaxu.b"]
+    fn pmaxub(a: __m128i, b: __m128i) -> __m128i;
+
+    #[link_name = "llvm.x86.sse41.pminsb"]
+    fn pminsb(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pmaxsb"]
+    fn pmaxsb(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pminuw"]
+    fn pminuw(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pmaxuw"]
+    fn pmaxuw(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pminsd"]
+    fn pminsd(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pmaxsd"]
+    fn pmaxsd(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pminud"]
+    fn pminud(a: __m128i, b: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pmaxud"]
+    fn pmaxud(a: __m128i, b: __m128i) -> __m128i;
+
+    #[link_name = "llvm.x86.avx2.pmins.b"]
+    fn pminsb256(a: __m256i, b: __m256i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pmaxu.b"]
+    fn pmaxub256(a: __m256i, b: __m256i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pmins.w"]
+    fn pminsw256(a: __m256i, b: __m256i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pminu.w"]
+    fn pminuw256(a: __m256i, b: __m256i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pmaxs.d"]
+    fn pmaxsd256(a: __m256i, b: __m256i) -> __m256i;
+    #[link_name = "llvm.x86.avx2.pmaxu.d"]
+    fn pmaxud256(a: __m256i, b: __m256i) -> __m256i;
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn assert_eq_m128i(a: __m128i, b: __m128i) {
+    assert_eq!(transmute::<_, [u64; 2]>(a), transmute::<_, [u64; 2]>(b))
+}
+
+#[target_feature(enable = "avx")]
+unsafe fn assert_eq_m256i(a: __m256i, b: __m256i) {
+    assert_eq!(transmute::<_, [u64; 4]>(a), transmute::<_, [u64; 4]>(b))
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn test_sse2() {
+    // With the high bit set, an element is the smallest signed value but a large
+    // unsigned value.
+    let a = _mm_set1_epi16(0x8000u16 as i16);
+    let b = _mm_set1_epi16(1);
+    assert_eq_m128i(pminsw(a, b), a);
+    assert_eq_m128i(pmaxsw(a, b), b);
+    assert_eq_m128i(pminsw(a, b), _mm_min_epi16(a, b));
+    assert_eq_m128i(pmaxsw(a, b), _mm_max_epi16(a, b));
+
+    let a = _mm_set1_epi8(0x80u8 as i8);
+    let b = _mm_set1_epi8(1);
+    assert_eq_m128i(pminub(a, b), b);
+    assert_eq_m128i(pmaxub(a, b), a);
+    assert_eq_m128i(pminub(a, b), _mm_min_epu8(a, b));
+    assert_eq_m128i(pmaxub(a, b), _mm_max_epu8(a, b));
+
+    // Each element is compared on its own.
+    let a = _mm_setr_epi16(1, -2, 3, -4, 5, -6, 7, -8);
+    let b = _mm_setr_epi16(-1, 2, -3, 4, -5, 6, -7, 8);
+    assert_eq_m128i(pminsw(a, b), _mm_setr_epi16(-1, -2, -3, -4, -5, -6, -7, -8));
+    assert_eq_m128i(pmaxsw(a, b), _mm_setr_epi16(1, 2, 3, 4, 5, 6, 7, 8));
+}
+
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_sse41() {
+    let a = _mm_set1_epi8(0x80u8 as i8);
+    let b = _mm_set1_epi8(1);
+    assert_eq_m128i(pminsb(a, b), a);
+    assert_eq_m128i(pmaxsb(a, b), b);
+    assert_eq_m128i(pminsb(a, b), _mm_min_epi8(a, b));
+    assert_eq_m128i(pmaxsb(a, b), _mm_max_epi8(a, b));
+
+    let a = _mm_set1_epi16(0x8000u16 as i16);
+    let b = _mm_set1_epi16(1);
+    assert_eq_m128i(pminuw(a, b), b);
+    assert_eq_m128i(pmaxuw(a, b), a);
+    assert_eq_m128i(pminuw(a, b), _mm_min_epu16(a, b));
+    assert_eq_m128i(pmaxuw(a, b), _mm_max_epu16(a, b));
+
+    let a = _mm_set1_epi32(0x8000_0000u32 as i32);
+    let b = _mm_set1_epi32(1);
+    assert_eq_m128i(pminsd(a, b), a);
+    assert_eq_m128i(pmaxsd(a, b), b);
+    assert_eq_m128i(pminud(a, b), b);
+    assert_eq_m128i(pmaxud(a, b), a);
+    assert_eq_m128i(pminsd(a, b), _mm_min_epi32(a, b));
+    assert_eq_m128i(pmaxsd(a, b), _mm_max_epi32(a, b));
+    assert_eq_m128i(pminud(a, b), _mm_min_epu32(a, b));
+    assert_eq_m128i(pmaxud(a, b), _mm_max_epu32(a, b));
+
+    // Each element is compared on its own.
+    let a = _mm_setr_epi32(i32::MIN, 0, -1, 2);
+    let b = _mm_setr_epi32(i32::MAX, -1, 0, 1);
+    assert_eq_m128i(pminsd(a, b), _mm_setr_epi32(i32::MIN, -1, -1, 1));
+    assert_eq_m128i(pminud(a, b), _mm_setr_epi32(i32::MAX, 0, 0, 1));
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn test_avx2() {
+    let a = _mm256_set1_epi8(0x80u8 as i8);
+    let b = _mm256_set1_epi8(1);
+    assert_eq_m256i(pminsb256(a, b), a);
+    assert_eq_m256i(pmaxub256(a, b), a);
+    assert_eq_m256i(pminsb256(a, b), _mm256_min_epi8(a, b));
+    assert_eq_m256i(pmaxub256(a, b), _mm256_max_epu8(a, b));
+
+    let a = _mm256_set1_epi16(0x8000u16 as i16);
+    let b = _mm256_set1_epi16(1);
+    assert_eq_m256i(pminsw256(a, b), a);
+    assert_eq_m256i(pminuw256(a, b), b);
+    assert_eq_m256i(pminsw256(a, b), _mm256_min_epi16(a, b));
+    assert_eq_m256i(pminuw256(a, b), _mm256_min_epu16(a, b));
+
+    let a = _mm256_set1_epi32(0x8000_0000u32 as i32);
+    let b = _mm256_set1_epi32(1);
+    assert_eq_m256i(pmaxsd256(a, b), b);
+    assert_eq_m256i(pmaxud256(a, b), a);
+    assert_eq_m256i(pmaxsd256(a, b), _mm256_max_epi32(a, b));
+    assert_eq_m256i(pmaxud256(a, b), _mm256_max_epu32(a, b));
+
+    // Both 128-bit halves are compared.
+    let a = _mm256_setr_epi32(1, 2, 3, 4, -5, -6, -7, -8);
+    let b = _mm256_setr_epi32(-1, -2, -3, -4, 5, 6, 7, 8);
+    assert_eq_m256i(pmaxsd256(a, b), _mm256_setr_epi32(1, 2, 3, 4, 5, 6, 7, 8));
+}
@@ -0,0 +1,79 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse2
+
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+#[repr(align(16))]
+struct Aligned<T>(T);
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse2"));
+
+    unsafe {
+        test_stream();
+        test_movnt();
+    }
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn test_stream() {
+    let mut f = Aligned([0.0f32; 4]);
+    _mm_stream_ps(f.0.as_mut_ptr(), _mm_setr_ps(1.0, 2.0, 3.0, 4.0));
+    assert_eq!(f.0, [1.0, 2.0, 3.0, 4.0]);
+
+    let mut d = Aligned([0.0f64; 2]);
+    _mm_stream_pd(d.0.as_mut_ptr(), _mm_setr_pd(5.0, 6.0));
+    assert_eq!(d.0, [5.0, 6.0]);
+
+    let mut i = Aligned([0i32; 4]);
+    _mm_stream_si128(i.0.as_mut_ptr().cast(), _mm_setr_epi32(7, 8, 9, 10));
+    assert_eq!(i.0, [7, 8, 9, 10]);
+    _mm_sfence();
+}
+
+// Calls the LLVM intrinsics directly, since the functions above may be lowered
+// to a generic non-temporal store instead.
+#[target_feature(enable = "sse2")]
+unsafe fn test_movnt() {
+    let mut f = Aligned([0.0f32; 4]);
+    movntps(f.0.as_mut_ptr().cast(), _mm_setr_ps(1.0, 2.0, 3.0, 4.0));
+    assert_eq!(f.0, [1.0, 2.0, 3.0, 4.0]);
+
+    let mut d = Aligned([0.0f64; 2]);
+    movntpd(d.0.as_mut_ptr().cast(), _mm_setr_pd(5.0, 6.0));
+    assert_eq!(d.0, [5.0, 6.0]);
+
+    let mut i = Aligned([0i32; 4]);
+    movntdq(i.0.as_mut_ptr().cast(), _mm_setr_epi32(7, 8, 9, 10));
+    assert_eq!(i.0, [7, 8, 9, 10]);
+
+    // Only the 16 bytes at the destination are written.
+    let mut wide = Aligned([0u64; 4]);
+    movntdq(wide.0.as_mut_ptr().add(2).cast(), transmute([u64::MAX; 2]));
+    assert_eq!(wide.0, [0, 0, u64::MAX, u64::MAX]);
+    _mm_sfence();
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse.movnt.ps"]
+    fn movntps(ptr: *mut u8, a: __m128);
+    #[link_name = "llvm.x86.sse2.movnt.pd"]
+    fn movntpd(ptr: *mut u8, a: __m128d);
+    #[link_name = "llvm.x86.sse2.movnt.dq"]
+    fn movntdq(ptr: *mut u8, a: __m128i);
+}
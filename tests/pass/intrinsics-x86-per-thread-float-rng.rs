@@ -0,0 +1,67 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse -Zmiri-per-thread-float-rng -Zmiri-seed=42
+//@revisions: alone interleaved
+//@[interleaved]compile-flags: -Zmiri-env-set=INTERLEAVED=1
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+use std::thread;
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse"));
+
+    // Both threads draw the random error of `_mm_rcp_ps` from their own RNG, so what one thread
+    // computes does not depend on how it is interleaved with the other. Both revisions print the
+    // results of the first thread, and the expected output is the same for both, even though in
+    // the `interleaved` revision, a second thread (and the main thread) draw errors in between.
+    let interleaved = std::env::var_os("INTERLEAVED").is_some();
+    let first = thread::spawn(rcp_loop);
+    let second = interleaved.then(|| thread::spawn(rcp_loop));
+    if interleaved {
+        for _ in 0..4 {
+            check(unsafe { rcp() });
+            thread::yield_now();
+        }
+    }
+
+    for r in first.join().unwrap() {
+        check(r);
+        println!("{:08x?}", r.map(f32::to_bits));
+    }
+    if let Some(second) = second {
+        second.join().unwrap().into_iter().for_each(check);
+    }
+}
+
+fn rcp_loop() -> Vec<[f32; 4]> {
+    let mut results = Vec::new();
+    for _ in 0..4 {
+        results.push(unsafe { rcp() });
+        thread::yield_now();
+    }
+    results
+}
+
+fn check(r: [f32; 4]) {
+    let e = [0.25f32, 0.125, 0.0625, 0.01];
+    for i in 0..4 {
+        let rel_err = (r[i] - e[i]).abs() / e[i];
+        assert!(rel_err <= 2.0f32.powi(-11), "{} is not close to {}", r[i], e[i]);
+    }
+}
+
+#[target_feature(enable = "sse")]
+unsafe fn rcp() -> [f32; 4] {
+    transmute(_mm_rcp_ps(_mm_setr_ps(4.0, 8.0, 16.0, 100.0)))
+}
@@ -0,0 +1,44 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+popcnt
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+fn main() {
+    assert!(is_x86_feature_detected!("popcnt"));
+
+    unsafe {
+        test_popcnt();
+    }
+}
+
+#[target_feature(enable = "popcnt")]
+unsafe fn test_popcnt() {
+    #[target_feature(enable = "popcnt")]
+    unsafe fn test_popcnt32() {
+        for (x, e) in [(0, 0), (-1, 32), (0x0F0F_00FF, 16)] {
+            assert_eq!(_popcnt32(x), e);
+        }
+    }
+    test_popcnt32();
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "popcnt")]
+    unsafe fn test_popcnt64() {
+        for (x, e) in [(0, 0), (-1, 64), (0x0F0F_00FF_0000_0001, 17)] {
+            assert_eq!(_popcnt64(x), e);
+        }
+    }
+    #[cfg(target_arch = "x86_64")]
+    test_popcnt64();
+}
@@ -0,0 +1,140 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+avx2
+
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("avx"));
+    assert!(is_x86_feature_detected!("avx2"));
+
+    unsafe {
+        test_shuffle_ps();
+        test_shuffle_pd();
+        test_shuffle_ps_256();
+        test_shuffle_pd_256();
+        test_shuffle_epi32();
+        test_shuffle_epi32_256();
+    }
+}
+
+// The functions from `std::arch` are lowered to a generic shuffle, so we call the
+// LLVM intrinsics directly and compare against them.
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse.shuf.ps"]
+    fn shufps(a: __m128, b: __m128, imm: i8) -> __m128;
+    #[link_name = "llvm.x86.sse2.shuf.pd"]
+    fn shufpd(a: __m128d, b: __m128d, imm: i8) -> __m128d;
+    #[link_name = "llvm.x86.avx.shuf.ps.256"]
+    fn shufps256(a: __m256, b: __m256, imm: i8) -> __m256;
+    #[link_name = "llvm.x86.avx.shuf.pd.256"]
+    fn shufpd256(a: __m256d, b: __m256d, imm: i8) -> __m256d;
+    #[link_name = "llvm.x86.sse2.pshuf.d"]
+    fn pshufd(a: __m128i, imm: i8) -> __m128i;
+    #[link_name = "llvm.x86.avx2.pshuf.d"]
+    fn pshufd256(a: __m256i, imm: i8) -> __m256i;
+}
+
+#[target_feature(enable = "sse")]
+unsafe fn test_shuffle_ps() {
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let b = _mm_setr_ps(5.0, 6.0, 7.0, 8.0);
+
+    // The identity selects the lower half of `a` and the upper half of `b`.
+    let r: [f32; 4] = transmute(shufps(a, b, 0b11_10_01_00));
+    assert_eq!(r, [1.0, 2.0, 7.0, 8.0]);
+    assert_eq!(r, transmute::<_, [f32; 4]>(_mm_shuffle_ps::<0b11_10_01_00>(a, b)));
+
+    // Reverse `a` into the lower half and broadcast `b[0]` into the upper half.
+    let r: [f32; 4] = transmute(shufps(a, b, 0b00_00_10_11));
+    assert_eq!(r, [4.0, 3.0, 5.0, 5.0]);
+    assert_eq!(r, transmute::<_, [f32; 4]>(_mm_shuffle_ps::<0b00_00_10_11>(a, b)));
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn test_shuffle_pd() {
+    let a = _mm_setr_pd(1.0, 2.0);
+    let b = _mm_setr_pd(3.0, 4.0);
+
+    let r: [f64; 2] = transmute(shufpd(a, b, 0b10));
+    assert_eq!(r, [1.0, 4.0]);
+    assert_eq!(r, transmute::<_, [f64; 2]>(_mm_shuffle_pd::<0b10>(a, b)));
+
+    let r: [f64; 2] = transmute(shufpd(a, b, 0b01));
+    assert_eq!(r, [2.0, 3.0]);
+    assert_eq!(r, transmute::<_, [f64; 2]>(_mm_shuffle_pd::<0b01>(a, b)));
+}
+
+#[target_feature(enable = "avx")]
+unsafe fn test_shuffle_ps_256() {
+    let a = _mm256_setr_ps(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0);
+    let b = _mm256_setr_ps(9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0);
+
+    // The same selectors are used for both 128-bit chunks.
+    let r: [f32; 8] = transmute(shufps256(a, b, 0b11_10_01_00));
+    assert_eq!(r, [1.0, 2.0, 11.0, 12.0, 5.0, 6.0, 15.0, 16.0]);
+    assert_eq!(r, transmute::<_, [f32; 8]>(_mm256_shuffle_ps::<0b11_10_01_00>(a, b)));
+
+    let r: [f32; 8] = transmute(shufps256(a, b, 0b01_00_11_10));
+    assert_eq!(r, [3.0, 4.0, 9.0, 10.0, 7.0, 8.0, 13.0, 14.0]);
+    assert_eq!(r, transmute::<_, [f32; 8]>(_mm256_shuffle_ps::<0b01_00_11_10>(a, b)));
+}
+
+#[target_feature(enable = "avx")]
+unsafe fn test_shuffle_pd_256() {
+    let a = _mm256_setr_pd(1.0, 2.0, 3.0, 4.0);
+    let b = _mm256_setr_pd(5.0, 6.0, 7.0, 8.0);
+
+    // Unlike `shufps`, the upper chunk uses the next two bits of `imm`.
+    let r: [f64; 4] = transmute(shufpd256(a, b, 0b10_10));
+    assert_eq!(r, [1.0, 6.0, 3.0, 8.0]);
+    assert_eq!(r, transmute::<_, [f64; 4]>(_mm256_shuffle_pd::<0b10_10>(a, b)));
+
+    let r: [f64; 4] = transmute(shufpd256(a, b, 0b01_10));
+    assert_eq!(r, [1.0, 6.0, 4.0, 7.0]);
+    assert_eq!(r, transmute::<_, [f64; 4]>(_mm256_shuffle_pd::<0b01_10>(a, b)));
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn test_shuffle_epi32() {
+    let a = _mm_setr_epi32(1, 2, 3, 4);
+
+    // Broadcast element 0.
+    let r: [i32; 4] = transmute(pshufd(a, 0b00_00_00_00));
+    assert_eq!(r, [1, 1, 1, 1]);
+    assert_eq!(r, transmute::<_, [i32; 4]>(_mm_shuffle_epi32::<0b00_00_00_00>(a)));
+
+    // Reverse the elements.
+    let r: [i32; 4] = transmute(pshufd(a, 0b00_01_10_11));
+    assert_eq!(r, [4, 3, 2, 1]);
+    assert_eq!(r, transmute::<_, [i32; 4]>(_mm_shuffle_epi32::<0b00_01_10_11>(a)));
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn test_shuffle_epi32_256() {
+    let a = _mm256_setr_epi32(1, 2, 3, 4, 5, 6, 7, 8);
+
+    // Each 128-bit chunk broadcasts its own element 0.
+    let r: [i32; 8] = transmute(pshufd256(a, 0b00_00_00_00));
+    assert_eq!(r, [1, 1, 1, 1, 5, 5, 5, 5]);
+    assert_eq!(r, transmute::<_, [i32; 8]>(_mm256_shuffle_epi32::<0b00_00_00_00>(a)));
+
+    // Each 128-bit chunk is reversed on its own.
+    let r: [i32; 8] = transmute(pshufd256(a, 0b00_01_10_11));
+    assert_eq!(r, [4, 3, 2, 1, 8, 7, 6, 5]);
+    assert_eq!(r, transmute::<_, [i32; 8]>(_mm256_shuffle_epi32::<0b00_01_10_11>(a)));
+}
@@ -132,6 +132,14 @@ mod tests {
             let r = _mm_sad_epu8(a, b);
             let e = _mm_setr_epi64x(1020, 614);
             assert_eq_m128i(r, e);
+
+            // The largest possible sum only needs 16 bits, the upper 48 bits of
+            // each element are zero.
+            let r = _mm_sad_epu8(_mm_set1_epi8(-1), _mm_setzero_si128());
+            for x in transmute::<_, [u64; 2]>(r) {
+                assert_eq!(x, 2040);
+                assert_eq!(x >> 16, 0);
+            }
         }
         test_mm_sad_epu8();
 
@@ -705,6 +713,19 @@ mod tests {
 
             let r = _mm_cvtpd_ps(_mm_setr_pd(f32::MAX as f64, f32::MIN as f64));
             assert_eq_m128(r, _mm_setr_ps(f32::MAX, f32::MIN, 0.0, 0.0));
+
+            // Narrowing rounds to nearest, ties to even.
+            let r = _mm_cvtpd_ps(_mm_setr_pd(1.0 + f64::EPSILON, 16777217.0));
+            assert_eq_m128(r, _mm_setr_ps(1.0, 16777216.0, 0.0, 0.0));
+            let r = _mm_cvtpd_ps(_mm_setr_pd(16777219.0, 0.1));
+            assert_eq_m128(r, _mm_setr_ps(16777220.0, 0.1, 0.0, 0.0));
+
+            let r = _mm_cvtpd_ps(_mm_setr_pd(f64::INFINITY, f64::NEG_INFINITY));
+            assert_eq_m128(r, _mm_setr_ps(f32::INFINITY, f32::NEG_INFINITY, 0.0, 0.0));
+
+            let r: [f32; 4] = transmute(_mm_cvtpd_ps(_mm_setr_pd(f64::NAN, 1.0)));
+            assert!(r[0].is_nan());
+            assert_eq!(r[1..], [1.0, 0.0, 0.0]);
         }
         test_mm_cvtpd_ps();
 
@@ -715,6 +736,13 @@ mod tests {
 
             let r = _mm_cvtps_pd(_mm_setr_ps(f32::MAX, f32::INFINITY, f32::NEG_INFINITY, f32::MIN));
             assert_eq_m128d(r, _mm_setr_pd(f32::MAX as f64, f64::INFINITY));
+
+            let r = _mm_cvtps_pd(_mm_setr_ps(0.1, f32::NEG_INFINITY, 1.0, 2.0));
+            assert_eq_m128d(r, _mm_setr_pd(0.1f32 as f64, f64::NEG_INFINITY));
+
+            let r: [f64; 2] = transmute(_mm_cvtps_pd(_mm_setr_ps(f32::NAN, 1.0, 2.0, 3.0)));
+            assert!(r[0].is_nan());
+            assert_eq!(r[1], 1.0);
         }
         test_mm_cvtps_pd();
 
@@ -749,6 +777,20 @@ mod tests {
         }
         test_mm_cvttpd_epi32();
 
+        // Only the `cvtt` variants truncate; the others round to nearest, with ties to even.
+        #[target_feature(enable = "sse2")]
+        unsafe fn test_mm_cvt_truncate_vs_round() {
+            let a = _mm_setr_ps(2.7, -2.7, 2.5, 3.5);
+            assert_eq_m128i(_mm_cvtps_epi32(a), _mm_setr_epi32(3, -3, 2, 4));
+            assert_eq_m128i(_mm_cvttps_epi32(a), _mm_setr_epi32(2, -2, 2, 3));
+
+            // The upper two lanes of the narrowing conversions are zeroed.
+            let a = _mm_setr_pd(2.7, -2.7);
+            assert_eq_m128i(_mm_cvtpd_epi32(a), _mm_setr_epi32(3, -3, 0, 0));
+            assert_eq_m128i(_mm_cvttpd_epi32(a), _mm_setr_epi32(2, -2, 0, 0));
+        }
+        test_mm_cvt_truncate_vs_round();
+
         #[target_feature(enable = "sse2")]
         unsafe fn test_mm_cvtsd_si32() {
             let r = _mm_cvtsd_si32(_mm_setr_pd(-2.0, 5.0));
@@ -798,6 +840,23 @@ mod tests {
         #[cfg(target_arch = "x86_64")]
         test_mm_cvttsd_si64();
 
+        // Intrinsics only available on x86_64
+        #[cfg(target_arch = "x86_64")]
+        #[target_feature(enable = "sse2")]
+        unsafe fn test_mm_cvtsi64_si128() {
+            let x = 0xfedc_ba98_7654_3210_u64;
+            // The upper element is zeroed.
+            let r = _mm_cvtsi64_si128(x as i64);
+            assert_eq!(transmute::<_, [u64; 2]>(r), [x, 0]);
+            assert_eq!(_mm_cvtsi128_si64(r) as u64, x);
+            // Only the lower element is read.
+            let r = _mm_cvtsi128_si64(_mm_set_epi64x(-1, x as i64));
+            assert_eq!(r as u64, x);
+            assert_eq!(_mm_cvtsi128_si64x(_mm_cvtsi64x_si128(x as i64)) as u64, x);
+        }
+        #[cfg(target_arch = "x86_64")]
+        test_mm_cvtsi64_si128();
+
         #[target_feature(enable = "sse2")]
         unsafe fn test_mm_cvtsd_ss() {
             let a = _mm_setr_ps(-1.1, -2.2, 3.3, 4.4);
@@ -816,6 +875,18 @@ mod tests {
                 r,
                 _mm_setr_ps(f32::INFINITY, f32::NEG_INFINITY, f32::MAX, f32::NEG_INFINITY),
             );
+
+            // The first element is rounded to nearest, and the upper elements are
+            // copied bit-for-bit from `a` (not from `b`), even when they are NaN.
+            let nan = f32::from_bits(0x7fc0_1234);
+            let a = _mm_setr_ps(-1.1, nan, -0.0, 4.4);
+            let b = _mm_setr_pd(16777217.0, f64::NAN);
+
+            let r: [u32; 4] = transmute(_mm_cvtsd_ss(a, b));
+            assert_eq!(
+                r,
+                [16777216.0f32.to_bits(), nan.to_bits(), (-0.0f32).to_bits(), 4.4f32.to_bits()],
+            );
         }
         test_mm_cvtsd_ss();
 
@@ -832,6 +903,15 @@ mod tests {
 
             let r = _mm_cvtss_sd(a, b);
             assert_eq_m128d(r, _mm_setr_pd(f64::NEG_INFINITY, f64::INFINITY));
+
+            // The upper element is copied bit-for-bit from `a` (not from `b`),
+            // even when it is NaN.
+            let nan = f64::from_bits(0x7ff8_0000_0000_1234);
+            let a = _mm_setr_pd(-1.1, nan);
+            let b = _mm_setr_ps(0.1, f32::NAN, f32::NAN, f32::NAN);
+
+            let r: [u64; 2] = transmute(_mm_cvtss_sd(a, b));
+            assert_eq!(r, [(0.1f32 as f64).to_bits(), nan.to_bits()]);
         }
         test_mm_cvtss_sd();
 
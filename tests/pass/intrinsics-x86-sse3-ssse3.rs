@@ -10,6 +10,8 @@
 // SSSE3 implicitly enables SSE3
 //@compile-flags: -C target-feature=+ssse3
 
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
 use core::mem::transmute;
 #[cfg(target_arch = "x86")]
 use std::arch::x86::*;
@@ -49,6 +51,29 @@ unsafe fn test_sse3() {
     }
     test_mm_addsub_pd();
 
+    // Calls the LLVM intrinsics directly, since `stdarch` may implement the
+    // functions above with generic SIMD operations instead.
+    #[target_feature(enable = "sse3")]
+    unsafe fn test_addsub_intrinsics() {
+        #[allow(improper_ctypes)]
+        extern "C" {
+            #[link_name = "llvm.x86.sse3.addsub.ps"]
+            fn addsubps(a: __m128, b: __m128) -> __m128;
+            #[link_name = "llvm.x86.sse3.addsub.pd"]
+            fn addsubpd(a: __m128d, b: __m128d) -> __m128d;
+        }
+
+        // Lane 0 is a subtraction, lane 1 an addition, and so on.
+        let a = _mm_setr_ps(10.0, 20.0, 30.0, 40.0);
+        let b = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+        assert_eq_m128(addsubps(a, b), _mm_setr_ps(9.0, 22.0, 27.0, 44.0));
+
+        let a = _mm_setr_pd(10.0, 20.0);
+        let b = _mm_setr_pd(1.0, 2.0);
+        assert_eq_m128d(addsubpd(a, b), _mm_setr_pd(9.0, 22.0));
+    }
+    test_addsub_intrinsics();
+
     #[target_feature(enable = "sse3")]
     unsafe fn test_mm_hadd_ps() {
         let a = _mm_setr_ps(-1.0, 5.0, 0.0, -10.0);
@@ -85,6 +110,24 @@ unsafe fn test_sse3() {
     }
     test_mm_hsub_pd();
 
+    // Distinct powers of two make it visible which source lanes ended up in
+    // which result lane: the low half of the result comes from adjacent pairs
+    // of `a`, the high half from adjacent pairs of `b`, and subtraction
+    // computes the even lane minus the odd lane.
+    #[target_feature(enable = "sse3")]
+    unsafe fn test_horizontal_lane_order() {
+        let a = _mm_setr_ps(1.0, 2.0, 4.0, 8.0);
+        let b = _mm_setr_ps(16.0, 32.0, 64.0, 128.0);
+        assert_eq_m128(_mm_hadd_ps(a, b), _mm_setr_ps(3.0, 12.0, 48.0, 192.0));
+        assert_eq_m128(_mm_hsub_ps(a, b), _mm_setr_ps(-1.0, -4.0, -16.0, -64.0));
+
+        let a = _mm_setr_pd(1.0, 2.0);
+        let b = _mm_setr_pd(16.0, 32.0);
+        assert_eq_m128d(_mm_hadd_pd(a, b), _mm_setr_pd(3.0, 48.0));
+        assert_eq_m128d(_mm_hsub_pd(a, b), _mm_setr_pd(-1.0, -16.0));
+    }
+    test_horizontal_lane_order();
+
     #[target_feature(enable = "sse3")]
     unsafe fn test_mm_lddqu_si128() {
         let a = _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
@@ -102,6 +145,19 @@ unsafe fn test_ssse3() {
     unsafe fn test_mm_abs_epi8() {
         let r = _mm_abs_epi8(_mm_set1_epi8(-5));
         assert_eq_m128i(r, _mm_set1_epi8(5));
+
+        // `i8::MIN` has no positive counterpart and wraps around to itself.
+        #[rustfmt::skip]
+        let a = _mm_setr_epi8(
+            0, 1, -1, 5, -5, i8::MAX, i8::MIN, 100,
+            -100, -32, 32, 127, -127, 64, -64, 2,
+        );
+        #[rustfmt::skip]
+        let e = _mm_setr_epi8(
+            0, 1, 1, 5, 5, i8::MAX, i8::MIN, 100,
+            100, 32, 32, 127, 127, 64, 64, 2,
+        );
+        assert_eq_m128i(_mm_abs_epi8(a), e);
     }
     test_mm_abs_epi8();
 
@@ -109,6 +165,10 @@ unsafe fn test_ssse3() {
     unsafe fn test_mm_abs_epi16() {
         let r = _mm_abs_epi16(_mm_set1_epi16(-5));
         assert_eq_m128i(r, _mm_set1_epi16(5));
+
+        // `i16::MIN` has no positive counterpart and wraps around to itself.
+        let r = _mm_abs_epi16(_mm_setr_epi16(0, 1, -1, -5, i16::MAX, i16::MIN, -1000, 1000));
+        assert_eq_m128i(r, _mm_setr_epi16(0, 1, 1, 5, i16::MAX, i16::MIN, 1000, 1000));
     }
     test_mm_abs_epi16();
 
@@ -116,6 +176,10 @@ unsafe fn test_ssse3() {
     unsafe fn test_mm_abs_epi32() {
         let r = _mm_abs_epi32(_mm_set1_epi32(-5));
         assert_eq_m128i(r, _mm_set1_epi32(5));
+
+        // `i32::MIN` has no positive counterpart and wraps around to itself.
+        let r = _mm_abs_epi32(_mm_setr_epi32(-1, i32::MAX, i32::MIN, -1000));
+        assert_eq_m128i(r, _mm_setr_epi32(1, i32::MAX, i32::MIN, 1000));
     }
     test_mm_abs_epi32();
 
@@ -369,6 +433,37 @@ unsafe fn test_ssse3() {
         assert_eq_m128i(r, expected);
     }
     test_mm_sign_epi32();
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn test_mm_alignr_epi8() {
+        #[allow(improper_ctypes)]
+        extern "C" {
+            #[link_name = "llvm.x86.ssse3.palign.r.128"]
+            fn palignr(a: __m128i, b: __m128i, count: u8) -> __m128i;
+        }
+
+        let a = _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+        let b = _mm_setr_epi8(17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32);
+
+        // With a count of 0, the result is `b`.
+        assert_eq_m128i(palignr(a, b, 0), b);
+        assert_eq_m128i(_mm_alignr_epi8::<0>(a, b), b);
+        // With a count of 16, the result is `a`.
+        assert_eq_m128i(palignr(a, b, 16), a);
+        assert_eq_m128i(_mm_alignr_epi8::<16>(a, b), a);
+
+        let e = _mm_setr_epi8(21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 1, 2, 3, 4);
+        assert_eq_m128i(palignr(a, b, 4), e);
+        assert_eq_m128i(_mm_alignr_epi8::<4>(a, b), e);
+
+        // Beyond 16, zeros are shifted in.
+        let e = _mm_setr_epi8(5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0);
+        assert_eq_m128i(palignr(a, b, 20), e);
+        assert_eq_m128i(_mm_alignr_epi8::<20>(a, b), e);
+        assert_eq_m128i(palignr(a, b, 32), _mm_setzero_si128());
+        assert_eq_m128i(_mm_alignr_epi8::<32>(a, b), _mm_setzero_si128());
+    }
+    test_mm_alignr_epi8();
 }
 
 #[track_caller]
@@ -9,6 +9,8 @@
 //@ignore-target-wasm32
 //@compile-flags: -C target-feature=+sse4.1
 
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
 #[cfg(target_arch = "x86")]
 use std::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
@@ -60,6 +62,11 @@ unsafe fn test_sse41() {
         let b = _mm_setr_pd(1.0, 4.0);
         let e = _mm_setr_pd(14.0, 0.0);
         assert_eq_m128d(_mm_dp_pd::<0b00110001>(a, b), e);
+
+        // Only the products selected by the high bits are summed, and the sum is
+        // broadcast to every element selected by the low bits.
+        let e = _mm_setr_pd(12.0, 12.0);
+        assert_eq_m128d(_mm_dp_pd::<0b00100011>(a, b), e);
     }
     test_mm_dp_pd();
 
@@ -69,6 +76,13 @@ unsafe fn test_sse41() {
         let b = _mm_setr_ps(1.0, 4.0, 0.5, 10.0);
         let e = _mm_setr_ps(14.5, 0.0, 14.5, 0.0);
         assert_eq_m128(_mm_dp_ps::<0b01110101>(a, b), e);
+
+        // Only the products selected by the high bits are summed.
+        let e = _mm_setr_ps(0.0, 100.0, 0.0, 0.0);
+        assert_eq_m128(_mm_dp_ps::<0b1000_0010>(a, b), e);
+        // The sum is broadcast to every element selected by the low bits.
+        let e = _mm_set1_ps(114.5);
+        assert_eq_m128(_mm_dp_ps::<0b1111_1111>(a, b), e);
     }
     test_mm_dp_ps();
 
@@ -118,6 +132,32 @@ unsafe fn test_sse41() {
     }
     test_round_nearest_f32();
 
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn test_round_halves() {
+        // Values exactly between two integers, with the exception flag suppressed.
+        const NEAREST: i32 = _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC;
+        const FLOOR: i32 = _MM_FROUND_TO_NEG_INF | _MM_FROUND_NO_EXC;
+        const CEIL: i32 = _MM_FROUND_TO_POS_INF | _MM_FROUND_NO_EXC;
+
+        let a = _mm_setr_ps(0.5, 1.5, -0.5, -1.5);
+        assert_eq_m128(_mm_round_ps::<NEAREST>(a), _mm_setr_ps(0.0, 2.0, -0.0, -2.0));
+        assert_eq_m128(_mm_round_ps::<FLOOR>(a), _mm_setr_ps(0.0, 1.0, -1.0, -2.0));
+        assert_eq_m128(_mm_round_ps::<CEIL>(a), _mm_setr_ps(1.0, 2.0, -0.0, -1.0));
+
+        let a = _mm_setr_pd(2.5, -2.5);
+        assert_eq_m128d(_mm_round_pd::<NEAREST>(a), _mm_setr_pd(2.0, -2.0));
+        assert_eq_m128d(_mm_round_pd::<FLOOR>(a), _mm_setr_pd(2.0, -3.0));
+        assert_eq_m128d(_mm_round_pd::<CEIL>(a), _mm_setr_pd(3.0, -2.0));
+
+        // The scalar forms only round the first element of `b`.
+        let a = _mm_setr_pd(0.5, 0.5);
+        let b = _mm_setr_pd(3.5, 7.5);
+        assert_eq_m128d(_mm_round_sd::<NEAREST>(a, b), _mm_setr_pd(4.0, 0.5));
+        assert_eq_m128d(_mm_round_sd::<FLOOR>(a, b), _mm_setr_pd(3.0, 0.5));
+        assert_eq_m128d(_mm_round_sd::<CEIL>(a, b), _mm_setr_pd(4.0, 0.5));
+    }
+    test_round_halves();
+
     #[target_feature(enable = "sse4.1")]
     unsafe fn test_round_floor_f32() {
         #[target_feature(enable = "sse4.1")]
@@ -427,6 +467,18 @@ unsafe fn test_sse41() {
         let r = _mm_minpos_epu16(a);
         let e = _mm_setr_epi16(13, 5, 0, 0, 0, 0, 0, 0);
         assert_eq_m128i(r, e);
+
+        // Elements are compared as unsigned, so negative values are large.
+        let a = _mm_setr_epi16(7, -1, i16::MIN, 7, 8, -7, 9, 7);
+        let r = _mm_minpos_epu16(a);
+        let e = _mm_setr_epi16(7, 0, 0, 0, 0, 0, 0, 0);
+        assert_eq_m128i(r, e);
+
+        // All elements are equal.
+        let a = _mm_set1_epi16(-1);
+        let r = _mm_minpos_epu16(a);
+        let e = _mm_setr_epi16(-1, 0, 0, 0, 0, 0, 0, 0);
+        assert_eq_m128i(r, e);
     }
     test_mm_minpos_epu16();
 
@@ -522,6 +574,117 @@ unsafe fn test_sse41() {
         assert_eq!(r, 1);
     }
     test_mm_testnzc_si128();
+
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn test_mm_blendv_epi8() {
+        let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let b = _mm_setr_epi8(16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31);
+        // Only the high bit of each mask byte matters.
+        let mask = _mm_setr_epi8(0, -1, 1, -128, 127, -2, 0, -1, 0, -1, 0, -1, 0, -1, 0, -1);
+        let e = _mm_setr_epi8(0, 17, 2, 19, 4, 21, 6, 23, 8, 25, 10, 27, 12, 29, 14, 31);
+        assert_eq_m128i(_mm_blendv_epi8(a, b, mask), e);
+        assert_eq_m128i(pblendvb(a, b, mask), e);
+
+        let mask = _mm_set1_epi8(-128);
+        assert_eq_m128i(_mm_blendv_epi8(a, b, mask), b);
+        assert_eq_m128i(pblendvb(a, b, mask), b);
+    }
+    test_mm_blendv_epi8();
+
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn test_mm_blendv_ps_pd() {
+        let a = _mm_setr_ps(0.0, 1.0, 2.0, 3.0);
+        let b = _mm_setr_ps(4.0, 5.0, 6.0, 7.0);
+        // Negative zero has the high bit set.
+        let mask = _mm_setr_ps(0.0, -0.0, 1.0, -1.0);
+        assert_eq_m128(_mm_blendv_ps(a, b, mask), _mm_setr_ps(0.0, 5.0, 2.0, 7.0));
+        assert_eq_m128(_mm_blendv_ps(a, b, _mm_set1_ps(-0.0)), b);
+
+        let a = _mm_setr_pd(0.0, 1.0);
+        let b = _mm_setr_pd(2.0, 3.0);
+        let mask = _mm_setr_pd(-0.0, 0.0);
+        assert_eq_m128d(_mm_blendv_pd(a, b, mask), _mm_setr_pd(2.0, 1.0));
+        assert_eq_m128d(_mm_blendv_pd(a, b, _mm_set1_pd(-1.0)), b);
+    }
+    test_mm_blendv_ps_pd();
+
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn test_mm_blend_imm() {
+        let a = _mm_setr_epi16(0, 1, 2, 3, 4, 5, 6, 7);
+        let b = _mm_setr_epi16(8, 9, 10, 11, 12, 13, 14, 15);
+        let e = _mm_setr_epi16(0, 9, 2, 11, 4, 13, 6, 15);
+        assert_eq_m128i(_mm_blend_epi16::<0b1010_1010>(a, b), e);
+        assert_eq_m128i(pblendw(a, b, 0b1010_1010), e);
+        assert_eq_m128i(pblendw(a, b, 0b1111_1111), b);
+
+        let a = _mm_setr_ps(0.0, 1.0, 2.0, 3.0);
+        let b = _mm_setr_ps(4.0, 5.0, 6.0, 7.0);
+        assert_eq_m128(blendps(a, b, 0b1010), _mm_setr_ps(0.0, 5.0, 2.0, 7.0));
+        assert_eq_m128(blendps(a, b, 0b1111), b);
+
+        let a = _mm_setr_pd(0.0, 1.0);
+        let b = _mm_setr_pd(2.0, 3.0);
+        assert_eq_m128d(blendpd(a, b, 0b10), _mm_setr_pd(0.0, 3.0));
+        assert_eq_m128d(blendpd(a, b, 0b11), b);
+    }
+    test_mm_blend_imm();
+
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn test_insert_extract() {
+        let a = _mm_setr_epi16(0, 1, 2, 3, 4, 5, 6, -1);
+        let e = _mm_setr_epi16(0, 1, 2, 42, 4, 5, 6, -1);
+        assert_eq_m128i(_mm_insert_epi16::<3>(a, 42), e);
+        assert_eq_m128i(pinsrw(a, 42, 3), e);
+        // The index wraps around, and the value is truncated.
+        assert_eq_m128i(pinsrw(a, 0x1_002a, 11), e);
+
+        // The element is zero-extended.
+        assert_eq!(_mm_extract_epi16::<7>(a), 0xffff);
+        assert_eq!(pextrw(a, 7), 0xffff);
+        assert_eq!(pextrw(a, 15), 0xffff);
+        assert_eq!(pextrw(a, 9), 1);
+
+        let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, -1);
+        assert_eq!(pextrb(a, 15), 0xff);
+        assert_eq!(pextrb(a, 17), 1);
+        let e = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 42, -1);
+        assert_eq_m128i(_mm_insert_epi8::<14>(a, 42), e);
+
+        let a = _mm_setr_epi32(0, 1, 2, -1);
+        assert_eq!(pextrd(a, 3), -1);
+        assert_eq!(pextrd(a, 6), 2);
+        assert_eq_m128i(_mm_insert_epi32::<1>(a, 42), _mm_setr_epi32(0, 42, 2, -1));
+
+        // Float elements are extracted as their bit pattern.
+        let a = _mm_setr_ps(1.5, -2.25, f32::NAN, -0.0);
+        assert_eq!(_mm_extract_ps::<1>(a), (-2.25f32).to_bits() as i32);
+        assert_eq!(extractps(a, 1), (-2.25f32).to_bits() as i32);
+        assert_eq!(extractps(a, 2), f32::NAN.to_bits() as i32);
+        assert_eq!(extractps(a, 7), (-0.0f32).to_bits() as i32);
+    }
+    test_insert_extract();
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse41.pblendvb"]
+    fn pblendvb(a: __m128i, b: __m128i, mask: __m128i) -> __m128i;
+    #[link_name = "llvm.x86.sse41.pblendw"]
+    fn pblendw(a: __m128i, b: __m128i, imm8: u8) -> __m128i;
+    #[link_name = "llvm.x86.sse41.blendps"]
+    fn blendps(a: __m128, b: __m128, imm8: u8) -> __m128;
+    #[link_name = "llvm.x86.sse41.blendpd"]
+    fn blendpd(a: __m128d, b: __m128d, imm8: u8) -> __m128d;
+    #[link_name = "llvm.x86.sse2.pinsrw"]
+    fn pinsrw(a: __m128i, i: i32, imm8: i32) -> __m128i;
+    #[link_name = "llvm.x86.sse2.pextrw"]
+    fn pextrw(a: __m128i, imm8: i32) -> i32;
+    #[link_name = "llvm.x86.sse41.pextrb"]
+    fn pextrb(a: __m128i, imm8: i32) -> i32;
+    #[link_name = "llvm.x86.sse41.extractps"]
+    fn extractps(a: __m128, imm8: i32) -> i32;
+    #[link_name = "llvm.x86.sse41.pextrd"]
+    fn pextrd(a: __m128i, imm8: i32) -> i32;
 }
 
 #[track_caller]
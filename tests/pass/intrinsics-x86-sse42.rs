@@ -0,0 +1,52 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse4.2
+
+#![feature(link_llvm_intrinsics, simd_ffi)]
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse4.2"));
+
+    unsafe {
+        test_sse42();
+    }
+}
+
+#[target_feature(enable = "sse4.2")]
+unsafe fn test_sse42() {
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn test_mm_cmpgt_epi64() {
+        let a = _mm_set_epi64x(i64::MIN, i64::MAX);
+        let b = _mm_set_epi64x(i64::MAX, i64::MIN);
+        assert_eq!(transmute::<_, [i64; 2]>(pcmpgtq(a, b)), [-1, 0]);
+        assert_eq!(transmute::<_, [i64; 2]>(_mm_cmpgt_epi64(a, b)), [-1, 0]);
+        assert_eq!(transmute::<_, [i64; 2]>(pcmpgtq(b, a)), [0, -1]);
+        assert_eq!(transmute::<_, [i64; 2]>(_mm_cmpgt_epi64(b, a)), [0, -1]);
+
+        // Equal elements do not compare greater, and -1 is less than 0.
+        let a = _mm_set_epi64x(-1, 7);
+        let b = _mm_set_epi64x(0, 7);
+        assert_eq!(transmute::<_, [i64; 2]>(pcmpgtq(a, b)), [0, 0]);
+        assert_eq!(transmute::<_, [i64; 2]>(pcmpgtq(b, a)), [0, -1]);
+    }
+    test_mm_cmpgt_epi64();
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    #[link_name = "llvm.x86.sse42.pcmpgtq"]
+    fn pcmpgtq(a: __m128i, b: __m128i) -> __m128i;
+}
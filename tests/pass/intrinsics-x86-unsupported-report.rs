@@ -0,0 +1,33 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the intrinsics below only exist on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -Zmiri-report-unsupported-intrinsics -Zmiri-panic-on-unsupported
+
+#![feature(link_llvm_intrinsics)]
+
+use std::panic;
+
+// Declared as `C-unwind` so that the panics raised for them can be caught.
+extern "C-unwind" {
+    #[link_name = "llvm.x86.rdtsc"]
+    fn rdtsc() -> u64;
+    #[link_name = "llvm.x86.xgetbv"]
+    fn xgetbv(xcr: u32) -> u64;
+}
+
+fn main() {
+    // Only the list of intrinsics at the end should be printed.
+    panic::set_hook(Box::new(|_| {}));
+
+    // Each unsupported intrinsic panics, so execution continues after catching that.
+    assert!(panic::catch_unwind(|| unsafe { xgetbv(0) }).is_err());
+    assert!(panic::catch_unwind(|| unsafe { rdtsc() }).is_err());
+    // Intrinsics are only listed once.
+    assert!(panic::catch_unwind(|| unsafe { rdtsc() }).is_err());
+}
@@ -43,6 +43,13 @@ mod x86 {
         assert_eq!(sbb(1, 1, 2), (1, u32::MAX - 1));
         assert_eq!(sbb(2, 1, 2), (1, u32::MAX - 1));
         assert_eq!(sbb(u8::MAX, 1, 2), (1, u32::MAX - 1));
+
+        // SAFETY: There are no safety requirements for calling `_bswap`.
+        unsafe {
+            assert_eq!(arch::_bswap(0x01020304), 0x04030201);
+            assert_eq!(arch::_bswap(0x04030201), 0x01020304);
+            assert_eq!(arch::_bswap(-0x100), 0x00FF_FFFF);
+        }
     }
 }
 
@@ -88,6 +95,13 @@ mod x86_64 {
         assert_eq!(sbb(1, 1, 2), (1, u64::MAX - 1));
         assert_eq!(sbb(2, 1, 2), (1, u64::MAX - 1));
         assert_eq!(sbb(u8::MAX, 1, 2), (1, u64::MAX - 1));
+
+        // SAFETY: There are no safety requirements for calling `_bswap64`.
+        unsafe {
+            assert_eq!(arch::_bswap64(0x0102030405060708), 0x0807060504030201);
+            assert_eq!(arch::_bswap64(0x01020304), 0x0403020100000000);
+            assert_eq!(arch::_bswap64(-0x100), 0x00FF_FFFF_FFFF_FFFF);
+        }
     }
 }
 